@@ -0,0 +1,74 @@
+use algorithm::divide_and_conquer::{
+    kth_max_of_array, kth_max_of_array2, select_nth, select_nth_deterministic, top_k,
+};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+/// Deterministic pseudo-random values, so every run measures the same inputs.
+fn values(len: usize) -> Vec<i32> {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 1_000_000) as i32
+        })
+        .collect()
+}
+
+fn kth_max(c: &mut Criterion) {
+    // kth_max_of_array is exponential in the worst case, so the inputs stay small
+    let mut group = c.benchmark_group("kth_max");
+    for len in [16, 20, 24] {
+        let arr = values(len);
+        let k = len / 2;
+        group.bench_with_input(BenchmarkId::new("kth_max_of_array", len), &arr, |b, arr| {
+            b.iter(|| kth_max_of_array(black_box(arr), k))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("kth_max_of_array2", len),
+            &arr,
+            |b, arr| b.iter(|| kth_max_of_array2(black_box(arr), k)),
+        );
+        group.bench_with_input(BenchmarkId::new("select_nth", len), &arr, |b, arr| {
+            b.iter(|| *select_nth(&mut black_box(arr).clone(), len - k))
+        });
+    }
+    group.finish();
+}
+
+fn select(c: &mut Criterion) {
+    let mut group = c.benchmark_group("select");
+    for len in [1_000, 100_000] {
+        let arr = values(len);
+        let n = len / 2;
+        if len <= 1_000 {
+            group.bench_with_input(
+                BenchmarkId::new("kth_max_of_array2", len),
+                &arr,
+                |b, arr| b.iter(|| kth_max_of_array2(black_box(arr), len - n)),
+            );
+        }
+        group.bench_with_input(BenchmarkId::new("select_nth", len), &arr, |b, arr| {
+            b.iter(|| *select_nth(&mut black_box(arr).clone(), n))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("select_nth_deterministic", len),
+            &arr,
+            |b, arr| b.iter(|| *select_nth_deterministic(&mut black_box(arr).clone(), n)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("slice::select_nth_unstable", len),
+            &arr,
+            |b, arr| b.iter(|| *black_box(arr).clone().select_nth_unstable(n).1),
+        );
+        group.bench_with_input(BenchmarkId::new("top_k", len), &arr, |b, arr| {
+            b.iter(|| top_k(black_box(arr), 10))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, kth_max, select);
+criterion_main!(benches);
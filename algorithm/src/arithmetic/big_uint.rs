@@ -0,0 +1,432 @@
+use super::multiply_exact;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul};
+use std::str::FromStr;
+
+/// Below this many limbs in the shorter factor, schoolbook multiplication beats Karatsuba.
+const KARATSUBA_THRESHOLD: usize = 32;
+/// From this many limbs in the shorter factor, multiplying through NTTs beats Karatsuba.
+const NTT_THRESHOLD: usize = 1 << 16;
+/// The most limbs in the shorter factor and in both factors that the NTTs can multiply, from
+/// the bounds of `multiply_exact` on their 16-bit digits.
+const NTT_MAX_SHORTER: usize = 1 << 20;
+const NTT_MAX_TOTAL: usize = 1 << 22;
+/// Decimal text is converted nine digits at a time.
+const DECIMAL_BASE: u32 = 1_000_000_000;
+const DECIMAL_DIGITS: usize = 9;
+
+/// An arbitrarily large unsigned integer, as base 2^32 limbs, least significant first.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct BigUint {
+    /// No most significant zero limbs, so zero has none at all
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn from_limbs(limbs: Vec<u32>) -> Self {
+        let mut number = Self { limbs };
+        number.normalize();
+        number
+    }
+
+    pub fn limbs(&self) -> &[u32] {
+        &self.limbs
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Multiplies every limb by every other: O(n·m).
+    pub fn mul_schoolbook(&self, other: &Self) -> Self {
+        Self::from_limbs(schoolbook(&self.limbs, &other.limbs))
+    }
+
+    /// Karatsuba multiplication: splitting both factors in halves, three half-size products
+    /// give the whole one, so it takes O(n^1.585).
+    pub fn mul_karatsuba(&self, other: &Self) -> Self {
+        Self::from_limbs(karatsuba(&self.limbs, &other.limbs))
+    }
+
+    /// Multiplication as a convolution of 16-bit digits through NTTs, in O(n log n). Panics
+    /// past 2^20 limbs in the shorter factor or 2^22 in both.
+    pub fn mul_ntt(&self, other: &Self) -> Self {
+        Self::from_limbs(ntt(&self.limbs, &other.limbs))
+    }
+
+    /// self * factor + addend, for small factors and addends.
+    fn mul_add_small(&mut self, factor: u32, addend: u32) {
+        let mut carry = addend as u64;
+        for limb in &mut self.limbs {
+            let value = *limb as u64 * factor as u64 + carry;
+            *limb = value as u32;
+            carry = value >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+        self.normalize();
+    }
+
+    /// Divides by a small divisor in place, returning the remainder.
+    fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let value = remainder << 32 | *limb as u64;
+            *limb = (value / divisor as u64) as u32;
+            remainder = value % divisor as u64;
+        }
+        self.normalize();
+        remainder as u32
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        Self::from_limbs(vec![value as u32, (value >> 32) as u32])
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        BigUint::from_limbs(add(&self.limbs, &other.limbs))
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: BigUint) -> BigUint {
+        &self + &other
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    /// Picks schoolbook, Karatsuba or NTT multiplication by the size of the shorter factor.
+    fn mul(self, other: &BigUint) -> BigUint {
+        let shorter = self.limbs.len().min(other.limbs.len());
+        let total = self.limbs.len() + other.limbs.len();
+        if shorter < KARATSUBA_THRESHOLD {
+            self.mul_schoolbook(other)
+        } else if !(NTT_THRESHOLD..NTT_MAX_SHORTER).contains(&shorter) || total > NTT_MAX_TOTAL {
+            self.mul_karatsuba(other)
+        } else {
+            self.mul_ntt(other)
+        }
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: BigUint) -> BigUint {
+        &self * &other
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseBigUintError {
+    Empty,
+    InvalidDigit,
+}
+
+impl fmt::Display for ParseBigUintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseBigUintError::Empty => write!(f, "cannot parse integer from empty string"),
+            ParseBigUintError::InvalidDigit => write!(f, "invalid digit found in string"),
+        }
+    }
+}
+
+impl std::error::Error for ParseBigUintError {}
+
+impl FromStr for BigUint {
+    type Err = ParseBigUintError;
+
+    /// Parses decimal digits, in O(n^2).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseBigUintError::Empty);
+        }
+        if !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigUintError::InvalidDigit);
+        }
+        let mut number = BigUint::zero();
+        // A shorter first chunk leaves the rest exactly nine digits long
+        let first = match s.len() % DECIMAL_DIGITS {
+            0 => DECIMAL_DIGITS,
+            len => len,
+        };
+        let mut start = 0;
+        let mut end = first;
+        while start < s.len() {
+            let chunk: u32 = s[start..end].parse().unwrap();
+            number.mul_add_small(10u32.pow((end - start) as u32), chunk);
+            (start, end) = (end, end + DECIMAL_DIGITS);
+        }
+        Ok(number)
+    }
+}
+
+impl fmt::Display for BigUint {
+    /// Formats as decimal digits, in O(n^2).
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut rest = self.clone();
+        let mut chunks = Vec::new();
+        while !rest.is_zero() {
+            chunks.push(rest.div_rem_small(DECIMAL_BASE));
+        }
+        let mut text = chunks.last().map_or("0".to_string(), u32::to_string);
+        for chunk in chunks.iter().rev().skip(1) {
+            text += &format!("{:09}", chunk);
+        }
+        f.pad_integral(true, "", &text)
+    }
+}
+
+fn schoolbook(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let value = product[i + j] as u64 + x as u64 * y as u64 + carry;
+            product[i + j] = value as u32;
+            carry = value >> 32;
+        }
+        // Nothing has been written this far up yet
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+/// The product of `a` and `b`, in `a.len() + b.len()` limbs.
+fn karatsuba(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+    if b.len() < KARATSUBA_THRESHOLD {
+        return schoolbook(a, b);
+    }
+    let mid = a.len() / 2;
+    let mut product = vec![0u32; a.len() + b.len()];
+    let (a0, a1) = a.split_at(mid);
+    if b.len() <= mid {
+        // Too unbalanced to split b: a0·b + a1·b·B^mid
+        add_shifted(&mut product, &karatsuba(a0, b), 0);
+        add_shifted(&mut product, &karatsuba(a1, b), mid);
+        return product;
+    }
+
+    // (a0 + a1·B^mid)(b0 + b1·B^mid) = z0 + z1·B^mid + z2·B^2mid, with
+    // z1 = (a0 + a1)(b0 + b1) - z0 - z2
+    let (b0, b1) = b.split_at(mid);
+    let z0 = karatsuba(a0, b0);
+    let z2 = karatsuba(a1, b1);
+    let mut z1 = karatsuba(&add(a0, a1), &add(b0, b1));
+    sub_assign(&mut z1, &z0);
+    sub_assign(&mut z1, &z2);
+    add_shifted(&mut product, &z0, 0);
+    add_shifted(&mut product, &z1, mid);
+    add_shifted(&mut product, &z2, 2 * mid);
+    product
+}
+
+fn ntt(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let digits = |limbs: &[u32]| -> Vec<u32> {
+        limbs
+            .iter()
+            .flat_map(|&limb| [limb & 0xffff, limb >> 16])
+            .collect()
+    };
+    let coefficients = multiply_exact(&digits(a), &digits(b));
+
+    let mut product = Vec::with_capacity(a.len() + b.len());
+    let mut carry = 0u128;
+    let mut low = None;
+    for coefficient in coefficients.into_iter().chain(std::iter::repeat_n(0, 8)) {
+        let value = coefficient + carry;
+        let digit = (value & 0xffff) as u32;
+        carry = value >> 16;
+        match low.take() {
+            None => low = Some(digit),
+            Some(low) => product.push(low | digit << 16),
+        }
+    }
+    product.extend(low);
+    debug_assert_eq!(carry, 0);
+    product
+}
+
+/// a + b, in one limb more than the longer of them.
+fn add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+    let mut sum = Vec::with_capacity(a.len() + 1);
+    sum.extend_from_slice(a);
+    sum.push(0);
+    add_shifted(&mut sum, b, 0);
+    sum
+}
+
+/// Adds `x · B^shift` into `acc`, which must have room for the sum.
+fn add_shifted(acc: &mut [u32], x: &[u32], shift: usize) {
+    let mut carry = 0u64;
+    let mut i = shift;
+    for &limb in x {
+        if i == acc.len() {
+            // Only most significant zeros can be past the end
+            debug_assert!(limb == 0 && carry == 0);
+            return;
+        }
+        let value = acc[i] as u64 + limb as u64 + carry;
+        acc[i] = value as u32;
+        carry = value >> 32;
+        i += 1;
+    }
+    while carry > 0 {
+        let value = acc[i] as u64 + carry;
+        acc[i] = value as u32;
+        carry = value >> 32;
+        i += 1;
+    }
+}
+
+/// Subtracts `x` from `acc`, which must not be smaller.
+fn sub_assign(acc: &mut [u32], x: &[u32]) {
+    let mut borrow = 0i64;
+    let mut i = 0;
+    while i < x.len() || borrow != 0 {
+        let value = acc[i] as i64 - x.get(i).copied().unwrap_or(0) as i64 - borrow;
+        acc[i] = value.rem_euclid(1 << 32) as u32;
+        borrow = (value < 0) as i64;
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random(rng: &mut StdRng, limbs: usize) -> BigUint {
+        BigUint::from_limbs((0..limbs).map(|_| rng.random()).collect())
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        for text in ["0", "1", "999999999", "1000000000", "18446744073709551616"] {
+            assert_eq!(text.parse::<BigUint>().unwrap().to_string(), text);
+        }
+        assert_eq!("000123".parse::<BigUint>().unwrap().to_string(), "123");
+        assert_eq!("0000".parse::<BigUint>().unwrap(), BigUint::zero());
+        assert_eq!("".parse::<BigUint>(), Err(ParseBigUintError::Empty));
+        assert_eq!(
+            "12a".parse::<BigUint>(),
+            Err(ParseBigUintError::InvalidDigit)
+        );
+        assert_eq!(
+            "-1".parse::<BigUint>(),
+            Err(ParseBigUintError::InvalidDigit)
+        );
+        assert_eq!(format!("{:>6}", BigUint::from(42)), "    42");
+
+        let mut rng = StdRng::seed_from_u64(33);
+        for len in 1..200 {
+            let digits: String = (0..len)
+                .map(|i| {
+                    let first = if i == 0 { 1 } else { 0 };
+                    char::from(b'0' + rng.random_range(first..10))
+                })
+                .collect();
+            assert_eq!(digits.parse::<BigUint>().unwrap().to_string(), digits);
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_matches_u128() {
+        let mut rng = StdRng::seed_from_u64(34);
+        for _ in 0..1000 {
+            let (a, b): (u64, u64) = (rng.random(), rng.random());
+            let (x, y) = (BigUint::from(a), BigUint::from(b));
+            assert_eq!((&x * &y).to_string(), (a as u128 * b as u128).to_string());
+            assert_eq!((&x + &y).to_string(), (a as u128 + b as u128).to_string());
+            assert_eq!(x.cmp(&y), a.cmp(&b));
+        }
+    }
+
+    #[test]
+    fn test_factorial() {
+        let mut factorial = BigUint::from(1);
+        for i in 1..=50 {
+            factorial = factorial * BigUint::from(i);
+        }
+        assert_eq!(
+            factorial.to_string(),
+            "30414093201713378043612608166064768844377641568960512000000000000"
+        );
+    }
+
+    #[test]
+    fn test_multiplication_strategies_agree() {
+        let mut rng = StdRng::seed_from_u64(35);
+        let sizes = [
+            (0, 5),
+            (1, 1),
+            (31, 33),
+            (32, 32),
+            (100, 100),
+            (777, 1001),
+            (3000, 40),
+        ];
+        for (m, n) in sizes {
+            let (a, b) = (random(&mut rng, m), random(&mut rng, n));
+            let expected = a.mul_schoolbook(&b);
+            assert_eq!(a.mul_karatsuba(&b), expected, "{} by {}", m, n);
+            assert_eq!(a.mul_ntt(&b), expected, "{} by {}", m, n);
+            assert_eq!(&a * &b, expected, "{} by {}", m, n);
+        }
+
+        // Every limb at its maximum, for the most carrying
+        let max = BigUint::from_limbs(vec![u32::MAX; 2500]);
+        let expected = max.mul_schoolbook(&max);
+        assert_eq!(max.mul_karatsuba(&max), expected);
+        assert_eq!(&max * &max, expected);
+    }
+
+    #[test]
+    fn test_powers_of_ten() {
+        let ten_to = |n: usize| format!("1{}", "0".repeat(n)).parse::<BigUint>().unwrap();
+        let (a, b) = (ten_to(30_000), ten_to(25_000));
+        assert_eq!(&a * &b, ten_to(55_000));
+        assert_eq!(a.mul_ntt(&b), ten_to(55_000));
+    }
+}
@@ -0,0 +1,364 @@
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+/// Below this many coefficients in the shorter polynomial, schoolbook multiplication beats the
+/// transforms.
+const SCHOOLBOOK_THRESHOLD: usize = 64;
+
+/// 119 · 2^23 + 1, so transforms of up to 2^23 points exist modulo it.
+pub const NTT_MODULUS: u64 = 998_244_353;
+
+/// NTT-friendly primes p = c · 2^k + 1 with primitive root 3, whose product bounds the exact
+/// convolutions of `multiply_exact`.
+const PRIMES: [u64; 3] = [998_244_353, 167_772_161, 469_762_049];
+const ROOT: u64 = 3;
+
+/// The most points of a transform modulo every one of `PRIMES`, set by the first.
+const MAX_POINTS: usize = 1 << 23;
+
+/// The product of two polynomials given by their coefficients, lowest degree first, by
+/// multiplying every pair of coefficients: O(n·m).
+pub fn schoolbook_multiply<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<Output = T>,
+{
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![T::default(); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            product[i + j] = product[i + j] + x * y;
+        }
+    }
+    product
+}
+
+/// The product of two real polynomials through the fast Fourier transform, in O(n log n).
+/// The coefficients carry floating-point error, growing with their size and the degree.
+pub fn fft_multiply(a: &[f64], b: &[f64]) -> Vec<f64> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let len = a.len() + b.len() - 1;
+    let n = len.next_power_of_two();
+    let complex = |values: &[f64]| {
+        let mut points = vec![Complex::default(); n];
+        for (point, &re) in points.iter_mut().zip(values) {
+            point.re = re;
+        }
+        points
+    };
+    let (mut fa, mut fb) = (complex(a), complex(b));
+    fft(&mut fa, false);
+    fft(&mut fb, false);
+    for (x, &y) in fa.iter_mut().zip(&fb) {
+        *x = *x * y;
+    }
+    fft(&mut fa, true);
+    fa.into_iter().take(len).map(|x| x.re).collect()
+}
+
+/// The product of two polynomials with coefficients modulo `NTT_MODULUS`, through the
+/// number-theoretic transform: the FFT over integers mod a prime, so it is exact.
+/// Panics if the product has more than 2^23 coefficients, the longest transform there is
+/// modulo `NTT_MODULUS`.
+pub fn ntt_multiply(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let reduce = |values: &[u64]| values.iter().map(|&x| x % NTT_MODULUS).collect::<Vec<_>>();
+    ntt_multiply_mod(&reduce(a), &reduce(b), NTT_MODULUS)
+}
+
+/// The exact product of two polynomials with 32-bit coefficients: schoolbook for short ones,
+/// otherwise NTTs modulo three primes whose results are combined by the Chinese remainder
+/// theorem. Their product exceeds 2^85, which bounds coefficients while the shorter operand
+/// has fewer than 2^21 of them; panics if it has more. A longer operand whose product would
+/// not fit one transform is multiplied a block at a time.
+pub fn multiply_exact(a: &[u32], b: &[u32]) -> Vec<u128> {
+    multiply_exact_in_blocks(a, b, MAX_POINTS)
+}
+
+/// `multiply_exact` with transforms of at most `max_points` points.
+fn multiply_exact_in_blocks(a: &[u32], b: &[u32], max_points: usize) -> Vec<u128> {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if short.len() < SCHOOLBOOK_THRESHOLD {
+        let widen = |values: &[u32]| values.iter().map(|&x| x as u128).collect::<Vec<_>>();
+        return schoolbook_multiply(&widen(a), &widen(b));
+    }
+    assert!(short.len() < 1 << 21, "coefficients could overflow");
+
+    let len = short.len() + long.len() - 1;
+    if len > max_points {
+        // The blocks' products overlap, and their sums are the coefficients of the whole,
+        // so they stay within the same bound
+        let block = max_points + 1 - short.len();
+        let mut product = vec![0; len];
+        for (i, chunk) in long.chunks(block).enumerate() {
+            let partial = multiply_exact_in_blocks(short, chunk, max_points);
+            for (sum, x) in product[i * block..].iter_mut().zip(partial) {
+                *sum += x;
+            }
+        }
+        return product;
+    }
+
+    let residues: Vec<Vec<u64>> = PRIMES
+        .iter()
+        .map(|&p| {
+            let reduce = |values: &[u32]| values.iter().map(|&x| x as u64 % p).collect::<Vec<_>>();
+            ntt_multiply_mod(&reduce(a), &reduce(b), p)
+        })
+        .collect();
+
+    // Garner's algorithm: x = x0 + x1·p0 + x2·p0·p1 with each xi below pi
+    let [p0, p1, p2] = PRIMES;
+    let p0_inverse = pow_mod(p0 % p1, p1 - 2, p1);
+    let p0p1_inverse = pow_mod(p0 % p2 * (p1 % p2) % p2, p2 - 2, p2);
+    (0..residues[0].len())
+        .map(|i| {
+            let (r0, r1, r2) = (residues[0][i], residues[1][i], residues[2][i]);
+            let x0 = r0;
+            let x1 = (r1 + p1 - x0 % p1) % p1 * p0_inverse % p1;
+            let partial = (x0 % p2 + x1 % p2 * (p0 % p2)) % p2;
+            let x2 = (r2 + p2 - partial) % p2 * p0p1_inverse % p2;
+            x0 as u128 + x1 as u128 * p0 as u128 + x2 as u128 * p0 as u128 * p1 as u128
+        })
+        .collect()
+}
+
+/// `a` times `b` modulo the prime `modulus`, whose coefficients are already reduced.
+fn ntt_multiply_mod(a: &[u64], b: &[u64], modulus: u64) -> Vec<u64> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let len = a.len() + b.len() - 1;
+    let n = len.next_power_of_two();
+    assert!(
+        (modulus - 1).is_multiple_of(n as u64),
+        "no transform of {} points modulo {}",
+        n,
+        modulus
+    );
+    let (mut fa, mut fb) = (a.to_vec(), b.to_vec());
+    fa.resize(n, 0);
+    fb.resize(n, 0);
+    ntt(&mut fa, false, modulus);
+    ntt(&mut fb, false, modulus);
+    for (x, &y) in fa.iter_mut().zip(&fb) {
+        *x = *x * y % modulus;
+    }
+    ntt(&mut fa, true, modulus);
+    fa.truncate(len);
+    fa
+}
+
+/// In-place iterative radix-2 transform of a power-of-two number of points, or its inverse.
+fn ntt(values: &mut [u64], invert: bool, modulus: u64) {
+    let n = values.len();
+    bit_reverse(values);
+    let mut len = 2;
+    while len <= n {
+        let mut root = pow_mod(ROOT, (modulus - 1) / len as u64, modulus);
+        if invert {
+            root = pow_mod(root, modulus - 2, modulus);
+        }
+        for chunk in values.chunks_mut(len) {
+            let (low, high) = chunk.split_at_mut(len / 2);
+            let mut w = 1;
+            for (u, v) in low.iter_mut().zip(high) {
+                let t = *v * w % modulus;
+                (*u, *v) = ((*u + t) % modulus, (*u + modulus - t) % modulus);
+                w = w * root % modulus;
+            }
+        }
+        len *= 2;
+    }
+    if invert {
+        let n_inverse = pow_mod(n as u64, modulus - 2, modulus);
+        for x in values.iter_mut() {
+            *x = *x * n_inverse % modulus;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex {
+            re: self.re - other.re,
+            im: self.im - other.im,
+        }
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+}
+
+/// `ntt` over the complex numbers. The roots of unity are each computed directly rather than
+/// by repeated multiplication, which would accumulate rounding error.
+fn fft(values: &mut [Complex], invert: bool) {
+    let n = values.len();
+    bit_reverse(values);
+    let sign = if invert { -1.0 } else { 1.0 };
+    let roots: Vec<Complex> = (0..n / 2)
+        .map(|k| {
+            let angle = sign * 2.0 * PI * k as f64 / n as f64;
+            Complex {
+                re: angle.cos(),
+                im: angle.sin(),
+            }
+        })
+        .collect();
+    let mut len = 2;
+    while len <= n {
+        let step = n / len;
+        for chunk in values.chunks_mut(len) {
+            let (low, high) = chunk.split_at_mut(len / 2);
+            for (k, (u, v)) in low.iter_mut().zip(high).enumerate() {
+                let t = *v * roots[k * step];
+                (*u, *v) = (*u + t, *u - t);
+            }
+        }
+        len *= 2;
+    }
+    if invert {
+        for x in values.iter_mut() {
+            x.re /= n as f64;
+            x.im /= n as f64;
+        }
+    }
+}
+
+/// Puts each element at the index with its index's bits reversed, the order in which the
+/// iterative transforms combine them.
+fn bit_reverse<T>(values: &mut [T]) {
+    let n = values.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+fn pow_mod(mut base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut result = 1;
+    base %= modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_schoolbook_multiply() {
+        // (1 + 2x)(3 + x + x^2) = 3 + 7x + 3x^2 + 2x^3
+        assert_eq!(schoolbook_multiply(&[1, 2], &[3, 1, 1]), [3, 7, 3, 2]);
+        assert_eq!(schoolbook_multiply::<i32>(&[], &[1]), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_fft_multiply() {
+        let mut rng = StdRng::seed_from_u64(30);
+        for (m, n) in [(1, 1), (2, 3), (100, 1), (500, 700)] {
+            let a: Vec<i64> = (0..m).map(|_| rng.random_range(-1000..1000)).collect();
+            let b: Vec<i64> = (0..n).map(|_| rng.random_range(-1000..1000)).collect();
+            let float = |values: &[i64]| values.iter().map(|&x| x as f64).collect::<Vec<_>>();
+            let product: Vec<i64> = fft_multiply(&float(&a), &float(&b))
+                .into_iter()
+                .map(|x| x.round() as i64)
+                .collect();
+            assert_eq!(product, schoolbook_multiply(&a, &b));
+        }
+        assert_eq!(fft_multiply(&[], &[1.0]), Vec::<f64>::new());
+    }
+
+    #[test]
+    fn test_ntt_multiply() {
+        let mut rng = StdRng::seed_from_u64(31);
+        for (m, n) in [(1, 1), (3, 2), (64, 200), (1000, 1000)] {
+            let a: Vec<u64> = (0..m).map(|_| rng.random_range(0..NTT_MODULUS)).collect();
+            let b: Vec<u64> = (0..n).map(|_| rng.random_range(0..NTT_MODULUS)).collect();
+            let wide = |values: &[u64]| values.iter().map(|&x| x as u128).collect::<Vec<_>>();
+            let expected: Vec<u64> = schoolbook_multiply(&wide(&a), &wide(&b))
+                .into_iter()
+                .map(|x| (x % NTT_MODULUS as u128) as u64)
+                .collect();
+            assert_eq!(ntt_multiply(&a, &b), expected);
+        }
+    }
+
+    #[test]
+    fn test_multiply_exact() {
+        let mut rng = StdRng::seed_from_u64(32);
+        for (m, n) in [(5, 5), (63, 1000), (64, 64), (777, 1500)] {
+            let a: Vec<u32> = (0..m).map(|_| rng.random()).collect();
+            let b: Vec<u32> = (0..n).map(|_| rng.random()).collect();
+            let wide = |values: &[u32]| values.iter().map(|&x| x as u128).collect::<Vec<_>>();
+            assert_eq!(
+                multiply_exact(&a, &b),
+                schoolbook_multiply(&wide(&a), &wide(&b))
+            );
+        }
+        // The largest coefficients there can be, past any single prime
+        let max = vec![u32::MAX; 4096];
+        let product = multiply_exact(&max, &max);
+        assert_eq!(product[4095], 4096 * (u32::MAX as u128).pow(2));
+    }
+
+    #[test]
+    fn test_multiply_exact_in_blocks() {
+        // Small transforms stand in for the 2^23 points of the real limit
+        let mut rng = StdRng::seed_from_u64(33);
+        for (m, n) in [(64, 2000), (1000, 200), (255, 256), (100, 157)] {
+            let a: Vec<u32> = (0..m).map(|_| rng.random()).collect();
+            let b: Vec<u32> = (0..n).map(|_| rng.random()).collect();
+            let wide = |values: &[u32]| values.iter().map(|&x| x as u128).collect::<Vec<_>>();
+            assert_eq!(
+                multiply_exact_in_blocks(&a, &b, 256),
+                schoolbook_multiply(&wide(&a), &wide(&b))
+            );
+        }
+    }
+}
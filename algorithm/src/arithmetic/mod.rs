@@ -0,0 +1,5 @@
+mod convolution;
+pub use convolution::*;
+
+mod big_uint;
+pub use big_uint::*;
@@ -0,0 +1,161 @@
+use algorithm::sort::{ExternalSort, RecordFormat};
+use clap::Parser;
+use std::error::Error;
+use std::fs::File;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Sorts a file larger than memory by a key column, through sorted runs in temporary files
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    input: PathBuf,
+    output: PathBuf,
+
+    #[arg(
+        short,
+        long,
+        help = "1-based column of each line to sort by; whole records when absent",
+        value_name = "N"
+    )]
+    key: Option<NonZeroUsize>,
+
+    #[arg(short, long, default_value_t = ',', help = "Column separator")]
+    delimiter: char,
+
+    #[arg(
+        short,
+        long,
+        help = "Compare keys as numbers; keys that are not come first"
+    )]
+    numeric: bool,
+
+    #[arg(short, long)]
+    reverse: bool,
+
+    #[arg(
+        long,
+        help = "Sort fixed-width binary records of this many bytes instead of lines",
+        value_name = "BYTES"
+    )]
+    record_size: Option<NonZeroUsize>,
+
+    #[arg(
+        long,
+        help = "Byte range START:LEN of each binary record to sort by",
+        value_name = "START:LEN",
+        requires = "record_size"
+    )]
+    key_bytes: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        default_value_t = 64,
+        help = "Memory budget in MiB",
+        value_name = "MIB"
+    )]
+    memory: usize,
+
+    #[arg(
+        long,
+        help = "Directory for the sorted runs; the system one by default"
+    )]
+    temp_dir: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    let format = match cli.record_size {
+        Some(size) => RecordFormat::Fixed(size.get()),
+        None => RecordFormat::Lines,
+    };
+    let mut sorter = ExternalSort::new(format);
+    sorter.memory_budget = cli
+        .memory
+        .checked_mul(1 << 20)
+        .ok_or("--memory is too large")?;
+    if let Some(dir) = &cli.temp_dir {
+        sorter.temp_dir = dir.clone();
+    }
+
+    let key_range = match &cli.key_bytes {
+        Some(range) => {
+            let (start, len) = range.split_once(':').ok_or("--key-bytes takes START:LEN")?;
+            let start: usize = start.parse()?;
+            let end = start
+                .checked_add(len.parse()?)
+                .ok_or("--key-bytes range is too large")?;
+            Some(start..end)
+        }
+        None => None,
+    };
+    let key = Key {
+        range: key_range,
+        column: cli.key.map(NonZeroUsize::get),
+        delimiter: cli.delimiter.to_string().into_bytes(),
+    };
+    let compare = |a: &[u8], b: &[u8]| {
+        let (a, b) = (key.of(a), key.of(b));
+        let ordering = if cli.numeric {
+            match (number(a), number(b)) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            }
+        } else {
+            a.cmp(b)
+        };
+        if cli.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    };
+
+    let summary = sorter.sort_by(File::open(&cli.input)?, File::create(&cli.output)?, compare)?;
+    eprintln!(
+        "Sorted {} records through {} runs and {} merge passes",
+        summary.records, summary.runs, summary.merge_passes
+    );
+    Ok(())
+}
+
+struct Key {
+    range: Option<Range<usize>>,
+    column: Option<usize>,
+    delimiter: Vec<u8>,
+}
+
+impl Key {
+    fn of<'a>(&self, record: &'a [u8]) -> &'a [u8] {
+        if let Some(range) = &self.range {
+            return &record[range.start.min(record.len())..range.end.min(record.len())];
+        }
+        match self.column {
+            Some(column) => column_of(record, &self.delimiter, column),
+            None => record,
+        }
+    }
+}
+
+/// The `column`-th field of `record`, empty when there are fewer.
+fn column_of<'a>(record: &'a [u8], delimiter: &[u8], column: usize) -> &'a [u8] {
+    let mut rest = record;
+    for _ in 1..column {
+        match rest.windows(delimiter.len()).position(|w| w == delimiter) {
+            Some(i) => rest = &rest[i + delimiter.len()..],
+            None => return &[],
+        }
+    }
+    match rest.windows(delimiter.len()).position(|w| w == delimiter) {
+        Some(i) => &rest[..i],
+        None => rest,
+    }
+}
+
+// None for keys that are not numbers, which orders them first
+fn number(key: &[u8]) -> Option<f64> {
+    std::str::from_utf8(key).ok()?.trim().parse().ok()
+}
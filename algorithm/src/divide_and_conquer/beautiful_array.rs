@@ -1,10 +1,14 @@
 use std::vec;
 
+/// A beautiful arrangement of 1..=n. Up to n = 6 it is found by inserting n into one for
+/// n - 1; from n = 7, where no place for n works, it is `beautiful_array_divide_and_conquer`,
+/// in O(n log n).
 pub fn beautiful_array(n: i32) -> Vec<i32> {
-    return match n {
+    match n {
         0 => vec![],
         1 => vec![1],
         2 => vec![1, 2],
+        7.. => beautiful_array_divide_and_conquer(n),
         _ => {
             let result = beautiful_array(n - 1);
             'f1: for i in 0..n - 1 {
@@ -21,9 +25,51 @@ pub fn beautiful_array(n: i32) -> Vec<i32> {
                 }
                 return result2;
             }
-            panic!("No beautiful array found for n = {}", n);
+            beautiful_array_divide_and_conquer(n)
         }
-    };
+    }
+}
+
+/// A beautiful arrangement of 1..=n for any n, in O(n log n). Odd numbers and even numbers
+/// never average to a number in the array, so a beautiful array of the odd ones followed by
+/// one of the even ones is beautiful. Both halves are beautiful arrays of smaller n mapped by
+/// x -> 2x - 1 and x -> 2x, which keep averages where they were.
+pub fn beautiful_array_divide_and_conquer(n: i32) -> Vec<i32> {
+    if n <= 1 {
+        return (1..=n).collect();
+    }
+    let odds = beautiful_array_divide_and_conquer((n + 1) / 2);
+    let evens = beautiful_array_divide_and_conquer(n / 2);
+    odds.into_iter()
+        .map(|x| 2 * x - 1)
+        .chain(evens.into_iter().map(|x| 2 * x))
+        .collect()
+}
+
+/// Whether `arr` is an arrangement of 1..=n in which no number lies between two others that
+/// it is the average of. O(n^2).
+pub fn is_beautiful(arr: &[i32]) -> bool {
+    let n = arr.len();
+    // position[x] is where x is in arr
+    let mut position = vec![usize::MAX; n + 1];
+    for (i, &x) in arr.iter().enumerate() {
+        match usize::try_from(x) {
+            Ok(x) if (1..=n).contains(&x) && position[x] == usize::MAX => position[x] = i,
+            _ => return false,
+        }
+    }
+    for i in 0..n {
+        for l in i + 2..n {
+            let sum = arr[i] + arr[l];
+            if sum % 2 == 0 {
+                let middle = position[(sum / 2) as usize];
+                if i < middle && middle < l {
+                    return false;
+                }
+            }
+        }
+    }
+    true
 }
 
 #[cfg(test)]
@@ -31,11 +77,79 @@ mod tests {
     use super::*;
     #[test]
     fn test_beautiful_array() {
-        assert_eq!(beautiful_array(0), vec![]);
+        assert_eq!(beautiful_array(0), Vec::<i32>::new());
         assert_eq!(beautiful_array(1), vec![1]);
         assert_eq!(beautiful_array(2), vec![1, 2]);
         println!("{:?}", beautiful_array(3));
         println!("{:?}", beautiful_array(4));
         println!("{:?}", beautiful_array(5));
     }
+
+    #[test]
+    fn test_is_beautiful() {
+        assert!(is_beautiful(&[]));
+        assert!(is_beautiful(&[2, 1, 4, 3]));
+        assert!(is_beautiful(&[3, 1, 2, 5, 4]));
+        assert!(!is_beautiful(&[1, 2, 3]));
+        assert!(!is_beautiful(&[1, 4, 3, 2]));
+        // Not arrangements of 1..=n
+        assert!(!is_beautiful(&[1, 1]));
+        assert!(!is_beautiful(&[0, 1]));
+        assert!(!is_beautiful(&[1, 3]));
+    }
+
+    #[test]
+    fn test_is_beautiful_matches_brute_force() {
+        fn brute_force(arr: &[i32]) -> bool {
+            let mut sorted = arr.to_vec();
+            sorted.sort();
+            sorted.iter().copied().eq(1..=arr.len() as i32)
+                && (0..arr.len()).all(|i| {
+                    (i + 1..arr.len())
+                        .all(|j| (j + 1..arr.len()).all(|k| arr[i] + arr[k] != 2 * arr[j]))
+                })
+        }
+        // Every arrangement of up to 6 numbers, by Heap's algorithm
+        for n in 0..=6 {
+            let mut arr: Vec<i32> = (1..=n).collect();
+            let mut counters = vec![0; n as usize];
+            assert_eq!(is_beautiful(&arr), brute_force(&arr));
+            let mut i = 0;
+            while i < arr.len() {
+                if counters[i] < i {
+                    arr.swap(if i % 2 == 0 { 0 } else { counters[i] }, i);
+                    assert_eq!(is_beautiful(&arr), brute_force(&arr), "{:?}", arr);
+                    counters[i] += 1;
+                    i = 0;
+                } else {
+                    counters[i] = 0;
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_beautiful_array_divide_and_conquer() {
+        assert_eq!(beautiful_array_divide_and_conquer(0), Vec::<i32>::new());
+        assert_eq!(beautiful_array_divide_and_conquer(4), [1, 3, 2, 4]);
+        assert_eq!(beautiful_array_divide_and_conquer(5), [1, 5, 3, 2, 4]);
+
+        // From n = 7 beautiful_array is the divide-and-conquer construction
+        for n in 0..=30 {
+            assert!(is_beautiful(&beautiful_array(n)), "{}", n);
+        }
+        assert_eq!(beautiful_array(1_000_000).len(), 1_000_000);
+        for n in 0..=500 {
+            assert!(
+                is_beautiful(&beautiful_array_divide_and_conquer(n)),
+                "{}",
+                n
+            );
+        }
+        assert_eq!(
+            beautiful_array_divide_and_conquer(1_000_000).len(),
+            1_000_000
+        );
+    }
 }
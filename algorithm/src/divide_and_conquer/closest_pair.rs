@@ -0,0 +1,197 @@
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Sub};
+
+/// The two closest of a set of points, as indices into it, `i < j`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ClosestPair<T> {
+    pub i: usize,
+    pub j: usize,
+    pub distance_squared: T,
+}
+
+/// The closest pair of points by divide and conquer, in O(n log n): the closest pair is in the
+/// left half, the right half, or a narrow strip across the middle, where each point needs
+/// comparing only with the few that follow it by y. `None` for fewer than two points.
+pub fn closest_pair<T>(points: &[(T, T)]) -> Option<ClosestPair<T>>
+where
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    if points.len() < 2 {
+        return None;
+    }
+    let mut indices: Vec<usize> = (0..points.len()).collect();
+    indices.sort_by(|&a, &b| {
+        let (a, b) = (points[a], points[b]);
+        compare(a.0, b.0).then(compare(a.1, b.1))
+    });
+    let mut buffer = Vec::with_capacity(points.len());
+    let best = _closest_pair(points, &mut indices, &mut buffer);
+    Some(ClosestPair {
+        i: best.i.min(best.j),
+        j: best.i.max(best.j),
+        distance_squared: best.distance_squared,
+    })
+}
+
+/// Finds the closest pair of `indices`, sorted by x on entry and by y on return.
+fn _closest_pair<T>(
+    points: &[(T, T)],
+    indices: &mut [usize],
+    buffer: &mut Vec<usize>,
+) -> ClosestPair<T>
+where
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let by_y = |&a: &usize, &b: &usize| compare(points[a].1, points[b].1);
+    if indices.len() <= 3 {
+        let mut best = pair(points, indices[0], indices[1]);
+        for (a, b) in [(0, 2), (1, 2)] {
+            if b < indices.len() {
+                best = closer(best, pair(points, indices[a], indices[b]));
+            }
+        }
+        indices.sort_by(by_y);
+        return best;
+    }
+
+    let mid = indices.len() / 2;
+    let mid_x = points[indices[mid]].0;
+    let left = _closest_pair(points, &mut indices[..mid], buffer);
+    let right = _closest_pair(points, &mut indices[mid..], buffer);
+    let mut best = closer(left, right);
+
+    // Merges the halves by y
+    buffer.clear();
+    let (mut i, mut j) = (0, mid);
+    while i < mid || j < indices.len() {
+        if j == indices.len() || (i < mid && by_y(&indices[i], &indices[j]) != Ordering::Greater) {
+            buffer.push(indices[i]);
+            i += 1;
+        } else {
+            buffer.push(indices[j]);
+            j += 1;
+        }
+    }
+    indices.copy_from_slice(buffer);
+
+    // Only points nearer the middle line than the best distance can do better
+    buffer.clear();
+    buffer.extend(indices.iter().copied().filter(|&k| {
+        let dx = distance(points[k].0, mid_x);
+        dx * dx < best.distance_squared
+    }));
+    for (a, &p) in buffer.iter().enumerate() {
+        for &q in &buffer[a + 1..] {
+            let dy = distance(points[q].1, points[p].1);
+            if dy * dy >= best.distance_squared {
+                break;
+            }
+            best = closer(best, pair(points, p, q));
+        }
+    }
+    best
+}
+
+fn pair<T>(points: &[(T, T)], i: usize, j: usize) -> ClosestPair<T>
+where
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    let dx = distance(points[i].0, points[j].0);
+    let dy = distance(points[i].1, points[j].1);
+    ClosestPair {
+        i,
+        j,
+        distance_squared: dx * dx + dy * dy,
+    }
+}
+
+fn closer<T: PartialOrd>(a: ClosestPair<T>, b: ClosestPair<T>) -> ClosestPair<T> {
+    if b.distance_squared < a.distance_squared {
+        b
+    } else {
+        a
+    }
+}
+
+/// |a - b|, without going below zero for unsigned types.
+fn distance<T: PartialOrd + Sub<Output = T>>(a: T, b: T) -> T {
+    if a < b { b - a } else { a - b }
+}
+
+fn compare<T: PartialOrd>(a: T, b: T) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn brute_force<T>(points: &[(T, T)]) -> Option<T>
+    where
+        T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+    {
+        let mut best: Option<T> = None;
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                let d = pair(points, i, j).distance_squared;
+                if best.is_none_or(|best| d < best) {
+                    best = Some(d);
+                }
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_closest_pair() {
+        let points = [(2, 3), (12, 30), (40, 50), (5, 1), (12, 10), (3, 4)];
+        assert_eq!(
+            closest_pair(&points),
+            Some(ClosestPair {
+                i: 0,
+                j: 5,
+                distance_squared: 2
+            })
+        );
+        assert_eq!(closest_pair(&[(1u32, 1u32)]), None);
+        assert_eq!(
+            closest_pair(&[(0.5, 0.0), (0.0, 0.0)])
+                .unwrap()
+                .distance_squared,
+            0.25
+        );
+    }
+
+    #[test]
+    fn test_closest_pair_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(25);
+        for len in 2..120 {
+            // Small coordinates give ties, duplicates and points on the middle line
+            let range = if len % 2 == 0 { 10 } else { 10_000 };
+            let points: Vec<(i64, i64)> = (0..len)
+                .map(|_| (rng.random_range(0..range), rng.random_range(0..range)))
+                .collect();
+            let found = closest_pair(&points).unwrap();
+            assert!(found.i < found.j);
+            assert_eq!(
+                pair(&points, found.i, found.j).distance_squared,
+                found.distance_squared
+            );
+            assert_eq!(
+                Some(found.distance_squared),
+                brute_force(&points),
+                "{:?}",
+                points
+            );
+        }
+    }
+
+    #[test]
+    fn test_closest_pair_on_a_line() {
+        let points: Vec<(u64, u64)> = (0..10_000).map(|i| (7, i * i)).collect();
+        let found = closest_pair(&points).unwrap();
+        assert_eq!((found.i, found.j, found.distance_squared), (0, 1, 1));
+    }
+}
@@ -1,34 +1,305 @@
-pub fn hanoi(n: usize, source: &str, target: &str, auxiliary: &str) {
-    if n == 0 {
-        return;
+/// The moves that take a tower of `n` disks from `source` to `target`, generated lazily from
+/// an explicit stack of the recursion, so only O(n) state is kept.
+pub fn hanoi<P: Copy>(n: usize, source: P, target: P, auxiliary: P) -> Hanoi<P> {
+    let mut stack = Vec::with_capacity(n);
+    if n > 0 {
+        stack.push(Frame {
+            n,
+            pegs: [source, target, auxiliary],
+            moved_above: false,
+        });
     }
-    if n == 1 {
-        println!("{} => {}", source, target);
-        return;
+    Hanoi { stack }
+}
+
+pub struct Hanoi<P> {
+    stack: Vec<Frame<P>>,
+}
+
+/// Moving `n` disks from pegs[0] to pegs[1] by way of pegs[2].
+struct Frame<P> {
+    n: usize,
+    pegs: [P; 3],
+    /// Whether the `n - 1` disks above have been moved out of the way yet
+    moved_above: bool,
+}
+
+impl<P: Copy> Iterator for Hanoi<P> {
+    type Item = (P, P);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            let [source, target, auxiliary] = frame.pegs;
+            let n = frame.n;
+            if n == 1 {
+                self.stack.pop();
+                return Some((source, target));
+            }
+            if !frame.moved_above {
+                frame.moved_above = true;
+                self.stack.push(Frame {
+                    n: n - 1,
+                    pegs: [source, auxiliary, target],
+                    moved_above: false,
+                });
+                continue;
+            }
+            // The largest disk moves, then the rest follow it
+            *frame = Frame {
+                n: n - 1,
+                pegs: [auxiliary, target, source],
+                moved_above: false,
+            };
+            return Some((source, target));
+        }
+    }
+}
+
+/// The same moves as `hanoi`, worked out from the move number alone: move m takes a disk
+/// from peg `(m & (m - 1)) % 3` to peg `((m | (m - 1)) + 1) % 3`. It keeps O(1) state,
+/// and skipping ahead with `nth` is O(1) too. Panics if `n` is over 127.
+pub fn hanoi_iterative<P: Copy>(n: usize, source: P, target: P, auxiliary: P) -> HanoiIterative<P> {
+    assert!(n < 128, "{} disks take more than u128::MAX moves", n);
+    // The formula moves the tower from peg 0 to peg 2 when n is odd, and to peg 1 when even
+    let pegs = if n % 2 == 1 {
+        [source, auxiliary, target]
+    } else {
+        [source, target, auxiliary]
+    };
+    HanoiIterative {
+        pegs,
+        next: 1,
+        end: 1u128 << n,
+    }
+}
+
+pub struct HanoiIterative<P> {
+    pegs: [P; 3],
+    next: u128,
+    end: u128,
+}
+
+impl<P: Copy> Iterator for HanoiIterative<P> {
+    type Item = (P, P);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let m = self.next;
+        self.next += 1;
+        let from = (m & (m - 1)) % 3;
+        let to = ((m | (m - 1)) + 1) % 3;
+        Some((self.pegs[from as usize], self.pegs[to as usize]))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.next = self.next.saturating_add(n as u128).min(self.end);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.next;
+        match usize::try_from(remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+/// The moves between two configurations of disks on three pegs 0, 1 and 2. `from[d]` and
+/// `to[d]` are the pegs of disk `d`, the smallest being disk 0; every configuration is legal
+/// since each peg's disks are stacked by size. The fewest moves when `to` is a single tower.
+pub fn hanoi_between(from: &[usize], to: &[usize]) -> Vec<(usize, usize)> {
+    assert_eq!(from.len(), to.len(), "different numbers of disks");
+    assert!(
+        from.iter().chain(to).all(|&peg| peg < 3),
+        "pegs are numbered 0 to 2"
+    );
+    let mut state = from.to_vec();
+    let mut moves = Vec::new();
+    settle(&mut state, to, &mut moves);
+    moves
+}
+
+/// Puts the largest misplaced disk in place, with everything smaller stacked on the third peg
+/// out of its way, then does the same for the smaller disks.
+fn settle(state: &mut [usize], goal: &[usize], moves: &mut Vec<(usize, usize)>) {
+    for disk in (0..state.len()).rev() {
+        let (from, to) = (state[disk], goal[disk]);
+        if from != to {
+            let spare = 3 - from - to;
+            settle(&mut state[..disk], &vec![spare; disk], moves);
+            moves.push((from, to));
+            state[disk] = to;
+        }
     }
+}
+
+/// The moves that take a tower of `n` disks from pegs[0] to pegs[1] using four pegs, by the
+/// Frame–Stewart algorithm: the top `k` disks go to a spare peg using all four, the rest go to
+/// the target using the other three, and the `k` follow them, with `k` chosen to need the
+/// fewest moves.
+pub fn hanoi_four_pegs<P: Copy>(n: usize, pegs: [P; 4]) -> Vec<(P, P)> {
+    let (counts, splits) = frame_stewart_table(n);
+    let mut moves = Vec::with_capacity(counts[n] as usize);
+    frame_stewart(n, pegs, &splits, &mut moves);
+    moves
+}
+
+/// How many moves `hanoi_four_pegs` takes for `n` disks.
+pub fn frame_stewart_moves(n: usize) -> u64 {
+    frame_stewart_table(n).0[n]
+}
+
+/// For each number of disks up to `n`, the fewest Frame–Stewart moves and the `k` giving them.
+fn frame_stewart_table(n: usize) -> (Vec<u64>, Vec<usize>) {
+    let mut counts = vec![0u64; n + 1];
+    let mut splits = vec![0; n + 1];
+    for disks in 1..=n {
+        (counts[disks], splits[disks]) = (0..disks)
+            .map(|k| {
+                let three_pegs = 1u64
+                    .checked_shl((disks - k) as u32)
+                    .map_or(u64::MAX, |moves| moves - 1);
+                (counts[k].saturating_mul(2).saturating_add(three_pegs), k)
+            })
+            .min()
+            .unwrap();
+    }
+    (counts, splits)
+}
 
-    hanoi(n - 1, source, auxiliary, target);
-    println!("{} => {}", source, target);
-    hanoi(n - 1, auxiliary, target, source);
+fn frame_stewart<P: Copy>(n: usize, pegs: [P; 4], splits: &[usize], moves: &mut Vec<(P, P)>) {
+    if n == 0 {
+        return;
+    }
+    let [source, target, spare, other] = pegs;
+    let k = splits[n];
+    frame_stewart(k, [source, spare, target, other], splits, moves);
+    moves.extend(hanoi(n - k, source, target, other));
+    frame_stewart(k, [spare, target, source, other], splits, moves);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    /// Plays `moves` on pegs holding the disks of `state`, checking every move is legal, and
+    /// returns the peg of each disk at the end.
+    fn play(state: &[usize], pegs: usize, moves: &[(usize, usize)]) -> Vec<usize> {
+        let mut stacks = vec![Vec::new(); pegs];
+        for disk in (0..state.len()).rev() {
+            stacks[state[disk]].push(disk);
+        }
+        for &(from, to) in moves {
+            let disk = stacks[from].pop().expect("move from an empty peg");
+            assert!(
+                stacks[to].last().is_none_or(|&top| top > disk),
+                "{} onto a smaller disk",
+                disk
+            );
+            stacks[to].push(disk);
+        }
+        let mut end = vec![0; state.len()];
+        for (peg, stack) in stacks.iter().enumerate() {
+            for &disk in stack {
+                end[disk] = peg;
+            }
+        }
+        end
+    }
+
     #[test]
     fn test_hanoi() {
         let n = 3;
         let source = "A";
         let target = "C";
         let auxiliary = "B";
-        hanoi(n, source, target, auxiliary);
-        // Expected output:
-        // Move disk 1 from A to C
-        // Move disk 2 from A to B
-        // Move disk 1 from C to B
-        // Move disk 3 from A to C
-        // Move disk 1 from B to A
-        // Move disk 2 from B to C
-        // Move disk 1 from A to C
+        let moves: Vec<_> = hanoi(n, source, target, auxiliary).collect();
+        assert_eq!(
+            moves,
+            [
+                ("A", "C"),
+                ("A", "B"),
+                ("C", "B"),
+                ("A", "C"),
+                ("B", "A"),
+                ("B", "C"),
+                ("A", "C"),
+            ]
+        );
+        assert_eq!(hanoi(0, 'a', 'b', 'c').count(), 0);
+    }
+
+    #[test]
+    fn test_hanoi_is_lazy() {
+        // 2^64 - 1 moves could never be collected, but the first ones come at once
+        let mut moves = hanoi(64, 0, 1, 2);
+        assert_eq!(moves.next(), Some((0, 2)));
+        assert_eq!(moves.next(), Some((0, 1)));
+        assert_eq!(moves.next(), Some((2, 1)));
+    }
+
+    #[test]
+    fn test_hanoi_iterative() {
+        for n in 0..12 {
+            let moves: Vec<_> = hanoi_iterative(n, 0, 2, 1).collect();
+            assert_eq!(moves, hanoi(n, 0, 2, 1).collect::<Vec<_>>());
+            assert_eq!(play(&vec![0; n], 3, &moves), vec![2; n]);
+        }
+
+        assert_eq!(
+            hanoi_iterative(100, 'a', 'b', 'c').size_hint(),
+            (usize::MAX, None)
+        );
+        // The middle move is the largest disk's
+        let mut moves = hanoi_iterative(60, 'a', 'b', 'c');
+        assert_eq!(moves.nth((1 << 59) - 1), Some(('a', 'b')));
+        let mut moves = hanoi_iterative(20, 0, 1, 2);
+        assert_eq!(moves.nth(1 << 19), hanoi(20, 0, 1, 2).nth(1 << 19));
+        assert_eq!(moves.size_hint(), ((1 << 19) - 2, Some((1 << 19) - 2)));
+    }
+
+    #[test]
+    fn test_hanoi_between() {
+        // Every configuration of 5 disks to every single tower, in the fewest moves
+        let n = 5;
+        for code in 0..3usize.pow(n as u32) {
+            let state: Vec<usize> = (0..n).map(|d| code / 3usize.pow(d as u32) % 3).collect();
+            for peg in 0..3 {
+                let goal = vec![peg; n];
+                let moves = hanoi_between(&state, &goal);
+                assert_eq!(play(&state, 3, &moves), goal);
+                // The largest misplaced disk d needs 2^d moves at least
+                let fewest: usize = (0..n)
+                    .rev()
+                    .scan(peg, |target, d| {
+                        let moves = if state[d] == *target { 0 } else { 1 << d };
+                        if state[d] != *target {
+                            *target = 3 - state[d] - *target;
+                        }
+                        Some(moves)
+                    })
+                    .sum();
+                assert_eq!(moves.len(), fewest, "{:?} to {}", state, peg);
+            }
+        }
+
+        let (from, to) = ([0, 1, 2, 0], [2, 2, 0, 1]);
+        assert_eq!(play(&from, 3, &hanoi_between(&from, &to)), to);
+        assert_eq!(hanoi_between(&[1, 2], &[1, 2]), []);
+    }
+
+    #[test]
+    fn test_hanoi_four_pegs() {
+        let expected = [0, 1, 3, 5, 9, 13, 17, 25, 33, 41, 49, 65, 81, 97, 113, 129];
+        for (n, &count) in expected.iter().enumerate() {
+            assert_eq!(frame_stewart_moves(n), count);
+            let moves = hanoi_four_pegs(n, [0, 3, 1, 2]);
+            assert_eq!(moves.len() as u64, count);
+            assert_eq!(play(&vec![0; n], 4, &moves), vec![3; n]);
+        }
     }
 }
@@ -0,0 +1,86 @@
+use std::cmp::Ordering;
+
+/// How many pairs `i < j` have `arr[i] > arr[j]`, counted while merge sorting a copy, in
+/// O(n log n).
+pub fn count_inversions<T: Ord + Clone>(arr: &[T]) -> u64 {
+    count_inversions_by(arr, T::cmp)
+}
+
+pub fn count_inversions_by<T: Clone, F: FnMut(&T, &T) -> Ordering>(
+    arr: &[T],
+    mut compare: F,
+) -> u64 {
+    let mut sorted = arr.to_vec();
+    let mut buffer = Vec::with_capacity(arr.len());
+    _count_inversions(&mut sorted, &mut buffer, &mut compare)
+}
+
+pub fn count_inversions_by_key<T: Clone, K: Ord, F: FnMut(&T) -> K>(arr: &[T], mut key: F) -> u64 {
+    count_inversions_by(arr, |a, b| key(a).cmp(&key(b)))
+}
+
+fn _count_inversions<T: Clone, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [T],
+    buffer: &mut Vec<T>,
+    compare: &mut F,
+) -> u64 {
+    if arr.len() < 2 {
+        return 0;
+    }
+    let mid = arr.len() / 2;
+    let mut count = _count_inversions(&mut arr[..mid], buffer, compare)
+        + _count_inversions(&mut arr[mid..], buffer, compare);
+
+    buffer.clear();
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < arr.len() {
+        if compare(&arr[j], &arr[i]) == Ordering::Less {
+            // arr[j] comes before everything left in the left half
+            count += (mid - i) as u64;
+            buffer.push(arr[j].clone());
+            j += 1;
+        } else {
+            buffer.push(arr[i].clone());
+            i += 1;
+        }
+    }
+    buffer.extend_from_slice(&arr[i..mid]);
+    // What is left of the right half is already in place
+    let merged = buffer.len();
+    arr[..merged].clone_from_slice(buffer);
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_count_inversions() {
+        assert_eq!(count_inversions(&[2, 4, 1, 3, 5]), 3);
+        assert_eq!(count_inversions(&[1, 2, 3]), 0);
+        assert_eq!(count_inversions(&[3, 2, 1]), 3);
+        assert_eq!(count_inversions(&[1, 1, 1]), 0);
+        assert_eq!(count_inversions::<i32>(&[]), 0);
+        assert_eq!(count_inversions_by(&[1, 2, 3], |a, b| b.cmp(a)), 3);
+        assert_eq!(count_inversions_by_key(&["ccc", "a", "bb"], |s| s.len()), 2);
+
+        let reversed: Vec<u32> = (0..100_000).rev().collect();
+        assert_eq!(count_inversions(&reversed), 100_000 * 99_999 / 2);
+    }
+
+    #[test]
+    fn test_count_inversions_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(26);
+        for len in 0..150 {
+            let arr: Vec<i32> = (0..len).map(|_| rng.random_range(0..20)).collect();
+            let brute_force = (0..arr.len())
+                .flat_map(|i| (i + 1..arr.len()).map(move |j| (i, j)))
+                .filter(|&(i, j)| arr[i] > arr[j])
+                .count();
+            assert_eq!(count_inversions(&arr), brute_force as u64, "{:?}", arr);
+        }
+    }
+}
@@ -0,0 +1,59 @@
+/// The element making up more than half of `arr`, if there is one. A majority of the whole is
+/// a majority of one of its halves, so only the halves' candidates need counting: O(n log n).
+pub fn majority_element<T: Eq>(arr: &[T]) -> Option<&T> {
+    if arr.len() <= 1 {
+        return arr.first();
+    }
+    let (left, right) = arr.split_at(arr.len() / 2);
+    [majority_element(left), majority_element(right)]
+        .into_iter()
+        .flatten()
+        .find(|&candidate| arr.iter().filter(|&x| x == candidate).count() * 2 > arr.len())
+}
+
+/// `majority_element` by the Boyer–Moore vote, in O(n) and O(1) space: pairing off different
+/// elements leaves the majority, if any, as the last one standing.
+pub fn majority_element_by_vote<T: Eq>(arr: &[T]) -> Option<&T> {
+    let mut candidate = arr.first()?;
+    let mut votes = 0;
+    for x in arr {
+        if votes == 0 {
+            candidate = x;
+        }
+        votes = if x == candidate { votes + 1 } else { votes - 1 };
+    }
+    // Without a majority the survivor may be anything
+    (arr.iter().filter(|&x| x == candidate).count() * 2 > arr.len()).then_some(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_majority_element() {
+        assert_eq!(majority_element(&[2, 2, 1, 1, 1, 2, 2]), Some(&2));
+        assert_eq!(majority_element(&[1, 2, 3]), None);
+        assert_eq!(majority_element(&[1, 1, 2, 2]), None);
+        assert_eq!(majority_element(&["x"]), Some(&"x"));
+        assert_eq!(majority_element::<i32>(&[]), None);
+        assert_eq!(majority_element_by_vote(&[3, 3, 4]), Some(&3));
+        assert_eq!(majority_element_by_vote(&[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn test_majority_element_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(29);
+        for len in 0..200 {
+            let values = rng.random_range(1..4);
+            let arr: Vec<u8> = (0..len).map(|_| rng.random_range(0..values)).collect();
+            let brute_force = arr
+                .iter()
+                .find(|&candidate| arr.iter().filter(|&x| x == candidate).count() * 2 > arr.len());
+            assert_eq!(majority_element(&arr), brute_force, "{:?}", arr);
+            assert_eq!(majority_element_by_vote(&arr), brute_force, "{:?}", arr);
+        }
+    }
+}
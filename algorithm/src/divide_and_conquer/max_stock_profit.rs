@@ -1,7 +1,7 @@
 pub fn max_stock_profit(prices: &[f64]) -> f64 {
     let len = prices.len();
     match len {
-        0 | 1 => return 0.0,
+        0 | 1 => 0.0,
         _ => {
             let mut i = len - 1;
             let mut profit = 0.0;
@@ -15,18 +15,165 @@ pub fn max_stock_profit(prices: &[f64]) -> f64 {
                 }
             }
 
-            return if i == len - 1 {
+            if i == len - 1 {
                 profit
             } else {
                 profit + prices[len - 1] - prices[i]
+            }
+        }
+    }
+}
+
+/// Buying on day `buy` and selling on day `sell`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Trade {
+    pub buy: usize,
+    pub sell: usize,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TradePlan {
+    pub profit: f64,
+    /// In order, without overlaps: at most one share is held at a time
+    pub trades: Vec<Trade>,
+}
+
+/// Constraints on the trades of a plan.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct TradingRules {
+    /// Most trades allowed, or `None` for any number
+    pub max_trades: Option<usize>,
+    /// Days after a sale on which nothing may be bought
+    pub cooldown: usize,
+    /// Paid once per trade, on selling
+    pub fee: f64,
+}
+
+pub fn max_profit_unlimited(prices: &[f64]) -> TradePlan {
+    plan_trades(prices, &TradingRules::default())
+}
+
+pub fn max_profit_k_trades(prices: &[f64], k: usize) -> TradePlan {
+    let rules = TradingRules {
+        max_trades: Some(k),
+        ..Default::default()
+    };
+    plan_trades(prices, &rules)
+}
+
+pub fn max_profit_with_cooldown(prices: &[f64], cooldown: usize) -> TradePlan {
+    let rules = TradingRules {
+        cooldown,
+        ..Default::default()
+    };
+    plan_trades(prices, &rules)
+}
+
+pub fn max_profit_with_fee(prices: &[f64], fee: f64) -> TradePlan {
+    let rules = TradingRules {
+        fee,
+        ..Default::default()
+    };
+    plan_trades(prices, &rules)
+}
+
+/// The most profitable trades under `rules`, by dynamic programming over the days, the trades
+/// completed so far and whether a share is held. O(n·k) time and space, or O(n) when the
+/// number of trades is unbounded.
+pub fn plan_trades(prices: &[f64], rules: &TradingRules) -> TradePlan {
+    let n = prices.len();
+    // Trades last at least two days, so more than n / 2 can't be used
+    let (limited, layers) = match rules.max_trades {
+        Some(k) if k < n / 2 => (true, k + 1),
+        _ => (false, 1),
+    };
+
+    // Indexed [day][trades completed]: the best cash after the day, and whether that day's
+    // action (selling from held, buying from flat) produced it
+    let mut flat = vec![vec![f64::NEG_INFINITY; layers]; n];
+    let mut held = vec![vec![f64::NEG_INFINITY; layers]; n];
+    let mut sold = vec![vec![false; layers]; n];
+    let mut bought = vec![vec![false; layers]; n];
+    // Flat at the end of day i - cooldown - 1, or before the first day
+    let flat_before =
+        |flat: &[Vec<f64>], day: usize, j: usize| match day.checked_sub(rules.cooldown + 1) {
+            Some(day) => flat[day][j],
+            None if j == 0 => 0.0,
+            None => f64::NEG_INFINITY,
+        };
+
+    for day in 0..n {
+        for j in 0..layers {
+            let (stay_held, stay_flat) = match day {
+                0 => (
+                    f64::NEG_INFINITY,
+                    if j == 0 { 0.0 } else { f64::NEG_INFINITY },
+                ),
+                _ => (held[day - 1][j], flat[day - 1][j]),
+            };
+
+            let buy = flat_before(&flat, day, j) - prices[day];
+            held[day][j] = stay_held;
+            if buy > stay_held {
+                (held[day][j], bought[day][j]) = (buy, true);
+            }
+
+            let from = if limited { j.checked_sub(1) } else { Some(j) };
+            let sell = match from {
+                Some(from) if day > 0 => held[day - 1][from] + prices[day] - rules.fee,
+                _ => f64::NEG_INFINITY,
             };
+            flat[day][j] = stay_flat;
+            if sell > stay_flat {
+                (flat[day][j], sold[day][j]) = (sell, true);
+            }
         }
     }
+
+    let Some(last) = flat.last() else {
+        return TradePlan {
+            profit: 0.0,
+            trades: Vec::new(),
+        };
+    };
+    let mut j = (0..layers).fold(0, |best, j| if last[j] > last[best] { j } else { best });
+    let profit = last[j];
+
+    let mut trades: Vec<Trade> = Vec::new();
+    let mut day = n - 1;
+    let mut holding = false;
+    loop {
+        if holding {
+            if bought[day][j] {
+                trades.last_mut().unwrap().buy = day;
+                holding = false;
+                match day.checked_sub(rules.cooldown + 1) {
+                    Some(before) => day = before,
+                    None => break,
+                }
+                continue;
+            }
+        } else if sold[day][j] {
+            trades.push(Trade { buy: 0, sell: day });
+            holding = true;
+            if limited {
+                j -= 1;
+            }
+        }
+        if day == 0 {
+            break;
+        }
+        day -= 1;
+    }
+    trades.reverse();
+    TradePlan { profit, trades }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
     #[test]
     fn test_max_stock_profit() {
         let prices = vec![1.0, 2.0, 3.0, 4.0, 5.0];
@@ -40,4 +187,112 @@ mod tests {
         let prices = vec![1.0, 2.0, 3.0, 2.0, 1.0, 4.0];
         assert_eq!(max_stock_profit(&prices), 3.0);
     }
+
+    fn check(prices: &[f64], plan: &TradePlan, rules: &TradingRules) {
+        let mut profit = 0.0;
+        let mut free_from = 0;
+        for trade in &plan.trades {
+            assert!(
+                free_from <= trade.buy && trade.buy < trade.sell,
+                "{:?}",
+                plan
+            );
+            profit += prices[trade.sell] - prices[trade.buy] - rules.fee;
+            free_from = trade.sell + rules.cooldown + 1;
+        }
+        assert!((profit - plan.profit).abs() < 1e-9, "{:?}", plan);
+        if let Some(k) = rules.max_trades {
+            assert!(plan.trades.len() <= k);
+        }
+    }
+
+    /// The best profit over every set of trades, from day `from` on.
+    fn brute_force(prices: &[f64], rules: &TradingRules, from: usize, trades: usize) -> f64 {
+        let mut best = 0.0;
+        if rules.max_trades.is_some_and(|k| trades >= k) {
+            return best;
+        }
+        for buy in from..prices.len() {
+            for sell in buy + 1..prices.len() {
+                let rest = brute_force(prices, rules, sell + rules.cooldown + 1, trades + 1);
+                best = f64::max(best, prices[sell] - prices[buy] - rules.fee + rest);
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_plan_trades() {
+        let prices = [3.0, 3.0, 5.0, 0.0, 0.0, 3.0, 1.0, 4.0];
+        let plan = max_profit_k_trades(&prices, 2);
+        assert_eq!(plan.profit, 6.0);
+        assert_eq!(
+            plan.trades,
+            [Trade { buy: 0, sell: 2 }, Trade { buy: 3, sell: 7 }]
+        );
+        assert_eq!(max_profit_k_trades(&prices, 1).profit, 4.0);
+        assert_eq!(max_profit_k_trades(&prices, 0).trades, []);
+
+        let plan = max_profit_with_cooldown(&[1.0, 2.0, 3.0, 0.0, 2.0], 1);
+        assert_eq!(plan.profit, 3.0);
+        assert_eq!(
+            plan.trades,
+            [Trade { buy: 0, sell: 1 }, Trade { buy: 3, sell: 4 }]
+        );
+
+        let plan = max_profit_with_fee(&[1.0, 3.0, 2.0, 8.0, 4.0, 9.0], 2.0);
+        assert_eq!(plan.profit, 8.0);
+        assert_eq!(
+            plan.trades,
+            [Trade { buy: 0, sell: 3 }, Trade { buy: 4, sell: 5 }]
+        );
+
+        let plan = max_profit_unlimited(&[1.0, 2.0, 3.0, 2.0, 5.0]);
+        assert_eq!(plan.profit, 5.0);
+        assert_eq!(
+            plan.trades,
+            [Trade { buy: 0, sell: 2 }, Trade { buy: 3, sell: 4 }]
+        );
+        assert_eq!(max_profit_unlimited(&[5.0, 4.0, 3.0]).trades, []);
+        assert_eq!(max_profit_unlimited(&[]).profit, 0.0);
+    }
+
+    #[test]
+    fn test_plan_trades_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(24);
+        for len in 0..10 {
+            for _ in 0..20 {
+                let prices: Vec<f64> = (0..len).map(|_| rng.random_range(0..10) as f64).collect();
+                let rules = TradingRules {
+                    max_trades: rng.random_bool(0.5).then(|| rng.random_range(0..4)),
+                    cooldown: rng.random_range(0..3),
+                    fee: rng.random_range(0..3) as f64,
+                };
+                let plan = plan_trades(&prices, &rules);
+                check(&prices, &plan, &rules);
+                let best = brute_force(&prices, &rules, 0, 0);
+                assert!(
+                    (plan.profit - best).abs() < 1e-9,
+                    "{:?} {:?}",
+                    prices,
+                    rules
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_long_unlimited_plan() {
+        let prices: Vec<f64> = (0..100_000).map(|i| (i % 7) as f64).collect();
+        let plan = max_profit_with_fee(&prices, 1.0);
+        check(
+            &prices,
+            &plan,
+            &TradingRules {
+                fee: 1.0,
+                ..Default::default()
+            },
+        );
+        assert_eq!(plan.trades.len(), 14_286);
+    }
 }
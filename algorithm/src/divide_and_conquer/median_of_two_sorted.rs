@@ -0,0 +1,92 @@
+/// The `k`th smallest (from 0) of the elements of two sorted slices, or `None` if there are
+/// not that many. Each step drops about half of the `k` smallest candidates from one slice,
+/// in O(log k).
+pub fn kth_of_two_sorted<'a, T: PartialOrd>(a: &'a [T], b: &'a [T], k: usize) -> Option<&'a T> {
+    if k >= a.len() + b.len() {
+        return None;
+    }
+    let (mut a, mut b) = (a, b);
+    // Counting from 1 from here on
+    let mut k = k + 1;
+    loop {
+        if a.is_empty() {
+            return Some(&b[k - 1]);
+        }
+        if b.is_empty() {
+            return Some(&a[k - 1]);
+        }
+        if k == 1 {
+            return Some(if b[0] < a[0] { &b[0] } else { &a[0] });
+        }
+        // The first i of a and j of b can't all be above the kth
+        let i = (k / 2).min(a.len());
+        let j = (k / 2).min(b.len());
+        if a[i - 1] <= b[j - 1] {
+            a = &a[i..];
+            k -= i;
+        } else {
+            b = &b[j..];
+            k -= j;
+        }
+    }
+}
+
+/// The median of the elements of two sorted slices, or `None` if both are empty.
+pub fn median_of_two_sorted<T: PartialOrd + Copy + Into<f64>>(a: &[T], b: &[T]) -> Option<f64> {
+    let len = a.len() + b.len();
+    let upper = (*kth_of_two_sorted(a, b, len / 2)?).into();
+    if len % 2 == 1 {
+        return Some(upper);
+    }
+    let lower = (*kth_of_two_sorted(a, b, len / 2 - 1)?).into();
+    Some((lower + upper) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_median_of_two_sorted() {
+        assert_eq!(median_of_two_sorted(&[1, 3], &[2]), Some(2.0));
+        assert_eq!(median_of_two_sorted(&[1, 2], &[3, 4]), Some(2.5));
+        assert_eq!(median_of_two_sorted(&[], &[0.5]), Some(0.5));
+        assert_eq!(median_of_two_sorted::<i32>(&[], &[]), None);
+        assert_eq!(kth_of_two_sorted(&["a", "c"], &["b"], 2), Some(&"c"));
+        assert_eq!(kth_of_two_sorted(&["a", "c"], &["b"], 3), None);
+    }
+
+    #[test]
+    fn test_kth_of_two_sorted_matches_merge() {
+        let mut rng = StdRng::seed_from_u64(28);
+        for _ in 0..300 {
+            let mut a: Vec<i32> = (0..rng.random_range(0..20))
+                .map(|_| rng.random_range(0..15))
+                .collect();
+            let mut b: Vec<i32> = (0..rng.random_range(0..20))
+                .map(|_| rng.random_range(0..15))
+                .collect();
+            a.sort();
+            b.sort();
+            let mut merged = [a.clone(), b.clone()].concat();
+            merged.sort();
+            for k in 0..=merged.len() {
+                assert_eq!(
+                    kth_of_two_sorted(&a, &b, k),
+                    merged.get(k),
+                    "{:?} {:?}",
+                    a,
+                    b
+                );
+            }
+            let median = match merged.len() {
+                0 => None,
+                len if len % 2 == 1 => Some(merged[len / 2] as f64),
+                len => Some((merged[len / 2 - 1] + merged[len / 2]) as f64 / 2.0),
+            };
+            assert_eq!(median_of_two_sorted(&a, &b), median);
+        }
+    }
+}
@@ -18,3 +18,21 @@ pub use max_k_of_array::*;
 
 mod beautiful_array;
 pub use beautiful_array::*;
+
+mod select_nth;
+pub use select_nth::*;
+
+mod closest_pair;
+pub use closest_pair::*;
+
+mod inversions;
+pub use inversions::*;
+
+mod skyline;
+pub use skyline::*;
+
+mod median_of_two_sorted;
+pub use median_of_two_sorted::*;
+
+mod majority_element;
+pub use majority_element::*;
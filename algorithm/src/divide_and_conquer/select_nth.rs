@@ -0,0 +1,291 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+/// Reorders `arr` so the element at `n` is the one that would be there if it were sorted, with
+/// nothing greater before it and nothing less after it, and returns it. Randomized quickselect:
+/// expected O(n). Panics if `n` is out of bounds.
+pub fn select_nth<T: Ord>(arr: &mut [T], n: usize) -> &mut T {
+    _select_nth(arr, n, &mut |a, b| a < b)
+}
+
+pub fn select_nth_by<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [T],
+    n: usize,
+    mut compare: F,
+) -> &mut T {
+    _select_nth(arr, n, &mut |a, b| compare(a, b) == Ordering::Less)
+}
+
+pub fn select_nth_by_key<T, K: Ord, F: FnMut(&T) -> K>(
+    arr: &mut [T],
+    n: usize,
+    mut key: F,
+) -> &mut T {
+    _select_nth(arr, n, &mut |a, b| key(a) < key(b))
+}
+
+/// `select_nth` with median-of-medians pivots, which make it O(n) in the worst case at the
+/// price of a larger constant.
+pub fn select_nth_deterministic<T: Ord>(arr: &mut [T], n: usize) -> &mut T {
+    _select_nth_deterministic(arr, n, &mut |a, b| a < b)
+}
+
+pub fn select_nth_deterministic_by<T, F: FnMut(&T, &T) -> Ordering>(
+    arr: &mut [T],
+    n: usize,
+    mut compare: F,
+) -> &mut T {
+    _select_nth_deterministic(arr, n, &mut |a, b| compare(a, b) == Ordering::Less)
+}
+
+/// The `k` largest elements, largest first. Takes O(n + k log k).
+pub fn top_k<T: Ord + Clone>(arr: &[T], k: usize) -> Vec<T> {
+    top_k_by(arr, k, T::cmp)
+}
+
+pub fn top_k_by<T: Clone, F: FnMut(&T, &T) -> Ordering>(
+    arr: &[T],
+    k: usize,
+    mut compare: F,
+) -> Vec<T> {
+    bottom_k_by(arr, k, |a, b| compare(b, a))
+}
+
+/// The `k` smallest elements, smallest first.
+pub fn bottom_k<T: Ord + Clone>(arr: &[T], k: usize) -> Vec<T> {
+    bottom_k_by(arr, k, T::cmp)
+}
+
+pub fn bottom_k_by<T: Clone, F: FnMut(&T, &T) -> Ordering>(
+    arr: &[T],
+    k: usize,
+    mut compare: F,
+) -> Vec<T> {
+    let k = k.min(arr.len());
+    let mut copy = arr.to_vec();
+    if k > 0 && k < copy.len() {
+        _select_nth(&mut copy, k - 1, &mut |a, b| {
+            compare(a, b) == Ordering::Less
+        });
+    }
+    copy.truncate(k);
+    copy.sort_unstable_by(compare);
+    copy
+}
+
+fn _select_nth<'a, T, F: FnMut(&T, &T) -> bool>(
+    arr: &'a mut [T],
+    n: usize,
+    is_less: &mut F,
+) -> &'a mut T {
+    assert!(
+        n < arr.len(),
+        "index {} out of range for length {}",
+        n,
+        arr.len()
+    );
+    let mut random = Random::new();
+    let (mut start, mut end) = (0, arr.len());
+    while end - start > 1 {
+        let pivot = start + random.below(end - start);
+        arr.swap(start, pivot);
+        let (lt, gt) = partition(&mut arr[start..end], is_less);
+        if n < start + lt {
+            end = start + lt;
+        } else if n >= start + gt {
+            start += gt;
+        } else {
+            break;
+        }
+    }
+    &mut arr[n]
+}
+
+fn _select_nth_deterministic<'a, T, F: FnMut(&T, &T) -> bool>(
+    arr: &'a mut [T],
+    n: usize,
+    is_less: &mut F,
+) -> &'a mut T {
+    assert!(
+        n < arr.len(),
+        "index {} out of range for length {}",
+        n,
+        arr.len()
+    );
+    let (mut start, mut end) = (0, arr.len());
+    while end - start > 5 {
+        let pivot = median_of_medians(&mut arr[start..end], is_less);
+        arr.swap(start, start + pivot);
+        let (lt, gt) = partition(&mut arr[start..end], is_less);
+        if n < start + lt {
+            end = start + lt;
+        } else if n >= start + gt {
+            start += gt;
+        } else {
+            return &mut arr[n];
+        }
+    }
+    insertion_sort(&mut arr[start..end], is_less);
+    &mut arr[n]
+}
+
+/// Moves the medians of each group of five to the front and selects their median, returning
+/// its index. At least 3/10 of the elements are on either side of it.
+fn median_of_medians<T, F: FnMut(&T, &T) -> bool>(arr: &mut [T], is_less: &mut F) -> usize {
+    let groups = arr.len() / 5;
+    for group in 0..groups {
+        insertion_sort(&mut arr[5 * group..5 * group + 5], is_less);
+        arr.swap(group, 5 * group + 2);
+    }
+    let middle = groups / 2;
+    _select_nth_deterministic(&mut arr[..groups], middle, is_less);
+    middle
+}
+
+/// Three-way partition around the pivot at arr[0]. Returns `(lt, gt)` such that arr[..lt] is
+/// less than the pivot, arr[lt..gt] equal to it and arr[gt..] greater, so runs of duplicates
+/// can't make selection quadratic.
+fn partition<T, F: FnMut(&T, &T) -> bool>(arr: &mut [T], is_less: &mut F) -> (usize, usize) {
+    let (head, rest) = arr.split_at_mut(1);
+    let pivot = &head[0];
+    let (mut lt, mut i, mut gt) = (0, 0, rest.len());
+    while i < gt {
+        if is_less(&rest[i], pivot) {
+            rest.swap(lt, i);
+            lt += 1;
+            i += 1;
+        } else if is_less(pivot, &rest[i]) {
+            gt -= 1;
+            rest.swap(i, gt);
+        } else {
+            i += 1;
+        }
+    }
+    // rest[j] is arr[j + 1]; the pivot goes after the lesser elements
+    arr.swap(0, lt);
+    (lt, gt + 1)
+}
+
+fn insertion_sort<T, F: FnMut(&T, &T) -> bool>(arr: &mut [T], is_less: &mut F) {
+    for i in 1..arr.len() {
+        let mut j = i;
+        while j > 0 && is_less(&arr[j], &arr[j - 1]) {
+            arr.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// xorshift64*, seeded from the per-process random keys of `RandomState`.
+struct Random(u64);
+
+impl Random {
+    fn new() -> Self {
+        Self(RandomState::new().hash_one(0u64) | 1)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn check_selected<T: Ord + std::fmt::Debug>(arr: &[T], n: usize) {
+        assert!(arr[..n].iter().all(|x| x <= &arr[n]), "{:?} at {}", arr, n);
+        assert!(
+            arr[n + 1..].iter().all(|x| x >= &arr[n]),
+            "{:?} at {}",
+            arr,
+            n
+        );
+    }
+
+    #[test]
+    fn test_select_nth() {
+        let mut arr = [5, 1, 4, 2, 3];
+        assert_eq!(*select_nth(&mut arr, 0), 1);
+        assert_eq!(*select_nth(&mut arr, 4), 5);
+        assert_eq!(*select_nth(&mut arr, 2), 3);
+        check_selected(&arr, 2);
+        assert_eq!(*select_nth_deterministic(&mut arr, 1), 2);
+
+        let mut words = ["pear", "fig", "banana", "kiwi"];
+        assert_eq!(*select_nth_by_key(&mut words, 0, |w| w.len()), "fig");
+        assert_eq!(*select_nth_by(&mut words, 0, |a, b| b.cmp(a)), "pear");
+    }
+
+    #[test]
+    fn test_select_nth_matches_sort() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for len in 1..200 {
+            // Few distinct values to exercise the duplicate handling
+            let range = if len % 2 == 0 { 5 } else { 1000 };
+            let arr: Vec<i32> = (0..len).map(|_| rng.random_range(0..range)).collect();
+            let mut sorted = arr.clone();
+            sorted.sort();
+            let n = rng.random_range(0..len);
+
+            let mut copy = arr.clone();
+            assert_eq!(*select_nth(&mut copy, n), sorted[n]);
+            check_selected(&copy, n);
+            let mut copy = arr.clone();
+            assert_eq!(*select_nth_deterministic(&mut copy, n), sorted[n]);
+            check_selected(&copy, n);
+        }
+    }
+
+    #[test]
+    fn test_select_nth_adversarial() {
+        let len = 100_000;
+        for arr in [
+            (0..len).collect::<Vec<_>>(),
+            (0..len).rev().collect(),
+            vec![7; len],
+            (0..len).map(|i| i % 2).collect(),
+        ] {
+            let mut sorted = arr.clone();
+            sorted.sort();
+            for n in [0, len / 2, len - 1] {
+                assert_eq!(*select_nth(&mut arr.clone(), n), sorted[n]);
+                assert_eq!(*select_nth_deterministic(&mut arr.clone(), n), sorted[n]);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_select_nth_out_of_bounds() {
+        select_nth(&mut [1, 2, 3], 3);
+    }
+
+    #[test]
+    fn test_top_k() {
+        let arr = [3, 9, 1, 9, 4, 7, 2];
+        assert_eq!(top_k(&arr, 3), [9, 9, 7]);
+        assert_eq!(bottom_k(&arr, 2), [1, 2]);
+        assert_eq!(top_k(&arr, 0), Vec::<i32>::new());
+        assert_eq!(top_k(&arr, 10), [9, 9, 7, 4, 3, 2, 1]);
+        assert_eq!(top_k_by(&arr, 2, |a, b| b.cmp(a)), [1, 2]);
+    }
+
+    #[test]
+    fn test_agrees_with_kth_max_of_array() {
+        use crate::divide_and_conquer::kth_max_of_array2;
+        let mut rng = StdRng::seed_from_u64(18);
+        let arr: Vec<i32> = (0..50).map(|_| rng.random_range(-20..20)).collect();
+        for k in 1..=arr.len() {
+            let kth = *select_nth(&mut arr.clone(), arr.len() - k);
+            assert_eq!(Some(kth), kth_max_of_array2(&arr, k));
+            assert_eq!(top_k(&arr, k).last(), Some(&kth));
+        }
+    }
+}
@@ -1,42 +1,187 @@
+use std::ops::{Add, Range, Sub};
+
+/// A non-empty run arr[start..end] and its sum.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Subarray<T> {
+    pub sum: T,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A block of a grid, grid[rows][cols], and its sum.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Submatrix<T> {
+    pub sum: T,
+    pub rows: Range<usize>,
+    pub cols: Range<usize>,
+}
+
 pub fn max_sum_of_sub_sequence(nums: &[i32]) -> i32 {
+    max_subarray(nums).map_or(0, |sub| sub.sum)
+}
+
+pub fn min_sum_of_sub_sequence(nums: &[i32]) -> i32 {
+    min_subarray(nums).map_or(0, |sub| sub.sum)
+}
+
+/// The subarray with the largest sum by Kadane's algorithm, in O(n) time and O(1) space. Of
+/// equal sums the first one found is kept. `None` for an empty slice.
+pub fn max_subarray<T: Copy + Ord + Add<Output = T>>(nums: &[T]) -> Option<Subarray<T>> {
+    kadane(nums, |a, b| a > b)
+}
+
+pub fn min_subarray<T: Copy + Ord + Add<Output = T>>(nums: &[T]) -> Option<Subarray<T>> {
+    kadane(nums, |a, b| a < b)
+}
+
+fn kadane<T: Copy + Add<Output = T>, F: Fn(T, T) -> bool>(
+    nums: &[T],
+    better: F,
+) -> Option<Subarray<T>> {
+    let first = *nums.first()?;
+    let mut best = Subarray {
+        sum: first,
+        start: 0,
+        end: 1,
+    };
+    // The best subarray ending at the current element
+    let mut current = best;
+    for (i, &x) in nums.iter().enumerate().skip(1) {
+        if better(x, current.sum + x) {
+            current = Subarray {
+                sum: x,
+                start: i,
+                end: i + 1,
+            };
+        } else {
+            current.sum = current.sum + x;
+            current.end = i + 1;
+        }
+        if better(current.sum, best.sum) {
+            best = current;
+        }
+    }
+    Some(best)
+}
+
+/// `max_subarray` by divide and conquer: the best subarray is in the left half, in the right
+/// half, or crosses the middle. O(n log n) time and O(log n) stack.
+pub fn max_subarray_divide_and_conquer<T: Copy + Ord + Add<Output = T>>(
+    nums: &[T],
+) -> Option<Subarray<T>> {
     if nums.is_empty() {
-        return 0;
+        return None;
     }
-    if nums.len() == 1 {
-        return nums[0];
+    Some(_max_subarray(nums, 0, nums.len()))
+}
+
+fn _max_subarray<T: Copy + Ord + Add<Output = T>>(
+    nums: &[T],
+    start: usize,
+    end: usize,
+) -> Subarray<T> {
+    if end - start == 1 {
+        return Subarray {
+            sum: nums[start],
+            start,
+            end,
+        };
     }
-    let max = max_sum_of_sub_sequence(&nums[0..nums.len() - 1]);
-    let mut sum = nums[nums.len() - 1];
-    for i in (0..nums.len() - 1).rev() {
-        if nums[i] < 0 {
-            break;
+    let mid = start + (end - start) / 2;
+    let left = _max_subarray(nums, start, mid);
+    let right = _max_subarray(nums, mid, end);
+
+    // The best sum ending at mid - 1 plus the best starting at mid
+    let (mut sum, mut best_left, mut from) = (nums[mid - 1], nums[mid - 1], mid - 1);
+    for i in (start..mid - 1).rev() {
+        sum = sum + nums[i];
+        if sum > best_left {
+            (best_left, from) = (sum, i);
+        }
+    }
+    let (mut sum, mut best_right, mut to) = (nums[mid], nums[mid], mid + 1);
+    for (i, &x) in (mid + 1..end).zip(&nums[mid + 1..end]) {
+        sum = sum + x;
+        if sum > best_right {
+            (best_right, to) = (sum, i + 1);
         }
-        sum += nums[i];
     }
-    if sum > max { sum } else { max }
+    let crossing = Subarray {
+        sum: best_left + best_right,
+        start: from,
+        end: to,
+    };
+
+    [right, crossing].into_iter().fold(
+        left,
+        |best, sub| if sub.sum > best.sum { sub } else { best },
+    )
 }
 
-pub fn min_sum_of_sub_sequence(nums: &[i32]) -> i32 {
-    if nums.is_empty() {
-        return 0;
+/// The subarray with the largest sum when the end of `nums` wraps around to its start. When
+/// `end <= start` the subarray is nums[start..] followed by nums[..end].
+pub fn max_subarray_circular<T: Copy + Ord + Add<Output = T> + Sub<Output = T>>(
+    nums: &[T],
+) -> Option<Subarray<T>> {
+    let straight = max_subarray(nums)?;
+    let excluded = min_subarray(nums)?;
+    // A wrapped subarray is everything but a middle run, best when that run is the minimum.
+    // It can't exclude everything.
+    if excluded.end - excluded.start == nums.len() {
+        return Some(straight);
     }
-    if nums.len() == 1 {
-        return nums[0];
-    }
-    let min = min_sum_of_sub_sequence(&nums[0..nums.len() - 1]);
-    let mut sum = nums[nums.len() - 1];
-    for i in (0..nums.len() - 1).rev() {
-        if nums[i] > 0 {
-            break;
+    let total = nums[1..].iter().fold(nums[0], |sum, &x| sum + x);
+    let wrapped = Subarray {
+        sum: total - excluded.sum,
+        start: excluded.end,
+        end: excluded.start,
+    };
+    Some(if wrapped.sum > straight.sum {
+        wrapped
+    } else {
+        straight
+    })
+}
+
+/// The block of a rectangular grid with the largest sum. Each pair of bounding rows is
+/// collapsed into column sums for Kadane's algorithm: O(rows² · cols), so pass the grid with
+/// its shorter side as rows.
+pub fn max_sum_submatrix<T: Copy + Ord + Add<Output = T>, R: AsRef<[T]>>(
+    grid: &[R],
+) -> Option<Submatrix<T>> {
+    let width = grid.first()?.as_ref().len();
+    assert!(
+        grid.iter().all(|row| row.as_ref().len() == width),
+        "rows of different lengths"
+    );
+
+    let mut best: Option<Submatrix<T>> = None;
+    for top in 0..grid.len() {
+        let mut columns = grid[top].as_ref().to_vec();
+        for (bottom, row) in grid.iter().enumerate().skip(top) {
+            if bottom > top {
+                for (sum, &x) in columns.iter_mut().zip(row.as_ref()) {
+                    *sum = *sum + x;
+                }
+            }
+            let sub = max_subarray(&columns)?;
+            if best.as_ref().is_none_or(|best| sub.sum > best.sum) {
+                best = Some(Submatrix {
+                    sum: sub.sum,
+                    rows: top..bottom + 1,
+                    cols: sub.start..sub.end,
+                });
+            }
         }
-        sum += nums[i];
     }
-    if sum < min { sum } else { min }
+    best
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
     #[test]
     fn test_max_sum_sub_sequence() {
         let nums = vec![1, 2, 3, 4, 5];
@@ -60,4 +205,125 @@ mod tests {
         let nums = vec![1, -2, -3, -4, 5];
         assert_eq!(min_sum_of_sub_sequence(&nums), -9);
     }
+
+    #[test]
+    fn test_long_sequence() {
+        // The old prefix recursion overflowed the stack here
+        let nums: Vec<i32> = (0..1_000_000)
+            .map(|i| if i % 3 == 0 { -2 } else { 1 })
+            .collect();
+        assert_eq!(max_sum_of_sub_sequence(&nums), 2);
+        assert_eq!(min_sum_of_sub_sequence(&nums), -2);
+    }
+
+    fn sum(nums: &[i64], start: usize, end: usize) -> i64 {
+        if end <= start {
+            nums[start..].iter().chain(&nums[..end]).sum()
+        } else {
+            nums[start..end].iter().sum()
+        }
+    }
+
+    #[test]
+    fn test_max_subarray() {
+        let nums = [-2, 1, -3, 4, -1, 2, 1, -5, 4];
+        let expected = Subarray {
+            sum: 6,
+            start: 3,
+            end: 7,
+        };
+        assert_eq!(max_subarray(&nums), Some(expected));
+        assert_eq!(max_subarray_divide_and_conquer(&nums), Some(expected));
+        assert_eq!(max_subarray::<i32>(&[]), None);
+        assert_eq!(
+            max_subarray(&[-3, -1, -2]),
+            Some(Subarray {
+                sum: -1,
+                start: 1,
+                end: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_max_subarray_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(22);
+        for len in 1..60 {
+            let nums: Vec<i64> = (0..len).map(|_| rng.random_range(-10..10)).collect();
+            let best = (0..len)
+                .flat_map(|start| (start + 1..=len).map(move |end| (start, end)))
+                .map(|(start, end)| sum(&nums, start, end))
+                .max()
+                .unwrap();
+            let circular_best = (0..len)
+                .flat_map(|start| (1..=len).map(move |n| (start, (start + n) % len)))
+                .map(|(start, end)| sum(&nums, start, end))
+                .max()
+                .unwrap();
+
+            for sub in [
+                max_subarray(&nums).unwrap(),
+                max_subarray_divide_and_conquer(&nums).unwrap(),
+            ] {
+                assert_eq!(sub.sum, best, "{:?}", nums);
+                assert_eq!(sum(&nums, sub.start, sub.end), sub.sum);
+            }
+            let sub = max_subarray_circular(&nums).unwrap();
+            assert_eq!(sub.sum, circular_best, "{:?}", nums);
+            assert_eq!(sum(&nums, sub.start, sub.end), sub.sum);
+        }
+    }
+
+    #[test]
+    fn test_max_subarray_circular() {
+        let sub = max_subarray_circular(&[5, -3, 5]).unwrap();
+        assert_eq!((sub.sum, sub.start, sub.end), (10, 2, 1));
+        let sub = max_subarray_circular(&[-3, -2, -3]).unwrap();
+        assert_eq!((sub.sum, sub.start, sub.end), (-2, 1, 2));
+        assert_eq!(max_subarray_circular::<i32>(&[]), None);
+    }
+
+    #[test]
+    fn test_max_sum_submatrix() {
+        let grid = [
+            [1, 2, -1, -4, -20],
+            [-8, -3, 4, 2, 1],
+            [3, 8, 10, 1, 3],
+            [-4, -1, 1, 7, -6],
+        ];
+        let expected = Submatrix {
+            sum: 29,
+            rows: 1..4,
+            cols: 1..4,
+        };
+        assert_eq!(max_sum_submatrix(&grid), Some(expected));
+        assert_eq!(max_sum_submatrix::<i32, Vec<i32>>(&[]), None);
+
+        let mut rng = StdRng::seed_from_u64(23);
+        for _ in 0..50 {
+            let (height, width) = (rng.random_range(1..6), rng.random_range(1..6));
+            let grid: Vec<Vec<i64>> = (0..height)
+                .map(|_| (0..width).map(|_| rng.random_range(-9..10)).collect())
+                .collect();
+            let block_sum = |rows: Range<usize>, cols: Range<usize>| -> i64 {
+                grid[rows]
+                    .iter()
+                    .map(|row| row[cols.clone()].iter().sum::<i64>())
+                    .sum()
+            };
+            let mut best = i64::MIN;
+            for top in 0..height {
+                for bottom in top + 1..=height {
+                    for left in 0..width {
+                        for right in left + 1..=width {
+                            best = best.max(block_sum(top..bottom, left..right));
+                        }
+                    }
+                }
+            }
+            let found = max_sum_submatrix(&grid).unwrap();
+            assert_eq!(found.sum, best);
+            assert_eq!(block_sum(found.rows, found.cols), best);
+        }
+    }
 }
@@ -0,0 +1,123 @@
+/// The outline of buildings `(left, right, height)` standing on the ground, as the points
+/// `(x, height)` where its height changes, the last dropping to the default height. Buildings
+/// are split in halves whose skylines are merged, in O(n log n).
+pub fn skyline<X: Copy + Ord, H: Copy + Ord + Default>(buildings: &[(X, X, H)]) -> Vec<(X, H)> {
+    match buildings {
+        [] => Vec::new(),
+        &[(left, right, height)] => {
+            if left < right && height > H::default() {
+                vec![(left, height), (right, H::default())]
+            } else {
+                Vec::new()
+            }
+        }
+        _ => {
+            let (left, right) = buildings.split_at(buildings.len() / 2);
+            merge(&skyline(left), &skyline(right))
+        }
+    }
+}
+
+/// Walks both skylines from left to right, keeping the higher of their current heights.
+fn merge<X: Copy + Ord, H: Copy + Ord + Default>(a: &[(X, H)], b: &[(X, H)]) -> Vec<(X, H)> {
+    let mut merged: Vec<(X, H)> = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    let (mut height_a, mut height_b) = (H::default(), H::default());
+    while i < a.len() || j < b.len() {
+        let x = match (a.get(i), b.get(j)) {
+            (Some(&(xa, _)), Some(&(xb, _))) => xa.min(xb),
+            (Some(&(xa, _)), None) => xa,
+            (None, Some(&(xb, _))) => xb,
+            (None, None) => unreachable!(),
+        };
+        if let Some(&(xa, height)) = a.get(i)
+            && xa == x
+        {
+            height_a = height;
+            i += 1;
+        }
+        if let Some(&(xb, height)) = b.get(j)
+            && xb == x
+        {
+            height_b = height;
+            j += 1;
+        }
+        let height = height_a.max(height_b);
+        if merged.last().is_none_or(|&(_, last)| last != height) {
+            merged.push((x, height));
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// The height over each unit step from every building's edge.
+    fn brute_force(buildings: &[(i32, i32, u32)]) -> Vec<(i32, u32)> {
+        let mut xs: Vec<i32> = buildings.iter().flat_map(|&(l, r, _)| [l, r]).collect();
+        xs.sort();
+        xs.dedup();
+        let mut outline: Vec<(i32, u32)> = Vec::new();
+        for x in xs {
+            let height = buildings
+                .iter()
+                .filter(|&&(l, r, _)| l <= x && x < r)
+                .map(|&(_, _, h)| h)
+                .max()
+                .unwrap_or(0);
+            if outline.last().map_or(0, |&(_, last)| last) != height {
+                outline.push((x, height));
+            }
+        }
+        outline
+    }
+
+    #[test]
+    fn test_skyline() {
+        let buildings = [
+            (2, 9, 10),
+            (3, 7, 15),
+            (5, 12, 12),
+            (15, 20, 10),
+            (19, 24, 8),
+        ];
+        assert_eq!(
+            skyline(&buildings),
+            [
+                (2, 10),
+                (3, 15),
+                (7, 12),
+                (12, 0),
+                (15, 10),
+                (20, 8),
+                (24, 0)
+            ]
+        );
+        assert_eq!(skyline(&[(0, 2, 3), (2, 5, 3)]), [(0, 3), (5, 0)]);
+        assert_eq!(skyline::<i32, u32>(&[]), []);
+        assert_eq!(skyline(&[(1, 1, 5), (2, 3, 0)]), []);
+    }
+
+    #[test]
+    fn test_skyline_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(27);
+        for len in 0..80 {
+            let buildings: Vec<(i32, i32, u32)> = (0..len)
+                .map(|_| {
+                    let left = rng.random_range(0..30);
+                    (left, left + rng.random_range(1..10), rng.random_range(0..6))
+                })
+                .collect();
+            assert_eq!(
+                skyline(&buildings),
+                brute_force(&buildings),
+                "{:?}",
+                buildings
+            );
+        }
+    }
+}
@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 
+pub mod arithmetic;
 pub mod divide_and_conquer;
 pub mod search;
 pub mod sort;
+pub mod streaming;
@@ -0,0 +1,104 @@
+use std::ops::RangeInclusive;
+
+/// Binary search on the answer: the least `x` in `range` for which `feasible(x)` holds, given
+/// that it is false then true across the range. `None` if it is false everywhere.
+pub fn min_feasible<P: FnMut(i64) -> bool>(
+    range: RangeInclusive<i64>,
+    mut feasible: P,
+) -> Option<i64> {
+    let (mut lo, mut hi) = range.into_inner();
+    if lo > hi || !feasible(hi) {
+        return None;
+    }
+    // feasible(hi) holds and nothing below lo does
+    while lo < hi {
+        let mid = lo + (hi.abs_diff(lo) / 2) as i64;
+        if feasible(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    Some(lo)
+}
+
+/// The greatest `x` in `range` for which `feasible(x)` holds, given that it is true then
+/// false across the range. `None` if it is false everywhere.
+pub fn max_feasible<P: FnMut(i64) -> bool>(
+    range: RangeInclusive<i64>,
+    mut feasible: P,
+) -> Option<i64> {
+    let (mut lo, mut hi) = range.into_inner();
+    if lo > hi || !feasible(lo) {
+        return None;
+    }
+    // feasible(lo) holds and nothing above hi does
+    while lo < hi {
+        let mid = hi - (hi.abs_diff(lo) / 2) as i64;
+        if feasible(mid) {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    Some(lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether packages can ship in order within `days`, loading at most `capacity` a day.
+    fn ships_in(weights: &[i64], days: i64, capacity: i64) -> bool {
+        let mut needed = 1;
+        let mut load = 0;
+        for &weight in weights {
+            if weight > capacity {
+                return false;
+            }
+            if load + weight > capacity {
+                needed += 1;
+                load = 0;
+            }
+            load += weight;
+        }
+        needed <= days
+    }
+
+    fn ship_within_days(weights: &[i64], days: i64) -> i64 {
+        let total = weights.iter().sum();
+        min_feasible(1..=total, |capacity| ships_in(weights, days, capacity)).unwrap()
+    }
+
+    #[test]
+    fn test_ship_within_days() {
+        assert_eq!(ship_within_days(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10], 5), 15);
+        assert_eq!(ship_within_days(&[3, 2, 2, 4, 1, 4], 3), 6);
+        assert_eq!(ship_within_days(&[1, 2, 3, 1, 1], 4), 3);
+        assert_eq!(ship_within_days(&[7], 1), 7);
+    }
+
+    #[test]
+    fn test_ship_within_days_matches_linear_scan() {
+        let weights = [5, 1, 8, 3, 3, 9, 2, 6, 4, 7];
+        for days in 1..=10 {
+            let linear = (1..).find(|&c| ships_in(&weights, days, c)).unwrap();
+            assert_eq!(ship_within_days(&weights, days), linear);
+        }
+    }
+
+    #[test]
+    fn test_feasible_bounds() {
+        // Integer square root: the greatest x with x * x <= n
+        let n = 1_000_000_007;
+        assert_eq!(max_feasible(0..=n, |x| x * x <= n), Some(31622));
+        assert_eq!(min_feasible(-50..=50, |x| x >= -7), Some(-7));
+        assert_eq!(min_feasible(0..=10, |_| false), None);
+        assert_eq!(max_feasible(0..=10, |_| false), None);
+        assert_eq!(max_feasible(0..=10, |_| true), Some(10));
+        assert_eq!(
+            max_feasible(i64::MIN..=i64::MAX, |x| x < i64::MAX),
+            Some(i64::MAX - 1)
+        );
+    }
+}
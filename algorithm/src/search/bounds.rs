@@ -0,0 +1,113 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// The index of the first element for which `pred` is false, in a slice where it holds for a
+/// prefix and fails for the rest.
+pub fn partition_point<T, P: FnMut(&T) -> bool>(arr: &[T], mut pred: P) -> usize {
+    let (mut lo, mut hi) = (0, arr.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(&arr[mid]) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// The first index whose element is not less than `target`: where `target` would be inserted
+/// before any equal elements.
+pub fn lower_bound<T: Ord>(arr: &[T], target: &T) -> usize {
+    lower_bound_by(arr, |x| x.cmp(target))
+}
+
+/// `compare` orders an element against the target, as in `slice::binary_search_by`.
+pub fn lower_bound_by<T, F: FnMut(&T) -> Ordering>(arr: &[T], mut compare: F) -> usize {
+    partition_point(arr, |x| compare(x) == Ordering::Less)
+}
+
+pub fn lower_bound_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &[T], key: &K, mut f: F) -> usize {
+    lower_bound_by(arr, |x| f(x).cmp(key))
+}
+
+/// The first index whose element is greater than `target`: where `target` would be inserted
+/// after any equal elements.
+pub fn upper_bound<T: Ord>(arr: &[T], target: &T) -> usize {
+    upper_bound_by(arr, |x| x.cmp(target))
+}
+
+pub fn upper_bound_by<T, F: FnMut(&T) -> Ordering>(arr: &[T], mut compare: F) -> usize {
+    partition_point(arr, |x| compare(x) != Ordering::Greater)
+}
+
+pub fn upper_bound_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &[T], key: &K, mut f: F) -> usize {
+    upper_bound_by(arr, |x| f(x).cmp(key))
+}
+
+/// The range of elements equal to `target`, empty at its insertion point when there are none.
+pub fn equal_range<T: Ord>(arr: &[T], target: &T) -> Range<usize> {
+    equal_range_by(arr, |x| x.cmp(target))
+}
+
+pub fn equal_range_by<T, F: FnMut(&T) -> Ordering>(arr: &[T], mut compare: F) -> Range<usize> {
+    let start = lower_bound_by(arr, &mut compare);
+    // The equal elements can only follow the lower bound
+    start..start + upper_bound_by(&arr[start..], compare)
+}
+
+pub fn equal_range_by_key<T, K: Ord, F: FnMut(&T) -> K>(
+    arr: &[T],
+    key: &K,
+    mut f: F,
+) -> Range<usize> {
+    equal_range_by(arr, |x| f(x).cmp(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_bounds() {
+        let arr = [1, 2, 2, 2, 5, 8];
+        assert_eq!(lower_bound(&arr, &2), 1);
+        assert_eq!(upper_bound(&arr, &2), 4);
+        assert_eq!(equal_range(&arr, &2), 1..4);
+        assert_eq!(equal_range(&arr, &3), 4..4);
+        assert_eq!(lower_bound(&arr, &0), 0);
+        assert_eq!(upper_bound(&arr, &9), 6);
+        assert_eq!(equal_range(&[] as &[i32], &1), 0..0);
+        assert_eq!(partition_point(&arr, |&x| x < 5), 4);
+    }
+
+    #[test]
+    fn test_bounds_by_key() {
+        let people = [("ann", 20), ("bob", 25), ("cy", 25), ("dee", 31)];
+        assert_eq!(lower_bound_by_key(&people, &25, |&(_, age)| age), 1);
+        assert_eq!(upper_bound_by_key(&people, &25, |&(_, age)| age), 3);
+        assert_eq!(equal_range_by_key(&people, &31, |&(_, age)| age), 3..4);
+
+        // A descending slice searched with a reversed comparator
+        let arr = [9, 7, 7, 3];
+        assert_eq!(equal_range_by(&arr, |x| 7.cmp(x)), 1..3);
+    }
+
+    #[test]
+    fn test_bounds_match_std() {
+        let mut rng = StdRng::seed_from_u64(14);
+        for len in 0..100 {
+            let mut arr: Vec<i32> = (0..len).map(|_| rng.random_range(0..20)).collect();
+            arr.sort();
+            for target in -1..21 {
+                let lower = arr.partition_point(|&x| x < target);
+                let upper = arr.partition_point(|&x| x <= target);
+                assert_eq!(lower_bound(&arr, &target), lower);
+                assert_eq!(upper_bound(&arr, &target), upper);
+                assert_eq!(equal_range(&arr, &target), lower..upper);
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use super::lower_bound_by;
+use std::cmp::Ordering;
+
+/// Binary search that first doubles a bound from the front, so finding index i costs O(log i)
+/// whatever the slice's length. Returns `Ok` with the first index of an equal element, or `Err`
+/// with the index where `target` would be inserted.
+pub fn exponential_search<T: Ord>(arr: &[T], target: &T) -> Result<usize, usize> {
+    exponential_search_by(arr, |x| x.cmp(target))
+}
+
+/// `compare` orders an element against the target, as in `slice::binary_search_by`.
+pub fn exponential_search_by<T, F: FnMut(&T) -> Ordering>(
+    arr: &[T],
+    mut compare: F,
+) -> Result<usize, usize> {
+    // After doubling, arr[..lo] is less than the target and arr[hi..] is not
+    let (mut lo, mut hi) = (0, 1);
+    while hi <= arr.len() && compare(&arr[hi - 1]) == Ordering::Less {
+        lo = hi;
+        hi *= 2;
+    }
+    let hi = hi.min(arr.len());
+
+    let index = lo + lower_bound_by(&arr[lo..hi], &mut compare);
+    match arr.get(index).map(compare) {
+        Some(Ordering::Equal) => Ok(index),
+        _ => Err(index),
+    }
+}
+
+pub fn exponential_search_by_key<T, K: Ord, F: FnMut(&T) -> K>(
+    arr: &[T],
+    key: &K,
+    mut f: F,
+) -> Result<usize, usize> {
+    exponential_search_by(arr, |x| f(x).cmp(key))
+}
+
+/// The least `n` for which `pred(n)` holds, where `pred` is false then true on 0, 1, 2, ...
+/// with no known upper end. Returns `None` if it is still false at `usize::MAX`.
+pub fn exponential_search_unbounded<P: FnMut(usize) -> bool>(mut pred: P) -> Option<usize> {
+    // Probes 0, 1, 3, 7, ... up to usize::MAX
+    let (mut lo, mut hi) = (0, 0);
+    while !pred(hi) {
+        if hi == usize::MAX {
+            return None;
+        }
+        lo = hi + 1;
+        hi = hi.saturating_mul(2).saturating_add(1);
+    }
+
+    // pred is false below lo and true at hi
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    Some(lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_exponential_search() {
+        let arr = [1, 3, 3, 5, 8, 13, 21];
+        assert_eq!(exponential_search(&arr, &3), Ok(1));
+        assert_eq!(exponential_search(&arr, &21), Ok(6));
+        assert_eq!(exponential_search(&arr, &4), Err(3));
+        assert_eq!(exponential_search(&arr, &30), Err(7));
+        assert_eq!(exponential_search(&[] as &[i32], &1), Err(0));
+        assert_eq!(
+            exponential_search_by_key(&[("a", 1), ("b", 4)], &4, |&(_, v)| v),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn test_exponential_search_matches_lower_bound() {
+        let mut rng = StdRng::seed_from_u64(15);
+        for len in 0..200 {
+            let mut arr: Vec<i32> = (0..len).map(|_| rng.random_range(0..50)).collect();
+            arr.sort();
+            for target in -1..51 {
+                let index = arr.partition_point(|&x| x < target);
+                let expected = if arr.get(index) == Some(&target) {
+                    Ok(index)
+                } else {
+                    Err(index)
+                };
+                assert_eq!(exponential_search(&arr, &target), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_exponential_search_unbounded() {
+        // The integer square root of a huge number, without a bound to start from
+        let n: usize = 123_456_789_012;
+        let root = exponential_search_unbounded(|x| x.saturating_mul(x) > n).unwrap() - 1;
+        assert_eq!(root, 351_364);
+        assert_eq!(exponential_search_unbounded(|_| true), Some(0));
+        assert_eq!(exponential_search_unbounded(|x| x >= 1000), Some(1000));
+        assert_eq!(
+            exponential_search_unbounded(|x| x == usize::MAX),
+            Some(usize::MAX)
+        );
+        assert_eq!(exponential_search_unbounded(|_| false), None);
+    }
+}
@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+
+/// Search of a sorted slice of numbers that probes where the target should be if the values
+/// were spread evenly: O(log log n) steps on uniformly distributed keys. A probe that fails to
+/// halve the range is followed by a bisection, which bounds the worst case at O(log n).
+/// Returns `Ok` with the first index of an equal element, or `Err` with the insertion point.
+pub fn interpolation_search<T: Copy + Into<f64>>(arr: &[T], target: T) -> Result<usize, usize> {
+    interpolation_search_by_key(arr, target.into(), |&x| x.into())
+}
+
+/// `key` maps the elements, sorted by it, to the numbers searched for `target`.
+pub fn interpolation_search_by_key<T, F: FnMut(&T) -> f64>(
+    arr: &[T],
+    target: f64,
+    mut key: F,
+) -> Result<usize, usize> {
+    // arr[..lo] is less than the target and arr[hi..] is not
+    let (mut lo, mut hi) = (0, arr.len());
+    let mut bisect = false;
+    while lo < hi {
+        let (first, last) = (key(&arr[lo]), key(&arr[hi - 1]));
+        let mid = if bisect || first.partial_cmp(&last) != Some(Ordering::Less) {
+            lo + (hi - lo) / 2
+        } else {
+            let fraction = ((target - first) / (last - first)).clamp(0.0, 1.0);
+            lo + (fraction * (hi - 1 - lo) as f64) as usize
+        };
+
+        let before = hi - lo;
+        if key(&arr[mid]) < target {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+        bisect = !bisect && 2 * (hi - lo) > before;
+    }
+
+    match arr.get(lo) {
+        Some(x) if key(x) == target => Ok(lo),
+        _ => Err(lo),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_interpolation_search() {
+        let arr = [10, 20, 30, 30, 40, 50, 60];
+        assert_eq!(interpolation_search(&arr, 30), Ok(2));
+        assert_eq!(interpolation_search(&arr, 60), Ok(6));
+        assert_eq!(interpolation_search(&arr, 35), Err(4));
+        assert_eq!(interpolation_search(&arr, 5), Err(0));
+        assert_eq!(interpolation_search(&arr, 99), Err(7));
+        assert_eq!(interpolation_search(&[0.5, 1.5, 2.5], 1.5), Ok(1));
+        assert_eq!(interpolation_search(&[7u8; 5], 7), Ok(0));
+
+        let big = [-5_000_000_000i64, 0, 7_000_000_000];
+        assert_eq!(interpolation_search_by_key(&big, 0.0, |&x| x as f64), Ok(1));
+    }
+
+    #[test]
+    fn test_interpolation_search_matches_lower_bound() {
+        let mut rng = StdRng::seed_from_u64(16);
+        for len in 0..100 {
+            // Uniform keys, and skewed ones where interpolation guesses badly
+            for skew in [1, 3] {
+                let mut arr: Vec<u32> = (0..len)
+                    .map(|_| rng.random_range(0..100u32).pow(skew))
+                    .collect();
+                arr.sort();
+                for target in (0..100u32).map(|t| t.pow(skew)) {
+                    let index = arr.partition_point(|&x| x < target);
+                    let expected = if arr.get(index) == Some(&target) {
+                        Ok(index)
+                    } else {
+                        Err(index)
+                    };
+                    assert_eq!(interpolation_search(&arr, target), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_interpolation_search_is_fast_on_uniform_keys() {
+        let arr: Vec<u32> = (0..1_000_000).map(|i| i * 3).collect();
+        let mut probes = 0;
+        let result = interpolation_search_by_key(&arr, 2_345_679.0, |&x| {
+            probes += 1;
+            x as f64
+        });
+        assert_eq!(result, Ok(781_893));
+        assert!(probes < 20, "{} probes", probes);
+    }
+}
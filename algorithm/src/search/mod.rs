@@ -1,2 +1,17 @@
 mod binary_search;
 pub use binary_search::*;
+
+mod bounds;
+pub use bounds::*;
+
+mod exponential_search;
+pub use exponential_search::*;
+
+mod interpolation_search;
+pub use interpolation_search::*;
+
+mod ternary_search;
+pub use ternary_search::*;
+
+mod answer_search;
+pub use answer_search::*;
@@ -0,0 +1,180 @@
+use std::ops::RangeInclusive;
+
+/// The argument in `range` where a unimodal `f` peaks: strictly increasing, then strictly
+/// decreasing. Takes O(log n) evaluations.
+pub fn ternary_search_max<T: PartialOrd, F: FnMut(i64) -> T>(
+    range: RangeInclusive<i64>,
+    mut f: F,
+) -> i64 {
+    let (mut lo, mut hi) = range.into_inner();
+    assert!(lo <= hi, "empty range");
+    while hi.abs_diff(lo) > 2 {
+        let third = (hi.abs_diff(lo) / 3) as i64;
+        let (m1, m2) = (lo + third, hi - third);
+        // The peak can't be left of m1 if f rises there, nor right of m2 otherwise
+        if f(m1) < f(m2) {
+            lo = m1 + 1;
+        } else {
+            hi = m2;
+        }
+    }
+
+    let mut best = lo;
+    let mut best_value = f(lo);
+    for x in lo + 1..=hi {
+        let value = f(x);
+        if value > best_value {
+            (best, best_value) = (x, value);
+        }
+    }
+    best
+}
+
+/// The argument in `range` where a unimodal `f` bottoms out.
+pub fn ternary_search_min<T: PartialOrd, F: FnMut(i64) -> T>(
+    range: RangeInclusive<i64>,
+    mut f: F,
+) -> i64 {
+    ternary_search_max(range, |x| std::cmp::Reverse(f(x)))
+}
+
+/// The argument in `[lo, hi]` where a unimodal `f` peaks, to within `tolerance` or as close as
+/// floats that size can get. Panics unless `tolerance` is positive.
+pub fn ternary_search_max_f64<F: FnMut(f64) -> f64>(
+    lo: f64,
+    hi: f64,
+    tolerance: f64,
+    mut f: F,
+) -> f64 {
+    assert!(tolerance > 0.0, "tolerance {} is not positive", tolerance);
+    let (mut lo, mut hi) = (lo, hi);
+    while hi - lo > tolerance {
+        let third = (hi - lo) / 3.0;
+        let (m1, m2) = (lo + third, hi - third);
+        // Past the precision of the floats, so the interval would stop shrinking
+        if !(lo < m1 && m1 < m2 && m2 < hi) {
+            break;
+        }
+        if f(m1) < f(m2) {
+            lo = m1;
+        } else {
+            hi = m2;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+pub fn ternary_search_min_f64<F: FnMut(f64) -> f64>(
+    lo: f64,
+    hi: f64,
+    tolerance: f64,
+    mut f: F,
+) -> f64 {
+    ternary_search_max_f64(lo, hi, tolerance, |x| -f(x))
+}
+
+/// The argument in `[lo, hi]` where a unimodal `f` bottoms out, to within `tolerance` or as
+/// close as floats that size can get. Panics unless `tolerance` is positive.
+/// Splitting at the golden ratio lets each step reuse one of the previous evaluations, so it
+/// needs one evaluation per step where ternary search needs two.
+pub fn golden_section_min<F: FnMut(f64) -> f64>(lo: f64, hi: f64, tolerance: f64, mut f: F) -> f64 {
+    assert!(tolerance > 0.0, "tolerance {} is not positive", tolerance);
+    // 1 / phi
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut lo, mut hi) = (lo, hi);
+    let mut m1 = hi - ratio * (hi - lo);
+    let mut m2 = lo + ratio * (hi - lo);
+    let (mut f1, mut f2) = (f(m1), f(m2));
+    while hi - lo > tolerance && lo < m1 && m1 < m2 && m2 < hi {
+        if f1 < f2 {
+            (hi, m2, f2) = (m2, m1, f1);
+            m1 = hi - ratio * (hi - lo);
+            f1 = f(m1);
+        } else {
+            (lo, m1, f1) = (m1, m2, f2);
+            m2 = lo + ratio * (hi - lo);
+            f2 = f(m2);
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+pub fn golden_section_max<F: FnMut(f64) -> f64>(lo: f64, hi: f64, tolerance: f64, mut f: F) -> f64 {
+    golden_section_min(lo, hi, tolerance, |x| -f(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ternary_search() {
+        assert_eq!(ternary_search_max(-100..=100, |x| -(x - 17) * (x - 17)), 17);
+        assert_eq!(ternary_search_min(-100..=100, |x| (x + 42).abs()), -42);
+        assert_eq!(ternary_search_max(0..=1000, |x| x), 1000);
+        assert_eq!(ternary_search_max(5..=5, |x| x), 5);
+
+        // A peak in an array that rises then falls
+        let arr = [1, 4, 9, 12, 15, 11, 6, 2];
+        assert_eq!(ternary_search_max(0..=7, |i| arr[i as usize]), 4);
+    }
+
+    #[test]
+    fn test_ternary_search_every_peak() {
+        for len in 1..40 {
+            for peak in 0..len {
+                let f = |x: i64| -(x - peak).abs();
+                assert_eq!(ternary_search_max(0..=len - 1, f), peak);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ternary_search_f64() {
+        let x = ternary_search_max_f64(0.0, std::f64::consts::PI, 1e-9, f64::sin);
+        assert!((x - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+        let x = ternary_search_min_f64(-10.0, 10.0, 1e-9, |x| (x - 3.25).powi(2) + 1.0);
+        assert!((x - 3.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_golden_section() {
+        let mut evaluations = 0;
+        let x = golden_section_min(-10.0, 10.0, 1e-9, |x| {
+            evaluations += 1;
+            (x - 3.25).powi(2) + 1.0
+        });
+        assert!((x - 3.25).abs() < 1e-6);
+        assert!(evaluations < 60, "{} evaluations", evaluations);
+
+        let x = golden_section_max(0.0, std::f64::consts::PI, 1e-9, f64::sin);
+        assert!((x - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tolerance_below_float_precision() {
+        // Floats near 1.5e10 are about 2e-6 apart, far coarser than the tolerance
+        let f = |x: f64| (x - 1.5e10).powi(2);
+        let x = golden_section_min(1e10, 2e10, 1e-9, f);
+        assert!((x - 1.5e10).abs() < 1e-4, "{}", x);
+        let x = ternary_search_min_f64(1e10, 2e10, 1e-9, f);
+        assert!((x - 1.5e10).abs() < 1e-4, "{}", x);
+
+        let x = ternary_search_max_f64(0.0, 1.0, f64::MIN_POSITIVE, |x| -(x - 0.3).abs());
+        assert!((x - 0.3).abs() < 1e-12, "{}", x);
+        let x = golden_section_max(0.0, 1.0, f64::MIN_POSITIVE, |x| -(x - 0.3).abs());
+        assert!((x - 0.3).abs() < 1e-12, "{}", x);
+    }
+
+    #[test]
+    #[should_panic(expected = "not positive")]
+    fn test_zero_tolerance() {
+        ternary_search_max_f64(0.0, 1.0, 0.0, f64::sin);
+    }
+
+    #[test]
+    #[should_panic(expected = "not positive")]
+    fn test_nan_tolerance() {
+        golden_section_min(0.0, 1.0, f64::NAN, f64::sin);
+    }
+}
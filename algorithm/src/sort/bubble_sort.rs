@@ -1,16 +1,54 @@
+use super::{Probe, Sorter, swap};
+use std::cmp::Ordering;
+
+pub struct BubbleSort;
+
+impl Sorter for BubbleSort {
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], mut compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>,
+    {
+        _bubble_sort(
+            arr,
+            arr.len(),
+            &mut |a, b| compare(a, b) == Ordering::Less,
+            probe,
+        );
+    }
+}
+
 pub fn bubble_sort<T: PartialOrd>(arr: &mut [T]) {
-    _bubble_sort(arr, arr.len());
+    _bubble_sort(arr, arr.len(), &mut |a, b| a < b, &mut ());
+}
+
+pub fn bubble_sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mut compare: F) {
+    _bubble_sort(
+        arr,
+        arr.len(),
+        &mut |a, b| compare(a, b) == Ordering::Less,
+        &mut (),
+    );
 }
 
-fn _bubble_sort<T: PartialOrd>(arr: &mut [T], n: usize) {
+pub fn bubble_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    _bubble_sort(arr, arr.len(), &mut |a, b| key(a) < key(b), &mut ());
+}
+
+fn _bubble_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    n: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
     if n <= 1 {
         return; // No need to sort if the array has 0 or 1 elements
     }
 
     let mut swapped = false;
     for i in 0..n - 1 {
-        if arr[i] > arr[i + 1] {
-            arr.swap(i, i + 1);
+        if is_less(&arr[i + 1], &arr[i]) {
+            swap(arr, i, i + 1, probe);
             swapped = true;
         }
     }
@@ -19,7 +57,7 @@ fn _bubble_sort<T: PartialOrd>(arr: &mut [T], n: usize) {
         return; // If no elements were swapped, the array is sorted
     }
 
-    _bubble_sort(arr, n - 1);
+    _bubble_sort(arr, n - 1, is_less, probe);
 }
 
 fn bubble_sort2<T: PartialOrd>(arr: &mut [T]) {
@@ -55,4 +93,11 @@ mod tests {
         bubble_sort2(&mut arr);
         assert_eq!(arr, [11, 12, 22, 25, 34, 64, 90]);
     }
+
+    #[test]
+    fn test_bubble_sort_by() {
+        let mut arr = [64, 34, 25, 12, 22, 11, 90];
+        bubble_sort_by(&mut arr, |a, b| b.cmp(a));
+        assert_eq!(arr, [90, 64, 34, 25, 22, 12, 11]);
+    }
 }
@@ -0,0 +1,88 @@
+use super::{RadixKey, lsd_radix_sort_by_key};
+
+/// Stable counting sort of integers, O(n + k) time and O(k) memory where k is the spread between
+/// the smallest and largest value, so it only suits keys from a small range. When the spread is
+/// too wide for that, it falls back to LSD radix sort.
+pub fn counting_sort<T: RadixKey + Clone>(arr: &mut [T]) {
+    counting_sort_by_key(arr, T::clone);
+}
+
+/// Counts for spreads up to this wide cost little even for short slices.
+const MAX_SPREAD: u64 = 1 << 16;
+
+pub fn counting_sort_by_key<T: Clone, K: RadixKey, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    let keys: Vec<u64> = arr.iter().map(|x| key(x).radix()).collect();
+    let (Some(&min), Some(&max)) = (keys.iter().min(), keys.iter().max()) else {
+        return;
+    };
+
+    let spread = max - min;
+    if spread > MAX_SPREAD.max(4 * keys.len() as u64) {
+        lsd_radix_sort_by_key(arr, key);
+        return;
+    }
+
+    let mut starts = vec![0; spread as usize + 2];
+    for k in &keys {
+        starts[(k - min) as usize + 1] += 1;
+    }
+    for i in 1..starts.len() {
+        starts[i] += starts[i - 1];
+    }
+
+    let items = arr.to_vec();
+    for (x, k) in items.into_iter().zip(keys) {
+        let slot = &mut starts[(k - min) as usize];
+        arr[*slot] = x;
+        *slot += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_counting_sort() {
+        let mut arr = [3, -6, 8, 10, 3, 2, -9];
+        counting_sort(&mut arr);
+        assert_eq!(arr, [-9, -6, 2, 3, 3, 8, 10]);
+    }
+
+    #[test]
+    fn test_counting_sort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for len in 0..100 {
+            let arr: Vec<i16> = (0..len).map(|_| rng.random_range(-300..300)).collect();
+            let mut expected = arr.clone();
+            expected.sort();
+            let mut actual = arr.clone();
+            counting_sort(&mut actual);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_counting_sort_by_key_is_stable() {
+        let mut arr = [("c", 2u8), ("a", 1), ("d", 2), ("b", 0), ("e", 1)];
+        counting_sort_by_key(&mut arr, |&(_, k)| k);
+        assert_eq!(arr, [("b", 0), ("a", 1), ("e", 1), ("c", 2), ("d", 2)]);
+    }
+
+    #[test]
+    fn test_counting_sort_wide_spread() {
+        let mut arr = [u64::MAX, 0, 7];
+        counting_sort(&mut arr);
+        assert_eq!(arr, [0, 7, u64::MAX]);
+
+        let mut arr = [i64::MAX, i64::MIN, -1, i64::MIN];
+        counting_sort(&mut arr);
+        assert_eq!(arr, [i64::MIN, i64::MIN, -1, i64::MAX]);
+
+        let mut arr = [("b", u32::MAX), ("a", 0), ("c", u32::MAX)];
+        counting_sort_by_key(&mut arr, |&(_, k)| k);
+        assert_eq!(arr, [("a", 0), ("b", u32::MAX), ("c", u32::MAX)]);
+    }
+}
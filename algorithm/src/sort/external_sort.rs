@@ -0,0 +1,370 @@
+use super::tim_sort_by;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// How records are laid out in the files being sorted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordFormat {
+    /// Newline-terminated records. The newline is not part of the record, and every record is
+    /// written back with one, including a last line that had none.
+    Lines,
+    /// Binary records of exactly this many bytes, at least one.
+    Fixed(usize),
+}
+
+impl RecordFormat {
+    /// Reads the next record into `record`, returning false at the end of the input.
+    fn read(self, reader: &mut impl BufRead, record: &mut Vec<u8>) -> io::Result<bool> {
+        record.clear();
+        match self {
+            RecordFormat::Lines => {
+                if reader.read_until(b'\n', record)? == 0 {
+                    return Ok(false);
+                }
+                if record.last() == Some(&b'\n') {
+                    record.pop();
+                }
+                Ok(true)
+            }
+            RecordFormat::Fixed(size) => {
+                reader.take(size as u64).read_to_end(record)?;
+                match record.len() {
+                    0 => Ok(false),
+                    len if len == size => Ok(true),
+                    len => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("input ends with a partial record of {} bytes", len),
+                    )),
+                }
+            }
+        }
+    }
+
+    fn write(self, writer: &mut impl Write, record: &[u8]) -> io::Result<()> {
+        writer.write_all(record)?;
+        if self == RecordFormat::Lines {
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SortSummary {
+    pub records: usize,
+    /// Sorted runs written to temporary files; 0 when the input fit in memory.
+    pub runs: usize,
+    /// Merges of runs into longer runs needed before the final merge.
+    pub merge_passes: usize,
+}
+
+/// Sorts inputs larger than memory: sorted runs that fit the memory budget go to temporary
+/// files, which a k-way heap merge then combines. The sort is stable.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExternalSort {
+    pub format: RecordFormat,
+    /// Bytes of records to hold in memory at once, counting each record's bookkeeping.
+    pub memory_budget: usize,
+    /// The most runs merged at once, which is also the number of files open at once.
+    pub max_fan_in: usize,
+    /// Where the directory holding the runs is created; it is removed when the sort ends.
+    pub temp_dir: PathBuf,
+}
+
+impl ExternalSort {
+    /// Panics if `format` is fixed-width records of 0 bytes.
+    pub fn new(format: RecordFormat) -> Self {
+        assert!(
+            format != RecordFormat::Fixed(0),
+            "fixed-width records must be at least 1 byte"
+        );
+        Self {
+            format,
+            memory_budget: 64 << 20,
+            max_fan_in: 64,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+
+    /// Sorts the records bytewise.
+    pub fn sort(&self, input: impl Read, output: impl Write) -> io::Result<SortSummary> {
+        self.sort_by(input, output, |a, b| a.cmp(b))
+    }
+
+    pub fn sort_by_key<K: Ord, F: FnMut(&[u8]) -> K>(
+        &self,
+        input: impl Read,
+        output: impl Write,
+        mut key: F,
+    ) -> io::Result<SortSummary> {
+        self.sort_by(input, output, |a, b| key(a).cmp(&key(b)))
+    }
+
+    pub fn sort_by<F: FnMut(&[u8], &[u8]) -> Ordering>(
+        &self,
+        input: impl Read,
+        output: impl Write,
+        mut compare: F,
+    ) -> io::Result<SortSummary> {
+        let mut input = BufReader::new(input);
+        let mut output = BufWriter::new(output);
+        let mut summary = SortSummary {
+            records: 0,
+            runs: 0,
+            merge_passes: 0,
+        };
+
+        let mut chunk: Vec<Vec<u8>> = Vec::new();
+        let mut used = 0;
+        let mut record = Vec::new();
+        let mut runs = Vec::new();
+        let mut temp: Option<TempDir> = None;
+        while self.format.read(&mut input, &mut record)? {
+            summary.records += 1;
+            used += record.len() + size_of::<Vec<u8>>();
+            chunk.push(std::mem::take(&mut record));
+            if used >= self.memory_budget {
+                let dir = match &temp {
+                    Some(dir) => dir,
+                    None => temp.insert(TempDir::create(&self.temp_dir)?),
+                };
+                runs.push(self.write_run(dir, &mut chunk, &mut compare)?);
+                used = 0;
+            }
+        }
+
+        let Some(dir) = temp else {
+            // Everything fit in memory
+            tim_sort_by(&mut chunk, |a, b| compare(a, b));
+            for record in &chunk {
+                self.format.write(&mut output, record)?;
+            }
+            output.flush()?;
+            return Ok(summary);
+        };
+        if !chunk.is_empty() {
+            runs.push(self.write_run(&dir, &mut chunk, &mut compare)?);
+        }
+        summary.runs = runs.len();
+
+        // Merging consecutive runs keeps equal records in input order
+        let fan_in = self.max_fan_in.max(2);
+        while runs.len() > fan_in {
+            let mut merged = Vec::new();
+            for group in runs.chunks(fan_in) {
+                let path = dir.next_path();
+                let mut writer = BufWriter::new(File::create(&path)?);
+                self.merge(group, &mut writer, &mut compare)?;
+                writer.flush()?;
+                merged.push(path);
+            }
+            for path in &runs {
+                fs::remove_file(path)?;
+            }
+            runs = merged;
+            summary.merge_passes += 1;
+        }
+
+        self.merge(&runs, &mut output, &mut compare)?;
+        output.flush()?;
+        Ok(summary)
+    }
+
+    fn write_run<F: FnMut(&[u8], &[u8]) -> Ordering>(
+        &self,
+        dir: &TempDir,
+        chunk: &mut Vec<Vec<u8>>,
+        compare: &mut F,
+    ) -> io::Result<PathBuf> {
+        tim_sort_by(chunk, |a, b| compare(a, b));
+        let path = dir.next_path();
+        let mut writer = BufWriter::new(File::create(&path)?);
+        for record in chunk.drain(..) {
+            self.format.write(&mut writer, &record)?;
+        }
+        writer.flush()?;
+        Ok(path)
+    }
+
+    /// K-way merge of sorted run files through a min-heap of their first records. Ties go to
+    /// the earlier run, which keeps the merge stable.
+    fn merge<F: FnMut(&[u8], &[u8]) -> Ordering>(
+        &self,
+        runs: &[PathBuf],
+        output: &mut impl Write,
+        compare: &mut F,
+    ) -> io::Result<()> {
+        let mut readers = Vec::with_capacity(runs.len());
+        let mut heads = Vec::with_capacity(runs.len());
+        let mut heap = Vec::with_capacity(runs.len());
+        for (i, path) in runs.iter().enumerate() {
+            let mut reader = BufReader::new(File::open(path)?);
+            let mut head = Vec::new();
+            if self.format.read(&mut reader, &mut head)? {
+                heap.push(i);
+            }
+            readers.push(reader);
+            heads.push(head);
+        }
+
+        for node in (0..heap.len() / 2).rev() {
+            sift_down(&mut heap, node, &heads, compare);
+        }
+
+        while let Some(&run) = heap.first() {
+            self.format.write(output, &heads[run])?;
+            if !self.format.read(&mut readers[run], &mut heads[run])? {
+                heap.swap_remove(0);
+            }
+            sift_down(&mut heap, 0, &heads, compare);
+        }
+        Ok(())
+    }
+}
+
+/// Restores the heap of run indices below `node`, ordered by the runs' first records.
+fn sift_down<F: FnMut(&[u8], &[u8]) -> Ordering>(
+    heap: &mut [usize],
+    mut node: usize,
+    heads: &[Vec<u8>],
+    compare: &mut F,
+) {
+    let mut is_less = |a: usize, b: usize| match compare(&heads[a], &heads[b]) {
+        Ordering::Equal => a < b,
+        ordering => ordering == Ordering::Less,
+    };
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= heap.len() {
+            return;
+        }
+        if child + 1 < heap.len() && is_less(heap[child + 1], heap[child]) {
+            child += 1;
+        }
+        if !is_less(heap[child], heap[node]) {
+            return;
+        }
+        heap.swap(node, child);
+        node = child;
+    }
+}
+
+/// A uniquely named directory for the runs of one sort, removed with everything in it on drop.
+struct TempDir {
+    path: PathBuf,
+    files: Cell<usize>,
+}
+
+impl TempDir {
+    fn create(parent: &Path) -> io::Result<Self> {
+        static SORTS: AtomicUsize = AtomicUsize::new(0);
+        let sort = SORTS.fetch_add(1, AtomicOrdering::Relaxed);
+        let path = parent.join(format!("external-sort-{}-{}", std::process::id(), sort));
+        fs::create_dir_all(&path)?;
+        Ok(Self {
+            path,
+            files: Cell::new(0),
+        })
+    }
+
+    fn next_path(&self) -> PathBuf {
+        let file = self.files.get();
+        self.files.set(file + 1);
+        self.path.join(format!("run-{}", file))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn sorter(name: &str, format: RecordFormat) -> ExternalSort {
+        let mut sorter = ExternalSort::new(format);
+        sorter.temp_dir = std::env::temp_dir().join(format!("algorithm-{}", name));
+        sorter
+    }
+
+    #[test]
+    fn test_external_sort() {
+        let input = "pear\napple\nfig\nbanana";
+        let mut output = Vec::new();
+        let summary = sorter("in-memory", RecordFormat::Lines)
+            .sort(input.as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(output, b"apple\nbanana\nfig\npear\n");
+        assert_eq!(summary.records, 4);
+        assert_eq!(summary.runs, 0);
+    }
+
+    #[test]
+    fn test_runs_and_merge_passes() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let records: Vec<String> = (0..2000)
+            .map(|i| format!("{},{}", rng.random_range(0..50), i))
+            .collect();
+        let input = records.join("\n");
+
+        // Sorting by the first column only, so the stable order of the second shows
+        let mut sorter = sorter("runs", RecordFormat::Lines);
+        sorter.memory_budget = 2000;
+        sorter.max_fan_in = 4;
+        let mut output = Vec::new();
+        let summary = sorter
+            .sort_by_key(input.as_bytes(), &mut output, |record| {
+                let column = record.split(|&b| b == b',').next().unwrap();
+                std::str::from_utf8(column).unwrap().parse::<u32>().unwrap()
+            })
+            .unwrap();
+
+        let mut expected = records.clone();
+        expected.sort_by_key(|record| record.split(',').next().unwrap().parse::<u32>().unwrap());
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            expected.join("\n") + "\n"
+        );
+        assert_eq!(summary.records, 2000);
+        assert!(summary.runs > 16, "{:?}", summary);
+        assert!(summary.merge_passes >= 2, "{:?}", summary);
+        assert_eq!(fs::read_dir(&sorter.temp_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_fixed_width_records() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let records: Vec<[u8; 8]> = (0..1000)
+            .map(|_| rng.random::<u64>().to_be_bytes())
+            .collect();
+        let input = records.concat();
+
+        let mut sorter = sorter("fixed", RecordFormat::Fixed(8));
+        sorter.memory_budget = 4096;
+        let mut output = Vec::new();
+        let summary = sorter.sort(input.as_slice(), &mut output).unwrap();
+
+        let mut expected = records.clone();
+        expected.sort();
+        assert_eq!(output, expected.concat());
+        assert!(summary.runs > 1);
+
+        let error = sorter.sort(&input[..12], &mut Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 1 byte")]
+    fn test_empty_fixed_width_records() {
+        ExternalSort::new(RecordFormat::Fixed(0));
+    }
+}
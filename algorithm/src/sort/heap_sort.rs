@@ -0,0 +1,116 @@
+use super::{Probe, Sorter, swap};
+use std::cmp::Ordering;
+
+pub struct HeapSort;
+
+impl Sorter for HeapSort {
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], mut compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>,
+    {
+        _heap_sort(
+            arr,
+            0,
+            arr.len(),
+            &mut |a, b| compare(a, b) == Ordering::Less,
+            probe,
+        );
+    }
+}
+
+pub fn heap_sort<T: PartialOrd>(arr: &mut [T]) {
+    _heap_sort(arr, 0, arr.len(), &mut |a, b| a < b, &mut ());
+}
+
+pub fn heap_sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mut compare: F) {
+    _heap_sort(
+        arr,
+        0,
+        arr.len(),
+        &mut |a, b| compare(a, b) == Ordering::Less,
+        &mut (),
+    );
+}
+
+pub fn heap_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    _heap_sort(arr, 0, arr.len(), &mut |a, b| key(a) < key(b), &mut ());
+}
+
+/// Sorts arr[start..end] with a max-heap rooted at `start`.
+pub(crate) fn _heap_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    let len = end - start;
+    for root in (0..len / 2).rev() {
+        sift_down(arr, start, root, len, is_less, probe);
+    }
+
+    for last in (1..len).rev() {
+        swap(arr, start, start + last, probe);
+        sift_down(arr, start, 0, last, is_less, probe);
+    }
+}
+
+// Heap indices are relative to `start`; the heap holds the first `len` of them
+fn sift_down<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    mut node: usize,
+    len: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= len {
+            return;
+        }
+        if child + 1 < len && is_less(&arr[start + child], &arr[start + child + 1]) {
+            child += 1;
+        }
+        if !is_less(&arr[start + node], &arr[start + child]) {
+            return;
+        }
+
+        swap(arr, start + node, start + child, probe);
+        node = child;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_heap_sort() {
+        let mut arr = [3, 6, 8, 10, 11, 2, 9];
+        heap_sort(&mut arr);
+        assert_eq!(arr, [2, 3, 6, 8, 9, 10, 11]);
+    }
+
+    #[test]
+    fn test_heap_sort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for len in 0..100 {
+            let mut arr: Vec<i64> = (0..len).map(|_| rng.random_range(-50..50)).collect();
+            let mut expected = arr.clone();
+            expected.sort();
+            heap_sort(&mut arr);
+            assert_eq!(arr, expected);
+        }
+    }
+
+    #[test]
+    fn test_heap_sort_range() {
+        let mut arr = [9, 5, 4, 3, 0];
+        _heap_sort(&mut arr, 1, 4, &mut |a, b| a < b, &mut ());
+        assert_eq!(arr, [9, 3, 4, 5, 0]);
+    }
+}
@@ -1,48 +1,99 @@
-use lang_extension::slice::SliceExtention;
+use super::{Probe, Sorter, lift};
+use std::cmp::Ordering;
+
+pub struct InsertionSort;
+
+impl Sorter for InsertionSort {
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], mut compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>,
+    {
+        _insertion_sort(
+            arr,
+            0,
+            arr.len(),
+            &mut |a, b| compare(a, b) == Ordering::Less,
+            probe,
+        );
+    }
+}
 
 pub fn insertion_sort<T: PartialOrd>(arr: &mut [T]) {
-    _insertion_sort(arr, 0, arr.len());
+    _insertion_sort(arr, 0, arr.len(), &mut |a, b| a < b, &mut ());
+}
+
+pub fn insertion_sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mut compare: F) {
+    _insertion_sort(
+        arr,
+        0,
+        arr.len(),
+        &mut |a, b| compare(a, b) == Ordering::Less,
+        &mut (),
+    );
+}
+
+pub fn insertion_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    _insertion_sort(arr, 0, arr.len(), &mut |a, b| key(a) < key(b), &mut ());
 }
 
-fn _insertion_sort<T: PartialOrd>(arr: &mut [T], start: usize, end: usize) {
+fn _insertion_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
     if end - start <= 1 {
         return;
     }
 
-    _insertion_sort(arr, start, end - 1);
+    _insertion_sort(arr, start, end - 1, is_less, probe);
 
-    let f: fn(&[T], usize, usize, usize) -> usize = if (end - start) % 2 == 0 {
+    let f: fn(&[T], usize, usize, usize, &mut F) -> usize = if (end - start).is_multiple_of(2) {
         search
     } else {
         binary_search
     };
 
-    let i = f(arr, end - 1, start, end - 1);
-    arr.lift(end - 1, i);
+    let i = f(arr, end - 1, start, end - 1, is_less);
+    lift(arr, end - 1, i, probe);
 }
 
-fn search<T: PartialOrd>(arr: &[T], value_index: usize, start: usize, end: usize) -> usize {
+fn search<T, F: FnMut(&T, &T) -> bool>(
+    arr: &[T],
+    value_index: usize,
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+) -> usize {
     for i in start..end {
-        if arr[i] <= arr[value_index] {
+        if !is_less(&arr[value_index], &arr[i]) {
             continue;
         }
         return i;
     }
 
-    return end;
+    end
 }
 
-fn binary_search<T: PartialOrd>(arr: &[T], value_index: usize, start: usize, end: usize) -> usize {
+fn binary_search<T, F: FnMut(&T, &T) -> bool>(
+    arr: &[T],
+    value_index: usize,
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+) -> usize {
     let len = end - start;
     if len <= 1 {
-        return end;
+        return search(arr, value_index, start, end, is_less);
     }
 
-    let mid = start + end / 2;
-    if arr[mid] > arr[value_index] {
-        search(arr, value_index, start, mid)
+    let mid = start + len / 2;
+    if is_less(&arr[value_index], &arr[mid]) {
+        search(arr, value_index, start, mid, is_less)
     } else {
-        search(arr, value_index, mid + 1, end)
+        search(arr, value_index, mid + 1, end, is_less)
     }
 }
 
@@ -55,4 +106,21 @@ mod tests {
         insertion_sort(&mut arr);
         assert_eq!(arr, [11, 12, 22, 25, 34, 64, 90]);
     }
+
+    #[test]
+    fn test_insertion_sort_by_key() {
+        let mut arr = [(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e')];
+        insertion_sort_by_key(&mut arr, |&(k, _)| k);
+        assert_eq!(arr, [(1, 'b'), (1, 'e'), (2, 'd'), (3, 'a'), (3, 'c')]);
+    }
+
+    #[test]
+    fn test_insertion_sort_by_unordered_type() {
+        // Elements need no ordering of their own when the comparator supplies one
+        #[derive(Debug, PartialEq)]
+        struct Unordered(i32);
+        let mut arr = [Unordered(3), Unordered(1), Unordered(2)];
+        insertion_sort_by(&mut arr, |a, b| a.0.cmp(&b.0));
+        assert_eq!(arr, [Unordered(1), Unordered(2), Unordered(3)]);
+    }
 }
@@ -0,0 +1,231 @@
+use serde::Serialize;
+
+/// Watches the swaps and moves a sort makes, always with the whole array after the change.
+/// The sorts are generic over it, so the `()` probe of the plain entry points costs nothing.
+pub trait Probe<T> {
+    fn swapped(&mut self, arr: &[T], i: usize, j: usize);
+
+    /// The element at `from` was moved to `to`, shifting the ones between by one place.
+    fn moved(&mut self, arr: &[T], from: usize, to: usize);
+
+    /// arr[start..end] was rewritten through a buffer, with `moves` element moves in all.
+    fn merged(&mut self, arr: &[T], start: usize, end: usize, moves: usize);
+}
+
+impl<T> Probe<T> for () {
+    #[inline(always)]
+    fn swapped(&mut self, _arr: &[T], _i: usize, _j: usize) {}
+
+    #[inline(always)]
+    fn moved(&mut self, _arr: &[T], _from: usize, _to: usize) {}
+
+    #[inline(always)]
+    fn merged(&mut self, _arr: &[T], _start: usize, _end: usize, _moves: usize) {}
+}
+
+/// Counts of the basic operations of one sort. A move of an element across `n` others is
+/// `n + 1` element moves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize)]
+pub struct SortStats {
+    pub comparisons: usize,
+    pub swaps: usize,
+    pub moves: usize,
+}
+
+impl<T> Probe<T> for SortStats {
+    fn swapped(&mut self, _arr: &[T], _i: usize, _j: usize) {
+        self.swaps += 1;
+    }
+
+    fn moved(&mut self, _arr: &[T], from: usize, to: usize) {
+        self.moves += from.abs_diff(to) + 1;
+    }
+
+    fn merged(&mut self, _arr: &[T], _start: usize, _end: usize, moves: usize) {
+        self.moves += moves;
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Step<T> {
+    Swap {
+        i: usize,
+        j: usize,
+        array: Vec<T>,
+    },
+    Move {
+        from: usize,
+        to: usize,
+        array: Vec<T>,
+    },
+    Merge {
+        start: usize,
+        end: usize,
+        array: Vec<T>,
+    },
+}
+
+/// Every state the array went through during a sort, for visualization.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct Trace<T> {
+    pub initial: Vec<T>,
+    pub steps: Vec<Step<T>>,
+    pub stats: SortStats,
+}
+
+impl<T: Clone> Trace<T> {
+    pub fn new(arr: &[T]) -> Self {
+        Self {
+            initial: arr.to_vec(),
+            steps: Vec::new(),
+            stats: SortStats::default(),
+        }
+    }
+}
+
+impl<T: Serialize> Trace<T> {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a trace of serializable values always serializes")
+    }
+}
+
+impl<T: Clone> Probe<T> for Trace<T> {
+    fn swapped(&mut self, arr: &[T], i: usize, j: usize) {
+        Probe::<T>::swapped(&mut self.stats, arr, i, j);
+        self.steps.push(Step::Swap {
+            i,
+            j,
+            array: arr.to_vec(),
+        });
+    }
+
+    fn moved(&mut self, arr: &[T], from: usize, to: usize) {
+        Probe::<T>::moved(&mut self.stats, arr, from, to);
+        self.steps.push(Step::Move {
+            from,
+            to,
+            array: arr.to_vec(),
+        });
+    }
+
+    fn merged(&mut self, arr: &[T], start: usize, end: usize, moves: usize) {
+        Probe::<T>::merged(&mut self.stats, arr, start, end, moves);
+        self.steps.push(Step::Merge {
+            start,
+            end,
+            array: arr.to_vec(),
+        });
+    }
+}
+
+pub(crate) fn swap<T, P: Probe<T>>(arr: &mut [T], i: usize, j: usize, probe: &mut P) {
+    arr.swap(i, j);
+    probe.swapped(arr, i, j);
+}
+
+pub(crate) fn lift<T, P: Probe<T>>(arr: &mut [T], from: usize, to: usize, probe: &mut P) {
+    if from < to {
+        arr[from..=to].rotate_left(1);
+    } else if to < from {
+        arr[to..=from].rotate_right(1);
+    } else {
+        return;
+    }
+    probe.moved(arr, from, to);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test_count() {
+        let mut arr = [3, 2, 1];
+        let stats = BubbleSort.count(&mut arr);
+        assert_eq!(arr, [1, 2, 3]);
+        assert_eq!(
+            stats,
+            SortStats {
+                comparisons: 3,
+                swaps: 3,
+                moves: 0
+            }
+        );
+
+        let mut arr = [3, 2, 1];
+        let stats = InsertionSort.count(&mut arr);
+        assert_eq!(arr, [1, 2, 3]);
+        assert_eq!(stats.swaps, 0);
+        assert_eq!(stats.moves, 5);
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut arr = [2, 3, 1];
+        let trace = SelectionSort.trace(&mut arr);
+        assert_eq!(trace.initial, [2, 3, 1]);
+        assert_eq!(
+            trace.steps.last().unwrap(),
+            &Step::Swap {
+                i: 1,
+                j: 2,
+                array: vec![1, 2, 3]
+            }
+        );
+        assert_eq!(
+            trace.to_json(),
+            r#"{"initial":[2,3,1],"steps":[{"op":"swap","i":0,"j":2,"array":[1,3,2]},{"op":"swap","i":1,"j":2,"array":[1,2,3]}],"stats":{"comparisons":3,"swaps":2,"moves":0}}"#
+        );
+    }
+
+    #[test]
+    fn test_every_sort_reports_its_steps() {
+        let arr = [5, 1, 4, 2, 8, 0, 2, 9, 7, 3];
+        let mut expected = arr;
+        expected.sort();
+
+        fn check<S: Sorter>(sorter: S, arr: [i32; 10], expected: [i32; 10]) {
+            let mut traced = arr;
+            let trace = sorter.trace(&mut traced);
+            assert_eq!(traced, expected);
+            assert!(trace.stats.comparisons > 0);
+            assert!(trace.stats.swaps + trace.stats.moves > 0);
+
+            // Replaying the steps from the initial state agrees with the recorded arrays
+            let mut replay = trace.initial.clone();
+            for step in &trace.steps {
+                match step {
+                    Step::Swap { i, j, array } => {
+                        replay.swap(*i, *j);
+                        assert_eq!(&replay, array);
+                    }
+                    Step::Move { from, to, array } => {
+                        lang_extension::slice::SliceExtention::lift(&mut replay[..], *from, *to);
+                        assert_eq!(&replay, array);
+                    }
+                    Step::Merge { start, end, array } => {
+                        // Only the merged range changes, and only in order
+                        assert_eq!(replay[..*start], array[..*start]);
+                        assert_eq!(replay[*end..], array[*end..]);
+                        replay[*start..*end].sort();
+                        assert_eq!(&replay, array);
+                    }
+                }
+            }
+
+            let mut counted = arr;
+            assert_eq!(sorter.count(&mut counted), trace.stats);
+        }
+
+        check(BubbleSort, arr, expected);
+        check(SelectionSort, arr, expected);
+        check(InsertionSort, arr, expected);
+        check(MergeSort, arr, expected);
+        check(QuickSort, arr, expected);
+        check(HeapSort, arr, expected);
+        check(IntroSort, arr, expected);
+        check(TimSort, arr, expected);
+        check(ShellSort(Gaps::Knuth), arr, expected);
+    }
+}
@@ -0,0 +1,308 @@
+use super::heap_sort::_heap_sort;
+use super::{Probe, Sorter, lift, swap};
+use std::cmp::Ordering;
+
+/// Slices up to this length are insertion sorted.
+const INSERTION_THRESHOLD: usize = 16;
+/// Slices from this length pick their pivot with Tukey's ninther instead of a median of three.
+const NINTHER_THRESHOLD: usize = 128;
+/// How many elements a partial insertion sort may move before it gives up.
+const PARTIAL_INSERTION_LIMIT: usize = 8;
+
+/// Introspective quicksort: O(n log n) in the worst case, unstable, and O(log n) stack.
+pub struct IntroSort;
+
+impl Sorter for IntroSort {
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], mut compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>,
+    {
+        _intro_sort(arr, &mut |a, b| compare(a, b) == Ordering::Less, probe);
+    }
+}
+
+pub fn intro_sort<T: Ord>(arr: &mut [T]) {
+    _intro_sort(arr, &mut |a, b| a < b, &mut ());
+}
+
+pub fn intro_sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mut compare: F) {
+    _intro_sort(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+pub fn intro_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    _intro_sort(arr, &mut |a, b| key(a) < key(b), &mut ());
+}
+
+fn _intro_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    sort_range(arr, 0, arr.len(), depth_limit(arr.len()), is_less, probe);
+}
+
+/// Quicksort that goes past 2 log2(n) levels is on a bad input, so heap sort takes over.
+pub(crate) fn depth_limit(len: usize) -> usize {
+    2 * (usize::BITS - len.leading_zeros()) as usize
+}
+
+pub(crate) fn sort_range<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    mut start: usize,
+    mut end: usize,
+    mut depth_limit: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    while let Some(mid) = split(arr, start, end, &mut depth_limit, is_less, probe) {
+        // Recurse into the smaller side and loop on the larger one, bounding the stack
+        if mid - start < end - mid {
+            sort_range(arr, start, mid, depth_limit, is_less, probe);
+            start = mid + 1;
+        } else {
+            sort_range(arr, mid + 1, end, depth_limit, is_less, probe);
+            end = mid;
+        }
+    }
+}
+
+/// One level of introsort on arr[start..end]: finishes small and too deep slices, and partitions
+/// the rest. Returns the pivot's index when both sides of it still need sorting.
+pub(crate) fn split<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    depth_limit: &mut usize,
+    is_less: &mut F,
+    probe: &mut P,
+) -> Option<usize> {
+    let len = end - start;
+    if len <= INSERTION_THRESHOLD {
+        insertion_sort(arr, start, end, is_less, probe);
+        return None;
+    }
+    if *depth_limit == 0 {
+        _heap_sort(arr, start, end, is_less, probe);
+        return None;
+    }
+    *depth_limit -= 1;
+
+    let pivot = choose_pivot(arr, start, end, is_less);
+    let (mid, already_partitioned) = partition(arr, start, end, pivot, is_less, probe);
+
+    // A partition that swapped nothing suggests a presorted slice: if a short insertion sort
+    // finishes both sides, this slice is done in linear time
+    if already_partitioned
+        && partial_insertion_sort(arr, start, mid, is_less, probe)
+        && partial_insertion_sort(arr, mid + 1, end, is_less, probe)
+    {
+        return None;
+    }
+    Some(mid)
+}
+
+fn choose_pivot<T, F: FnMut(&T, &T) -> bool>(
+    arr: &[T],
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+) -> usize {
+    let len = end - start;
+    let (first, mid, last) = (start, start + len / 2, end - 1);
+    if len < NINTHER_THRESHOLD {
+        return median_of_three(arr, first, mid, last, is_less);
+    }
+
+    // Tukey's ninther: the median of the medians of three evenly spread triples
+    let step = len / 8;
+    let a = median_of_three(arr, first, first + step, first + 2 * step, is_less);
+    let b = median_of_three(arr, mid - step, mid, mid + step, is_less);
+    let c = median_of_three(arr, last - 2 * step, last - step, last, is_less);
+    median_of_three(arr, a, b, c, is_less)
+}
+
+fn median_of_three<T, F: FnMut(&T, &T) -> bool>(
+    arr: &[T],
+    a: usize,
+    b: usize,
+    c: usize,
+    is_less: &mut F,
+) -> usize {
+    let (a, b) = if is_less(&arr[b], &arr[a]) {
+        (b, a)
+    } else {
+        (a, b)
+    };
+    if is_less(&arr[c], &arr[a]) {
+        a
+    } else if is_less(&arr[c], &arr[b]) {
+        c
+    } else {
+        b
+    }
+}
+
+/// Hoare partition of arr[start..end] around the element at `pivot`. Returns the pivot's final
+/// index, and whether the slice was already partitioned. Both scans stop on elements equal to
+/// the pivot, so runs of equal elements split evenly instead of going quadratic.
+fn partition<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    pivot: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) -> (usize, bool) {
+    if pivot != start {
+        swap(arr, start, pivot, probe);
+    }
+
+    let (mut i, mut j) = (start + 1, end - 1);
+    let mut already_partitioned = true;
+    loop {
+        while i <= j && is_less(&arr[i], &arr[start]) {
+            i += 1;
+        }
+        while i <= j && is_less(&arr[start], &arr[j]) {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+
+        swap(arr, i, j, probe);
+        already_partitioned = false;
+        i += 1;
+        j -= 1;
+    }
+
+    if j != start {
+        swap(arr, start, j, probe);
+    }
+    (j, already_partitioned)
+}
+
+fn insertion_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    for i in start + 1..end {
+        let mut j = i;
+        while j > start && is_less(&arr[i], &arr[j - 1]) {
+            j -= 1;
+        }
+        lift(arr, i, j, probe);
+    }
+}
+
+/// Insertion sort that gives up once it has moved too many elements. Returns whether it
+/// finished.
+fn partial_insertion_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) -> bool {
+    let mut moved = 0;
+    for i in start + 1..end {
+        let mut j = i;
+        while j > start && is_less(&arr[i], &arr[j - 1]) {
+            j -= 1;
+        }
+        if j != i {
+            moved += 1;
+            if moved > PARTIAL_INSERTION_LIMIT {
+                return false;
+            }
+            lift(arr, i, j, probe);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_intro_sort() {
+        let mut arr = [3, 6, 8, 10, 11, 2, 9];
+        intro_sort(&mut arr);
+        assert_eq!(arr, [2, 3, 6, 8, 9, 10, 11]);
+    }
+
+    #[test]
+    fn test_intro_sort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for len in (0..200).chain([1000, 5000]) {
+            for range in [3, 1000] {
+                let mut arr: Vec<i32> = (0..len).map(|_| rng.random_range(0..range)).collect();
+                let mut expected = arr.clone();
+                expected.sort();
+                intro_sort(&mut arr);
+                assert_eq!(arr, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_adversarial_inputs_stay_n_log_n() {
+        let n = 20_000usize;
+        let inputs: [(&str, Vec<usize>); 6] = [
+            ("sorted", (0..n).collect()),
+            ("reversed", (0..n).rev().collect()),
+            ("equal", vec![7; n]),
+            ("organ pipe", (0..n / 2).chain((0..n / 2).rev()).collect()),
+            ("sawtooth", (0..n).map(|i| i % 100).collect()),
+            ("median of three killer", median_of_three_killer(n)),
+        ];
+
+        let bound = 4 * n * n.ilog2() as usize;
+        for (name, arr) in inputs {
+            let mut expected = arr.clone();
+            expected.sort();
+            let mut actual = arr;
+            let stats = IntroSort.count(&mut actual);
+            assert_eq!(actual, expected, "{}", name);
+            assert!(stats.comparisons < bound, "{}: {:?}", name, stats);
+        }
+    }
+
+    #[test]
+    fn test_presorted_input_is_linear() {
+        let mut arr: Vec<u32> = (0..10_000).collect();
+        let stats = IntroSort.count(&mut arr);
+        assert!(stats.comparisons < 3 * arr.len(), "{:?}", stats);
+        // Only the pivot of the first partition moves out and back
+        assert_eq!(stats.swaps, 2);
+        assert_eq!(stats.moves, 0);
+    }
+
+    #[test]
+    fn test_deep_input_does_not_overflow_the_stack() {
+        let mut arr: Vec<u64> = (0..1_000_000).rev().collect();
+        intro_sort(&mut arr);
+        assert!(arr.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    // Musser's sequence that drives median-of-three quicksort to quadratic time
+    fn median_of_three_killer(n: usize) -> Vec<usize> {
+        let k = n / 2;
+        let mut arr = vec![0; n];
+        for i in 1..=k {
+            if i % 2 == 1 {
+                arr[i - 1] = i;
+                arr[i] = k + i;
+            }
+            arr[k + i - 1] = 2 * i;
+        }
+        arr
+    }
+}
@@ -1,46 +1,106 @@
-use lang_extension::slice::SliceExtention;
-
-static MERGER1: _Merger1 = _Merger1;
-static MERGER2: _Merger2 = _Merger2;
+use super::{Probe, Sorter, lift};
+use std::cmp::Ordering;
+
+pub struct MergeSort;
+
+impl Sorter for MergeSort {
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], mut compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>,
+    {
+        _merge_sort(
+            arr,
+            0,
+            arr.len(),
+            &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+            probe,
+        );
+    }
+}
 
+/// In-place merge sort that shifts elements with `lift`, so it makes O(n^2) moves. `tim_sort`
+/// is the stable sort to use on real data.
 pub fn merge_sort<T: PartialOrd>(arr: &mut [T]) {
-    _merge_sort(arr, 0, arr.len());
+    _merge_sort(arr, 0, arr.len(), &mut |a: &T, b: &T| a < b, &mut ());
+}
+
+pub fn merge_sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mut compare: F) {
+    _merge_sort(
+        arr,
+        0,
+        arr.len(),
+        &mut |a: &T, b: &T| compare(a, b) == Ordering::Less,
+        &mut (),
+    );
 }
 
-fn _merge_sort<T: PartialOrd>(arr: &mut [T], start: usize, end: usize) {
+pub fn merge_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    _merge_sort(
+        arr,
+        0,
+        arr.len(),
+        &mut |a: &T, b: &T| key(a) < key(b),
+        &mut (),
+    );
+}
+
+fn _merge_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
     let len = end - start;
     if len <= 1 {
         return; // No need to sort if the array has 0 or 1 elements
     }
 
     let mid = start + len / 2;
-    _merge_sort(arr, 0, mid);
-    _merge_sort(arr, mid, end);
-
-    let merger: &dyn Merger<T> = if len % 2 == 0 { &MERGER1 } else { &MERGER2 };
-    merger.merge(arr, start, mid, end);
+    _merge_sort(arr, start, mid, is_less, probe);
+    _merge_sort(arr, mid, end, is_less, probe);
+
+    let merger: &dyn Merger<T, F, P> = if len.is_multiple_of(2) {
+        &_Merger1
+    } else {
+        &_Merger2
+    };
+    merger.merge(arr, start, mid, end, is_less, probe);
 }
 
-trait Merger<T: PartialOrd> {
-    fn merge(&self, arr: &mut [T], start: usize, mid: usize, end: usize);
+trait Merger<T, F: FnMut(&T, &T) -> bool, P: Probe<T>> {
+    fn merge(
+        &self,
+        arr: &mut [T],
+        start: usize,
+        mid: usize,
+        end: usize,
+        is_less: &mut F,
+        probe: &mut P,
+    );
 }
 
 struct _Merger1;
 
-impl<T: PartialOrd> Merger<T> for _Merger1 {
-    fn merge(&self, arr: &mut [T], start: usize, mid: usize, end: usize) {
+impl<T, F: FnMut(&T, &T) -> bool, P: Probe<T>> Merger<T, F, P> for _Merger1 {
+    fn merge(
+        &self,
+        arr: &mut [T],
+        start: usize,
+        mid: usize,
+        end: usize,
+        is_less: &mut F,
+        probe: &mut P,
+    ) {
         let mut greater = start;
         for i in mid..end {
-            for j in greater..i {
-                greater += 1;
-                if arr[j] > arr[i] {
-                    arr.lift(i, j);
-                    break;
+            match (greater..i).find(|&j| is_less(&arr[i], &arr[j])) {
+                Some(j) => {
+                    lift(arr, i, j, probe);
+                    greater = j + 1;
                 }
-            }
-
-            if greater == i {
-                break;
+                None => break, // The rest of the right part is not less than anything on the left
             }
         }
     }
@@ -48,11 +108,23 @@ impl<T: PartialOrd> Merger<T> for _Merger1 {
 
 struct _Merger2;
 
-impl<T: PartialOrd> Merger<T> for _Merger2 {
-    fn merge(&self, arr: &mut [T], start: usize, mid: usize, end: usize) {
+impl<T, F: FnMut(&T, &T) -> bool, P: Probe<T>> Merger<T, F, P> for _Merger2 {
+    fn merge(
+        &self,
+        arr: &mut [T],
+        start: usize,
+        mid: usize,
+        end: usize,
+        is_less: &mut F,
+        probe: &mut P,
+    ) {
+        if mid == end {
+            return;
+        }
+
         let mut left = mid;
         for i in start..mid {
-            if arr[i] > arr[mid] {
+            if is_less(&arr[mid], &arr[i]) {
                 left = i;
                 break;
             }
@@ -61,9 +133,10 @@ impl<T: PartialOrd> Merger<T> for _Merger2 {
             return; // No need to merge if the left part is already sorted
         }
 
+        // The right elements before `new_mid` are all less than arr[left], so they move as a block
         let mut right = mid;
         for i in (mid..end).rev() {
-            if arr[i] < arr[left] {
+            if is_less(&arr[i], &arr[left]) {
                 right = i;
                 break;
             }
@@ -72,11 +145,11 @@ impl<T: PartialOrd> Merger<T> for _Merger2 {
         let new_mid = right + 1;
         let new_start = left + (new_mid - mid);
         for i in mid..new_mid {
-            arr.lift(i, left);
+            lift(arr, i, left, probe);
             left += 1;
         }
 
-        self.merge(arr, new_start, new_mid, end);
+        self.merge(arr, new_start, new_mid, end, is_less, probe);
     }
 }
 
@@ -91,6 +164,27 @@ mod tests {
     }
 
     #[test]
+    fn test_merge_sort_by_key_is_stable() {
+        let mut arr = [(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd'), (1, 'e'), (2, 'f')];
+        merge_sort_by_key(&mut arr, |&(k, _)| k);
+        assert_eq!(
+            arr,
+            [(1, 'b'), (1, 'e'), (2, 'd'), (2, 'f'), (3, 'a'), (3, 'c')]
+        );
+    }
+
+    #[test]
+    fn test_merge_sort_by_unordered_type() {
+        // Elements need no ordering of their own when the comparator supplies one
+        #[derive(Debug, PartialEq)]
+        struct Unordered(i32);
+        let mut arr: Vec<_> = [5, 1, 4, 2, 3].map(Unordered).into();
+        merge_sort_by(&mut arr, |a, b| a.0.cmp(&b.0));
+        assert_eq!(arr, [1, 2, 3, 4, 5].map(Unordered));
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
     fn test_scope() {
         for i in 0..0 {
             println!("i: {}", i);
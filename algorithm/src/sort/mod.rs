@@ -1,3 +1,9 @@
+mod instrument;
+pub use instrument::*;
+
+mod sorter;
+pub use sorter::*;
+
 mod bubble_sort;
 pub use bubble_sort::*;
 
@@ -13,6 +19,30 @@ pub use insertion_sort::*;
 mod quick_sort;
 pub use quick_sort::*;
 
+mod heap_sort;
+pub use heap_sort::*;
+
+mod intro_sort;
+pub use intro_sort::*;
+
+mod tim_sort;
+pub use tim_sort::*;
+
+mod parallel_sort;
+pub use parallel_sort::*;
+
+mod external_sort;
+pub use external_sort::*;
+
+mod shell_sort;
+pub use shell_sort::*;
+
+mod counting_sort;
+pub use counting_sort::*;
+
+mod radix_sort;
+pub use radix_sort::*;
+
 #[cfg(test)]
 mod tests {
     use lang_extension::slice::SliceExtention;
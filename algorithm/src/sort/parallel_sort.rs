@@ -0,0 +1,204 @@
+use super::intro_sort::{depth_limit, sort_range, split};
+use super::tim_sort::{_tim_sort, merge};
+use std::cmp::Ordering;
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// How a parallel sort splits its work.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Parallelism {
+    /// The most threads sorting at once, the calling one included.
+    pub threads: usize,
+    /// Slices up to this length are sorted sequentially on the thread that has them.
+    pub threshold: usize,
+}
+
+impl Default for Parallelism {
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            threshold: 1 << 13,
+        }
+    }
+}
+
+impl Parallelism {
+    pub fn new(threads: usize, threshold: usize) -> Self {
+        Self { threads, threshold }
+    }
+
+    // The shares of the threads for the two halves of a split, or None to stay sequential
+    fn split(self, len: usize) -> Option<(Self, Self)> {
+        if self.threads < 2 || len <= self.threshold {
+            return None;
+        }
+        let left = Self {
+            threads: self.threads / 2,
+            ..self
+        };
+        let right = Self {
+            threads: self.threads - left.threads,
+            ..self
+        };
+        Some((left, right))
+    }
+}
+
+/// Stable merge sort across threads: halves are sorted on separate threads down to the
+/// threshold, with `tim_sort` below it, then merged. The output is the same as `tim_sort`'s,
+/// as a stable sort has only one possible result.
+pub fn parallel_merge_sort<T: Ord + Send>(arr: &mut [T], parallelism: Parallelism) {
+    parallel_merge_sort_by(arr, parallelism, T::cmp);
+}
+
+pub fn parallel_merge_sort_by<T: Send, F>(arr: &mut [T], parallelism: Parallelism, compare: F)
+where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    _parallel_merge_sort(arr, parallelism, &|a, b| compare(a, b) == Ordering::Less);
+}
+
+pub fn parallel_merge_sort_by_key<T: Send, K: Ord, F>(
+    arr: &mut [T],
+    parallelism: Parallelism,
+    key: F,
+) where
+    F: Fn(&T) -> K + Sync,
+{
+    _parallel_merge_sort(arr, parallelism, &|a, b| key(a) < key(b));
+}
+
+fn _parallel_merge_sort<T: Send, F: Fn(&T, &T) -> bool + Sync>(
+    arr: &mut [T],
+    parallelism: Parallelism,
+    is_less: &F,
+) {
+    let Some((left_share, right_share)) = parallelism.split(arr.len()) else {
+        _tim_sort(arr, &mut |a, b| is_less(a, b), &mut ());
+        return;
+    };
+
+    let mid = arr.len() / 2;
+    let (left, right) = arr.split_at_mut(mid);
+    thread::scope(|scope| {
+        scope.spawn(|| _parallel_merge_sort(left, left_share, is_less));
+        _parallel_merge_sort(right, right_share, is_less);
+    });
+    merge(arr, mid, &mut |a, b| is_less(a, b));
+}
+
+/// Introsort across threads: after each partition the two sides are sorted on separate threads
+/// down to the threshold. Every partition is the one `intro_sort` would make, so the output is
+/// identical to it, even for elements that compare equal.
+pub fn parallel_quick_sort<T: Ord + Send>(arr: &mut [T], parallelism: Parallelism) {
+    parallel_quick_sort_by(arr, parallelism, T::cmp);
+}
+
+pub fn parallel_quick_sort_by<T: Send, F>(arr: &mut [T], parallelism: Parallelism, compare: F)
+where
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let depth_limit = depth_limit(arr.len());
+    _parallel_quick_sort(arr, parallelism, depth_limit, &|a, b| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+pub fn parallel_quick_sort_by_key<T: Send, K: Ord, F>(
+    arr: &mut [T],
+    parallelism: Parallelism,
+    key: F,
+) where
+    F: Fn(&T) -> K + Sync,
+{
+    let depth_limit = depth_limit(arr.len());
+    _parallel_quick_sort(arr, parallelism, depth_limit, &|a, b| key(a) < key(b));
+}
+
+fn _parallel_quick_sort<T: Send, F: Fn(&T, &T) -> bool + Sync>(
+    arr: &mut [T],
+    parallelism: Parallelism,
+    mut depth_limit: usize,
+    is_less: &F,
+) {
+    let len = arr.len();
+    let Some((left_share, right_share)) = parallelism.split(len) else {
+        sort_range(arr, 0, len, depth_limit, &mut |a, b| is_less(a, b), &mut ());
+        return;
+    };
+
+    let Some(mid) = split(
+        arr,
+        0,
+        len,
+        &mut depth_limit,
+        &mut |a, b| is_less(a, b),
+        &mut (),
+    ) else {
+        return;
+    };
+    let (left, right) = arr.split_at_mut(mid);
+    let right = &mut right[1..];
+    thread::scope(|scope| {
+        scope.spawn(|| _parallel_quick_sort(left, left_share, depth_limit, is_less));
+        _parallel_quick_sort(right, right_share, depth_limit, is_less);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_parallel_sorts() {
+        let mut arr = [3, 6, 8, 10, 11, 2, 9];
+        parallel_merge_sort(&mut arr, Parallelism::new(4, 2));
+        assert_eq!(arr, [2, 3, 6, 8, 9, 10, 11]);
+
+        let mut arr = [3, 6, 8, 10, 11, 2, 9];
+        parallel_quick_sort(&mut arr, Parallelism::new(4, 2));
+        assert_eq!(arr, [2, 3, 6, 8, 9, 10, 11]);
+    }
+
+    #[test]
+    fn test_parallel_sorts_match_sequential() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for len in [0, 1, 100, 5000, 100_000] {
+            // Few distinct keys, so the order of equal ones shows any difference
+            let records: Vec<(u8, usize)> =
+                (0..len).map(|i| (rng.random_range(0..16), i)).collect();
+            for parallelism in [
+                Parallelism::new(1, 64),
+                Parallelism::new(3, 64),
+                Parallelism::new(8, 1000),
+                Parallelism::default(),
+            ] {
+                let mut expected = records.clone();
+                tim_sort_by_key(&mut expected, |&(key, _)| key);
+                let mut actual = records.clone();
+                parallel_merge_sort_by_key(&mut actual, parallelism, |&(key, _)| key);
+                assert_eq!(actual, expected);
+
+                let mut expected = records.clone();
+                intro_sort_by_key(&mut expected, |&(key, _)| key);
+                let mut actual = records.clone();
+                parallel_quick_sort_by_key(&mut actual, parallelism, |&(key, _)| key);
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parallel_sort_of_adversarial_input() {
+        let mut arr: Vec<u32> = (0..200_000).rev().collect();
+        parallel_quick_sort(&mut arr, Parallelism::new(4, 1000));
+        assert!(arr.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut arr = vec![7u32; 200_000];
+        parallel_merge_sort(&mut arr, Parallelism::new(4, 1000));
+        assert!(arr.iter().all(|&x| x == 7));
+    }
+}
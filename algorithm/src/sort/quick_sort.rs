@@ -1,20 +1,74 @@
+use super::{Probe, Sorter, swap};
+use std::cmp::Ordering;
+
+pub struct QuickSort;
+
+impl Sorter for QuickSort {
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], mut compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>,
+    {
+        _quick_sort(
+            arr,
+            0,
+            arr.len(),
+            &mut |a, b| compare(a, b) == Ordering::Less,
+            probe,
+        );
+    }
+}
+
+/// Textbook quicksort around the middle element. It goes quadratic and recurses O(n) deep on
+/// adversarial inputs, so prefer `intro_sort` outside of teaching.
 pub fn quick_sort<T: Ord>(arr: &mut [T]) {
-    if arr.len() <= 1 {
+    _quick_sort(arr, 0, arr.len(), &mut |a, b| a < b, &mut ());
+}
+
+pub fn quick_sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mut compare: F) {
+    _quick_sort(
+        arr,
+        0,
+        arr.len(),
+        &mut |a, b| compare(a, b) == Ordering::Less,
+        &mut (),
+    );
+}
+
+pub fn quick_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    _quick_sort(arr, 0, arr.len(), &mut |a, b| key(a) < key(b), &mut ());
+}
+
+// Works on arr[start..end] of the whole array rather than a subslice, so probes see every index
+fn _quick_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    if end - start <= 1 {
         return;
     }
 
-    let pivot_index = partition(arr);
-    quick_sort(&mut arr[0..pivot_index]);
-    quick_sort(&mut arr[pivot_index + 1..]);
+    let pivot_index = partition(arr, start, end, is_less, probe);
+    _quick_sort(arr, start, pivot_index, is_less, probe);
+    _quick_sort(arr, pivot_index + 1, end, is_less, probe);
 }
 
-fn partition<T: Ord>(arr: &mut [T]) -> usize {
-    let mut pivot_index = arr.len() / 2;
-    let (mut i, mut j) = (0, arr.len());
+fn partition<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    end: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) -> usize {
+    let mut pivot_index = start + (end - start) / 2;
+    let (mut i, mut j) = (start, end);
     while i < j {
         for k in i..pivot_index {
-            if arr[k] > arr[pivot_index] {
-                arr.swap(k, pivot_index);
+            if is_less(&arr[pivot_index], &arr[k]) {
+                swap(arr, k, pivot_index, probe);
                 pivot_index = k;
                 break;
             }
@@ -22,8 +76,8 @@ fn partition<T: Ord>(arr: &mut [T]) -> usize {
         i = pivot_index + 1;
 
         for k in (i..j).rev() {
-            if arr[k] < arr[pivot_index] {
-                arr.swap(k, pivot_index);
+            if is_less(&arr[k], &arr[pivot_index]) {
+                swap(arr, k, pivot_index, probe);
                 pivot_index = k;
                 break;
             }
@@ -38,6 +92,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::reversed_empty_ranges)]
     fn test_scope() {
         let s = 10..1;
         println!("s: {:?}", s);
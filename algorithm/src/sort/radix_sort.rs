@@ -0,0 +1,269 @@
+/// An integer usable as a radix sort key: `radix` maps it to a `u64` in the same order.
+pub trait RadixKey {
+    /// Significant bytes of `radix`, so narrow types take fewer passes.
+    const BYTES: usize;
+
+    fn radix(&self) -> u64;
+}
+
+macro_rules! unsigned_radix_key {
+    ($($t:ty),*) => {$(
+        impl RadixKey for $t {
+            const BYTES: usize = size_of::<$t>();
+
+            fn radix(&self) -> u64 {
+                *self as u64
+            }
+        }
+    )*};
+}
+
+macro_rules! signed_radix_key {
+    ($($t:ty => $u:ty),*) => {$(
+        impl RadixKey for $t {
+            const BYTES: usize = size_of::<$t>();
+
+            // Flipping the sign bit puts negative numbers below positive ones
+            fn radix(&self) -> u64 {
+                (*self as $u ^ (1 << (<$u>::BITS - 1))) as u64
+            }
+        }
+    )*};
+}
+
+unsigned_radix_key!(u8, u16, u32, u64, usize);
+signed_radix_key!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+fn byte(radix: u64, index: usize) -> usize {
+    ((radix >> (8 * index)) & 0xff) as usize
+}
+
+/// Least significant digit first radix sort, one stable counting pass per byte. O(n) per pass.
+pub fn lsd_radix_sort<T: RadixKey + Clone>(arr: &mut [T]) {
+    lsd_radix_sort_by_key(arr, T::clone);
+}
+
+pub fn lsd_radix_sort_by_key<T: Clone, K: RadixKey, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    let mut items: Vec<(u64, T)> = arr.iter().map(|x| (key(x).radix(), x.clone())).collect();
+    let mut buffer = items.clone();
+    for index in 0..K::BYTES {
+        let mut counts = [0; 256];
+        for (radix, _) in &items {
+            counts[byte(*radix, index)] += 1;
+        }
+        if counts.contains(&items.len()) {
+            continue; // Every key has the same byte here
+        }
+
+        let starts = prefix_sums(&counts);
+        distribute(&items, &mut buffer, starts, |(radix, _)| {
+            byte(*radix, index)
+        });
+        std::mem::swap(&mut items, &mut buffer);
+    }
+
+    for (slot, (_, x)) in arr.iter_mut().zip(items) {
+        *slot = x;
+    }
+}
+
+/// Most significant digit first radix sort, in place: each byte partitions the slice into 256
+/// buckets by swapping (American flag sort), and each bucket is sorted by the next byte.
+pub fn msd_radix_sort<T: RadixKey>(arr: &mut [T]) {
+    _msd_radix_sort(arr, 0);
+}
+
+fn _msd_radix_sort<T: RadixKey>(arr: &mut [T], depth: usize) {
+    if arr.len() <= 1 || depth == T::BYTES {
+        return;
+    }
+
+    let index = T::BYTES - 1 - depth;
+    let bounds = american_flag::<_, 256>(arr, |x| byte(x.radix(), index));
+    for bucket in bounds.windows(2) {
+        _msd_radix_sort(&mut arr[bucket[0]..bucket[1]], depth + 1);
+    }
+}
+
+/// LSD radix sort of byte strings in lexicographic order, a counting pass per position from the
+/// longest length down. A string that has ended sorts before any byte.
+pub fn lsd_radix_sort_bytes<S: AsRef<[u8]> + Clone>(arr: &mut [S]) {
+    let max_len = arr.iter().map(|s| s.as_ref().len()).max().unwrap_or(0);
+    let mut items = arr.to_vec();
+    let mut buffer = items.clone();
+    for position in (0..max_len).rev() {
+        let mut counts = [0; 257];
+        for s in &items {
+            counts[symbol(s.as_ref(), position)] += 1;
+        }
+
+        let starts = prefix_sums(&counts);
+        distribute(&items, &mut buffer, starts, |s| {
+            symbol(s.as_ref(), position)
+        });
+        std::mem::swap(&mut items, &mut buffer);
+    }
+
+    arr.clone_from_slice(&items);
+}
+
+/// MSD radix sort of byte strings in lexicographic order, in place.
+pub fn msd_radix_sort_bytes<S: AsRef<[u8]>>(arr: &mut [S]) {
+    _msd_radix_sort_bytes(arr, 0);
+}
+
+fn _msd_radix_sort_bytes<S: AsRef<[u8]>>(arr: &mut [S], position: usize) {
+    if arr.len() <= 1 {
+        return;
+    }
+
+    let bounds = american_flag::<_, 257>(arr, |s| symbol(s.as_ref(), position));
+    // Bucket 0 holds the strings that ended, which are all equal
+    for bucket in bounds[1..].windows(2) {
+        _msd_radix_sort_bytes(&mut arr[bucket[0]..bucket[1]], position + 1);
+    }
+}
+
+// 0 past the end of the string, otherwise the byte plus one
+fn symbol(s: &[u8], position: usize) -> usize {
+    s.get(position).map_or(0, |&b| b as usize + 1)
+}
+
+fn prefix_sums<const N: usize>(counts: &[usize; N]) -> [usize; N] {
+    let mut starts = [0; N];
+    for i in 1..N {
+        starts[i] = starts[i - 1] + counts[i - 1];
+    }
+    starts
+}
+
+// Stable counting-sort pass from `items` into `buffer`, given where each bucket starts
+fn distribute<T: Clone, const N: usize>(
+    items: &[T],
+    buffer: &mut [T],
+    mut next: [usize; N],
+    mut bucket: impl FnMut(&T) -> usize,
+) {
+    for x in items {
+        let b = bucket(x);
+        buffer[next[b]] = x.clone();
+        next[b] += 1;
+    }
+}
+
+/// Permutes `arr` so the buckets come in order, and returns the N + 1 bucket boundaries.
+fn american_flag<T, const N: usize>(
+    arr: &mut [T],
+    mut bucket: impl FnMut(&T) -> usize,
+) -> Vec<usize> {
+    let mut counts = [0; N];
+    for x in arr.iter() {
+        counts[bucket(x)] += 1;
+    }
+
+    let starts = prefix_sums(&counts);
+    let mut next = starts;
+    for b in 0..N {
+        let end = starts[b] + counts[b];
+        while next[b] < end {
+            // Swap the misplaced element into its bucket until one that belongs here arrives
+            let target = bucket(&arr[next[b]]);
+            if target == b {
+                next[b] += 1;
+            } else {
+                arr.swap(next[b], next[target]);
+                next[target] += 1;
+            }
+        }
+    }
+
+    let mut bounds = starts.to_vec();
+    bounds.push(arr.len());
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_radix_sort() {
+        let mut arr = [170, 45, 75, -90, 802, 24, -2, 66];
+        lsd_radix_sort(&mut arr);
+        assert_eq!(arr, [-90, -2, 24, 45, 66, 75, 170, 802]);
+
+        let mut arr = [170, 45, 75, -90, 802, 24, -2, 66];
+        msd_radix_sort(&mut arr);
+        assert_eq!(arr, [-90, -2, 24, 45, 66, 75, 170, 802]);
+    }
+
+    #[test]
+    fn test_radix_keys_keep_order() {
+        assert!((-1i8).radix() < 0i8.radix());
+        assert!(i8::MIN.radix() < i8::MAX.radix());
+        assert!(i64::MIN.radix() < (-1i64).radix());
+        assert_eq!(u16::MAX.radix(), 0xffff);
+        assert!(i16::MAX.radix() <= 0xffff);
+    }
+
+    #[test]
+    fn test_radix_sort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for len in (0..50).chain([1000]) {
+            let arr: Vec<i32> = (0..len).map(|_| rng.random()).collect();
+            let mut expected = arr.clone();
+            expected.sort();
+
+            let mut actual = arr.clone();
+            lsd_radix_sort(&mut actual);
+            assert_eq!(actual, expected);
+
+            let mut actual = arr.clone();
+            msd_radix_sort(&mut actual);
+            assert_eq!(actual, expected);
+
+            let arr: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+            let mut expected = arr.clone();
+            expected.sort();
+            let mut actual = arr.clone();
+            msd_radix_sort(&mut actual);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_lsd_radix_sort_by_key_is_stable() {
+        let mut arr = [(3u32, 'a'), (1, 'b'), (3, 'c'), (256, 'd'), (1, 'e')];
+        lsd_radix_sort_by_key(&mut arr, |&(k, _)| k);
+        assert_eq!(arr, [(1, 'b'), (1, 'e'), (3, 'a'), (3, 'c'), (256, 'd')]);
+    }
+
+    #[test]
+    fn test_byte_string_radix_sort() {
+        let mut arr = vec!["banana", "", "apple", "app", "b", "apples", "banan"];
+        lsd_radix_sort_bytes(&mut arr);
+        assert_eq!(arr, ["", "app", "apple", "apples", "b", "banan", "banana"]);
+
+        let mut rng = StdRng::seed_from_u64(4);
+        for len in 0..60 {
+            let arr: Vec<Vec<u8>> = (0..len)
+                .map(|_| {
+                    let n = rng.random_range(0..6);
+                    (0..n).map(|_| rng.random_range(b'a'..b'e')).collect()
+                })
+                .collect();
+            let mut expected = arr.clone();
+            expected.sort();
+
+            let mut actual = arr.clone();
+            lsd_radix_sort_bytes(&mut actual);
+            assert_eq!(actual, expected);
+
+            let mut actual = arr.clone();
+            msd_radix_sort_bytes(&mut actual);
+            assert_eq!(actual, expected);
+        }
+    }
+}
@@ -1,14 +1,45 @@
+use super::{Probe, Sorter, swap};
+use std::cmp::Ordering;
+
+pub struct SelectionSort;
+
+impl Sorter for SelectionSort {
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], mut compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>,
+    {
+        _selection_sort(arr, &mut |a, b| compare(a, b) == Ordering::Less, probe);
+    }
+}
+
 pub fn selection_sort<T: PartialOrd>(arr: &mut [T]) {
-    for i in 0..arr.len() - 1 {
+    _selection_sort(arr, &mut |a, b| a < b, &mut ());
+}
+
+pub fn selection_sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mut compare: F) {
+    _selection_sort(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+pub fn selection_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    _selection_sort(arr, &mut |a, b| key(a) < key(b), &mut ());
+}
+
+fn _selection_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    for i in 0..arr.len().saturating_sub(1) {
         let mut min_index = i;
         for j in i + 1..arr.len() {
-            if arr[j] < arr[min_index] {
+            if is_less(&arr[j], &arr[min_index]) {
                 min_index = j;
             }
         }
 
         if min_index != i {
-            arr.swap(i, min_index);
+            swap(arr, i, min_index, probe);
         }
     }
 }
@@ -21,5 +52,8 @@ mod tests {
         let mut arr = [64, 34, 25, 12, 22, 11, 90];
         selection_sort(&mut arr);
         assert_eq!(arr, [11, 12, 22, 25, 34, 64, 90]);
+
+        let mut empty: [i32; 0] = [];
+        selection_sort(&mut empty);
     }
 }
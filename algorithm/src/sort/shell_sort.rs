@@ -0,0 +1,158 @@
+use super::{Probe, Sorter, swap};
+use std::cmp::Ordering;
+
+/// Gap sequences for shell sort. Each ends with a gap of 1, which makes the last pass a plain
+/// insertion sort.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Gaps {
+    /// n/2, n/4, ..., 1: Shell's original sequence, O(n^2) in the worst case.
+    Shell,
+    /// 1, 4, 13, 40, ...: (3^k - 1) / 2, O(n^1.5).
+    Knuth,
+    /// 1, 8, 23, 77, 281, ...: 4^k + 3 * 2^(k-1) + 1, O(n^(4/3)).
+    Sedgewick,
+    /// 1, 4, 10, 23, 57, 132, 301, 701, then growing by 2.25: the best known in practice.
+    #[default]
+    Ciura,
+}
+
+impl Gaps {
+    /// The gaps to use for `n` elements, largest first.
+    pub fn sequence(self, n: usize) -> Vec<usize> {
+        let mut gaps = match self {
+            Gaps::Shell => {
+                let mut gaps = Vec::new();
+                let mut gap = n / 2;
+                while gap > 0 {
+                    gaps.push(gap);
+                    gap /= 2;
+                }
+                gaps.reverse();
+                gaps
+            }
+            Gaps::Knuth => grow(1, n.div_ceil(3), |gap, _| 3 * gap + 1),
+            Gaps::Sedgewick => grow(1, n, |_, k| 4usize.pow(k) + 3 * 2usize.pow(k - 1) + 1),
+            Gaps::Ciura => {
+                const CIURA: [usize; 8] = [1, 4, 10, 23, 57, 132, 301, 701];
+                let mut gaps: Vec<usize> = CIURA.iter().copied().filter(|&g| g < n).collect();
+                if gaps.len() == CIURA.len() {
+                    gaps.extend(grow(CIURA[7], n, |gap, _| gap * 9 / 4).into_iter().skip(1));
+                }
+                gaps
+            }
+        };
+        if gaps.is_empty() {
+            gaps.push(1);
+        }
+        gaps.reverse();
+        gaps
+    }
+}
+
+// The increasing gaps below `limit`, from `first`, where `next` gets the last gap and its index
+fn grow(first: usize, limit: usize, mut next: impl FnMut(usize, u32) -> usize) -> Vec<usize> {
+    let mut gaps = Vec::new();
+    let mut gap = first;
+    while gap < limit.max(2) {
+        gaps.push(gap);
+        gap = next(gap, gaps.len() as u32);
+    }
+    gaps
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ShellSort(pub Gaps);
+
+impl Sorter for ShellSort {
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], mut compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>,
+    {
+        _shell_sort(
+            arr,
+            self.0,
+            &mut |a, b| compare(a, b) == Ordering::Less,
+            probe,
+        );
+    }
+}
+
+pub fn shell_sort<T: PartialOrd>(arr: &mut [T]) {
+    _shell_sort(arr, Gaps::default(), &mut |a, b| a < b, &mut ());
+}
+
+pub fn shell_sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mut compare: F) {
+    _shell_sort(
+        arr,
+        Gaps::default(),
+        &mut |a, b| compare(a, b) == Ordering::Less,
+        &mut (),
+    );
+}
+
+pub fn shell_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    _shell_sort(arr, Gaps::default(), &mut |a, b| key(a) < key(b), &mut ());
+}
+
+fn _shell_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    gaps: Gaps,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    for gap in gaps.sequence(arr.len()) {
+        // Insertion sort of every gap-th element, swapping each one back into place
+        for i in gap..arr.len() {
+            let mut j = i;
+            while j >= gap && is_less(&arr[j], &arr[j - gap]) {
+                swap(arr, j, j - gap, probe);
+                j -= gap;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_shell_sort() {
+        let mut arr = [3, 6, 8, 10, 11, 2, 9];
+        shell_sort(&mut arr);
+        assert_eq!(arr, [2, 3, 6, 8, 9, 10, 11]);
+    }
+
+    #[test]
+    fn test_gap_sequences() {
+        assert_eq!(Gaps::Shell.sequence(20), [10, 5, 2, 1]);
+        assert_eq!(Gaps::Knuth.sequence(100), [13, 4, 1]);
+        assert_eq!(Gaps::Sedgewick.sequence(100), [77, 23, 8, 1]);
+        assert_eq!(Gaps::Ciura.sequence(100), [57, 23, 10, 4, 1]);
+        assert_eq!(
+            Gaps::Ciura.sequence(2000),
+            [1577, 701, 301, 132, 57, 23, 10, 4, 1]
+        );
+        for gaps in [Gaps::Shell, Gaps::Knuth, Gaps::Sedgewick, Gaps::Ciura] {
+            assert_eq!(gaps.sequence(0), [1]);
+            assert_eq!(gaps.sequence(1), [1]);
+        }
+    }
+
+    #[test]
+    fn test_shell_sort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for gaps in [Gaps::Shell, Gaps::Knuth, Gaps::Sedgewick, Gaps::Ciura] {
+            for len in (0..100).chain([1000, 3000]) {
+                let mut arr: Vec<i32> = (0..len).map(|_| rng.random_range(-50..50)).collect();
+                let mut expected = arr.clone();
+                expected.sort();
+                ShellSort(gaps).sort(&mut arr);
+                assert_eq!(arr, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use super::{Probe, SortStats, Trace};
+use std::cmp::Ordering;
+
+/// A sorting algorithm, so callers can pick one at the type level and sort by any order.
+pub trait Sorter {
+    /// Sorts `arr` by `compare`, reporting every swap and move to `probe`.
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>;
+
+    fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(&self, arr: &mut [T], compare: F) {
+        self.sort_probed(arr, compare, &mut ());
+    }
+
+    fn sort<T: Ord>(&self, arr: &mut [T]) {
+        self.sort_by(arr, T::cmp);
+    }
+
+    fn sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(&self, arr: &mut [T], mut key: F) {
+        self.sort_by(arr, |a, b| key(a).cmp(&key(b)));
+    }
+
+    fn count<T: Ord>(&self, arr: &mut [T]) -> SortStats {
+        self.count_by(arr, T::cmp)
+    }
+
+    fn count_by<T, F: FnMut(&T, &T) -> Ordering>(&self, arr: &mut [T], compare: F) -> SortStats {
+        let mut stats = SortStats::default();
+        stats.comparisons = sort_counting(self, arr, compare, &mut stats);
+        stats
+    }
+
+    fn trace<T: Ord + Clone>(&self, arr: &mut [T]) -> Trace<T> {
+        self.trace_by(arr, T::cmp)
+    }
+
+    fn trace_by<T: Clone, F: FnMut(&T, &T) -> Ordering>(
+        &self,
+        arr: &mut [T],
+        compare: F,
+    ) -> Trace<T> {
+        let mut trace = Trace::new(arr);
+        trace.stats.comparisons = sort_counting(self, arr, compare, &mut trace);
+        trace
+    }
+}
+
+/// Sorts like `Sorter::sort_probed`, and returns the number of comparisons made.
+fn sort_counting<S, T, F, P>(sorter: &S, arr: &mut [T], mut compare: F, probe: &mut P) -> usize
+where
+    S: Sorter + ?Sized,
+    F: FnMut(&T, &T) -> Ordering,
+    P: Probe<T>,
+{
+    let mut comparisons = 0;
+    sorter.sort_probed(
+        arr,
+        |a, b| {
+            comparisons += 1;
+            compare(a, b)
+        },
+        probe,
+    );
+    comparisons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Record {
+        name: String,
+        age: u32,
+    }
+
+    fn check<S: Sorter>(sorter: S) {
+        let mut rng = StdRng::seed_from_u64(7);
+        for len in 0..50 {
+            let arr: Vec<i32> = (0..len).map(|_| rng.random_range(-10..10)).collect();
+            let mut expected = arr.clone();
+            expected.sort();
+
+            let mut actual = arr.clone();
+            sorter.sort(&mut actual);
+            assert_eq!(actual, expected);
+
+            let mut actual = arr.clone();
+            sorter.sort_by(&mut actual, |a, b| b.cmp(a));
+            expected.reverse();
+            assert_eq!(actual, expected);
+        }
+
+        let mut records: Vec<Record> = ["carol", "alice", "dave", "bob"]
+            .iter()
+            .zip([35, 30, 20, 25])
+            .map(|(name, age)| Record {
+                name: name.to_string(),
+                age,
+            })
+            .collect();
+        sorter.sort_by_key(&mut records, |r| r.age);
+        let ages: Vec<u32> = records.iter().map(|r| r.age).collect();
+        assert_eq!(ages, [20, 25, 30, 35]);
+        sorter.sort_by_key(&mut records, |r| r.name.clone());
+        assert_eq!(records[0].name, "alice");
+        assert_eq!(records[3].name, "dave");
+    }
+
+    #[test]
+    fn test_sorters_match_std() {
+        check(BubbleSort);
+        check(SelectionSort);
+        check(InsertionSort);
+        check(MergeSort);
+        check(QuickSort);
+        check(HeapSort);
+        check(IntroSort);
+        check(TimSort);
+        check(ShellSort::default());
+    }
+}
@@ -0,0 +1,594 @@
+use super::{Probe, Sorter, lift, swap};
+use std::cmp::Ordering;
+use std::{ptr, slice};
+
+/// Runs shorter than this are extended with binary insertion sort before merging.
+const MAX_MIN_RUN: usize = 64;
+/// Wins in a row after which a merge switches to galloping.
+const MIN_GALLOP: usize = 7;
+
+/// Stable adaptive merge sort after TimSort: it finds the ascending and strictly descending runs
+/// already in the input, merges them through a buffer of at most n / 2 elements, and gallops
+/// through long stretches won by one side.
+///
+/// Stable: elements that compare equal keep their original order. O(n) on presorted input and
+/// O(n log n) in the worst case.
+pub struct TimSort;
+
+impl Sorter for TimSort {
+    fn sort_probed<T, F, P>(&self, arr: &mut [T], mut compare: F, probe: &mut P)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+        P: Probe<T>,
+    {
+        _tim_sort(arr, &mut |a, b| compare(a, b) == Ordering::Less, probe);
+    }
+}
+
+pub fn tim_sort<T: PartialOrd>(arr: &mut [T]) {
+    _tim_sort(arr, &mut |a, b| a < b, &mut ());
+}
+
+pub fn tim_sort_by<T, F: FnMut(&T, &T) -> Ordering>(arr: &mut [T], mut compare: F) {
+    _tim_sort(arr, &mut |a, b| compare(a, b) == Ordering::Less, &mut ());
+}
+
+pub fn tim_sort_by_key<T, K: Ord, F: FnMut(&T) -> K>(arr: &mut [T], mut key: F) {
+    _tim_sort(arr, &mut |a, b| key(a) < key(b), &mut ());
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+pub(crate) fn _tim_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    let min_run = min_run_length(arr.len());
+    let mut merger = Merger {
+        buffer: Vec::new(),
+        min_gallop: MIN_GALLOP,
+    };
+    let mut runs: Vec<Run> = Vec::new();
+
+    let mut start = 0;
+    while start < arr.len() {
+        let mut end = start + find_run(arr, start, is_less, probe);
+        if end - start < min_run {
+            let forced_end = (start + min_run).min(arr.len());
+            binary_insertion_sort(arr, start, end, forced_end, is_less, probe);
+            end = forced_end;
+        }
+        runs.push(Run {
+            start,
+            len: end - start,
+        });
+        start = end;
+
+        while let Some(i) = collapse_index(&runs) {
+            merge_at(arr, &mut runs, i, &mut merger, is_less, probe);
+        }
+    }
+
+    while runs.len() > 1 {
+        let n = runs.len();
+        let i = if n >= 3 && runs[n - 3].len < runs[n - 1].len {
+            n - 3
+        } else {
+            n - 2
+        };
+        merge_at(arr, &mut runs, i, &mut merger, is_less, probe);
+    }
+}
+
+// n itself when short, otherwise between 32 and 64 and such that n / min_run is close to a power
+// of two, which keeps the final merges balanced
+fn min_run_length(mut n: usize) -> usize {
+    let mut remainder = 0;
+    while n >= MAX_MIN_RUN {
+        remainder |= n & 1;
+        n >>= 1;
+    }
+    n + remainder
+}
+
+/// Length of the run starting at `start`, reversing it first if it is strictly descending.
+/// Descending runs must be strict, or reversing them would reorder equal elements.
+fn find_run<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) -> usize {
+    let mut end = start + 1;
+    if end == arr.len() {
+        return 1;
+    }
+
+    if is_less(&arr[end], &arr[start]) {
+        while end + 1 < arr.len() && is_less(&arr[end + 1], &arr[end]) {
+            end += 1;
+        }
+        let (mut i, mut j) = (start, end);
+        while i < j {
+            swap(arr, i, j, probe);
+            i += 1;
+            j -= 1;
+        }
+    } else {
+        while end + 1 < arr.len() && !is_less(&arr[end + 1], &arr[end]) {
+            end += 1;
+        }
+    }
+    end + 1 - start
+}
+
+/// Extends the sorted arr[start..sorted] to arr[start..end], inserting each element after the
+/// ones equal to it.
+fn binary_insertion_sort<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    start: usize,
+    sorted: usize,
+    end: usize,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    for i in sorted..end {
+        let (prefix, rest) = arr.split_at(i);
+        let position = start + prefix[start..].partition_point(|x| !is_less(&rest[0], x));
+        lift(arr, i, position, probe);
+    }
+}
+
+/// The run to merge with its successor next, keeping every run longer than the two after it
+/// so the stack stays O(log n) deep and merges stay balanced.
+fn collapse_index(runs: &[Run]) -> Option<usize> {
+    let n = runs.len();
+    if n < 2 {
+        return None;
+    }
+
+    let broken = |i: usize| runs[i].len <= runs[i + 1].len + runs[i + 2].len;
+    if (n >= 3 && broken(n - 3)) || (n >= 4 && broken(n - 4)) {
+        if runs[n - 3].len < runs[n - 1].len {
+            Some(n - 3)
+        } else {
+            Some(n - 2)
+        }
+    } else if runs[n - 2].len <= runs[n - 1].len {
+        Some(n - 2)
+    } else {
+        None
+    }
+}
+
+fn merge_at<T, F: FnMut(&T, &T) -> bool, P: Probe<T>>(
+    arr: &mut [T],
+    runs: &mut Vec<Run>,
+    i: usize,
+    merger: &mut Merger<T>,
+    is_less: &mut F,
+    probe: &mut P,
+) {
+    let (left, right) = (runs[i], runs[i + 1]);
+    runs[i].len += right.len;
+    runs.remove(i + 1);
+
+    // Elements of the left run not greater than the right run's first are already in place
+    let mid = right.start;
+    let (head, tail) = arr.split_at(mid);
+    let start = left.start + gallop(&head[left.start..], false, |x| !is_less(&tail[0], x));
+    // As are elements of the right run not less than the left run's last
+    let end = mid + gallop(&tail[..right.len], true, |x| is_less(x, &head[mid - 1]));
+    if start == mid || end == mid {
+        return;
+    }
+
+    let moves = if mid - start <= end - mid {
+        merger.merge_lo(arr, start, mid, end, is_less)
+    } else {
+        merger.merge_hi(arr, start, mid, end, is_less)
+    };
+    probe.merged(arr, start, end, moves);
+}
+
+/// Stably merges the sorted arr[..mid] and arr[mid..].
+pub(crate) fn merge<T, F: FnMut(&T, &T) -> bool>(arr: &mut [T], mid: usize, is_less: &mut F) {
+    if mid == 0 || mid == arr.len() {
+        return;
+    }
+
+    let mut runs = vec![
+        Run { start: 0, len: mid },
+        Run {
+            start: mid,
+            len: arr.len() - mid,
+        },
+    ];
+    let mut merger = Merger {
+        buffer: Vec::new(),
+        min_gallop: MIN_GALLOP,
+    };
+    merge_at(arr, &mut runs, 0, &mut merger, is_less, &mut ());
+}
+
+/// The number of leading elements of `arr` that satisfy `pred`, which must hold for a prefix.
+/// Probes 1, 3, 7, 15, ... elements in from one end before a binary search, so the cost is
+/// logarithmic in the distance of the answer from that end.
+fn gallop<T>(arr: &[T], from_right: bool, mut pred: impl FnMut(&T) -> bool) -> usize {
+    let n = arr.len();
+    let (mut lo, mut hi) = (0, n);
+    let mut offset = 1;
+    if from_right {
+        while offset <= n && !pred(&arr[n - offset]) {
+            hi = n - offset;
+            offset = 2 * offset + 1;
+        }
+        lo = (n + 1).saturating_sub(offset);
+    } else {
+        while offset <= n && pred(&arr[offset - 1]) {
+            lo = offset;
+            offset = 2 * offset + 1;
+        }
+        hi = hi.min(offset - 1);
+    }
+    lo + arr[lo..hi].partition_point(pred)
+}
+
+struct Merger<T> {
+    /// Scratch space for the shorter run. Its length stays 0, so it never drops what it holds.
+    buffer: Vec<T>,
+    min_gallop: usize,
+}
+
+/// The part of a run still in the merge buffer, and the gap in the array it will fill. Dropping
+/// it copies that part into the gap, which completes a merge and also keeps every element in the
+/// array exactly once if the comparison panics.
+struct Hole<T> {
+    src: *const T,
+    start: usize,
+    end: usize,
+    dest: *mut T,
+}
+
+impl<T> Hole<T> {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+impl<T> Drop for Hole<T> {
+    fn drop(&mut self) {
+        // SAFETY: the gap at `dest` is exactly `len()` elements long and holds no live values
+        unsafe { ptr::copy_nonoverlapping(self.src.add(self.start), self.dest, self.len()) };
+    }
+}
+
+impl<T> Merger<T> {
+    /// Merges the sorted arr[start..mid] and arr[mid..end], buffering the left run and filling
+    /// the array from the front. Returns the number of element moves.
+    fn merge_lo<F: FnMut(&T, &T) -> bool>(
+        &mut self,
+        arr: &mut [T],
+        start: usize,
+        mid: usize,
+        end: usize,
+        is_less: &mut F,
+    ) -> usize {
+        let len = mid - start;
+        self.buffer.reserve(len);
+        let v = arr.as_mut_ptr();
+        let b = self.buffer.as_mut_ptr();
+
+        // SAFETY: every index stays within arr[start..end] or the buffer's capacity. The gap
+        // between `hole.dest` and `right` always has room for exactly the buffered elements
+        // left, so no live element is overwritten, and `hole` fills it on every exit.
+        unsafe {
+            ptr::copy_nonoverlapping(v.add(start), b, len);
+            let mut hole = Hole {
+                src: b,
+                start: 0,
+                end: len,
+                dest: v.add(start),
+            };
+            let mut right = mid;
+
+            'merge: loop {
+                // One element at a time until a side wins often enough in a row
+                let (mut left_wins, mut right_wins) = (0, 0);
+                while left_wins.max(right_wins) < self.min_gallop {
+                    if is_less(&*v.add(right), &*b.add(hole.start)) {
+                        ptr::copy_nonoverlapping(v.add(right), hole.dest, 1);
+                        hole.dest = hole.dest.add(1);
+                        right += 1;
+                        (left_wins, right_wins) = (0, right_wins + 1);
+                        if right == end {
+                            break 'merge;
+                        }
+                    } else {
+                        ptr::copy_nonoverlapping(b.add(hole.start), hole.dest, 1);
+                        hole.dest = hole.dest.add(1);
+                        hole.start += 1;
+                        (left_wins, right_wins) = (left_wins + 1, 0);
+                        if hole.len() == 0 {
+                            break 'merge;
+                        }
+                    }
+                }
+
+                // Galloping: copy whole stretches while they stay long
+                loop {
+                    let key = &*v.add(right);
+                    let buffered = slice::from_raw_parts(b.add(hole.start), hole.len());
+                    let left_count = gallop(buffered, false, |x| !is_less(key, x));
+                    ptr::copy_nonoverlapping(b.add(hole.start), hole.dest, left_count);
+                    hole.dest = hole.dest.add(left_count);
+                    hole.start += left_count;
+                    if hole.len() == 0 {
+                        break 'merge;
+                    }
+
+                    ptr::copy_nonoverlapping(v.add(right), hole.dest, 1);
+                    hole.dest = hole.dest.add(1);
+                    right += 1;
+                    if right == end {
+                        break 'merge;
+                    }
+
+                    let key = &*b.add(hole.start);
+                    let rest = slice::from_raw_parts(v.add(right), end - right);
+                    let right_count = gallop(rest, false, |x| is_less(x, key));
+                    ptr::copy(v.add(right), hole.dest, right_count);
+                    hole.dest = hole.dest.add(right_count);
+                    right += right_count;
+                    if right == end {
+                        break 'merge;
+                    }
+
+                    ptr::copy_nonoverlapping(b.add(hole.start), hole.dest, 1);
+                    hole.dest = hole.dest.add(1);
+                    hole.start += 1;
+                    if hole.len() == 0 {
+                        break 'merge;
+                    }
+
+                    self.min_gallop = self.min_gallop.saturating_sub(1);
+                    if left_count < MIN_GALLOP && right_count < MIN_GALLOP {
+                        self.min_gallop += 2;
+                        break;
+                    }
+                }
+            }
+
+            2 * len + (right - mid)
+        }
+    }
+
+    /// Merges the sorted arr[start..mid] and arr[mid..end], buffering the right run and filling
+    /// the array from the back. Returns the number of element moves.
+    fn merge_hi<F: FnMut(&T, &T) -> bool>(
+        &mut self,
+        arr: &mut [T],
+        start: usize,
+        mid: usize,
+        end: usize,
+        is_less: &mut F,
+    ) -> usize {
+        let len = end - mid;
+        self.buffer.reserve(len);
+        let v = arr.as_mut_ptr();
+        let b = self.buffer.as_mut_ptr();
+
+        // SAFETY: as in `merge_lo`, mirrored: the gap starts at `left` and is as long as the
+        // buffered elements left, and is filled from its end
+        unsafe {
+            ptr::copy_nonoverlapping(v.add(mid), b, len);
+            let mut hole = Hole {
+                src: b,
+                start: 0,
+                end: len,
+                dest: v.add(mid),
+            };
+            let mut left = mid;
+
+            'merge: loop {
+                let (mut left_wins, mut right_wins) = (0, 0);
+                while left_wins.max(right_wins) < self.min_gallop {
+                    let gap_end = v.add(left + hole.len() - 1);
+                    if is_less(&*b.add(hole.end - 1), &*v.add(left - 1)) {
+                        ptr::copy_nonoverlapping(v.add(left - 1), gap_end, 1);
+                        left -= 1;
+                        hole.dest = v.add(left);
+                        (left_wins, right_wins) = (left_wins + 1, 0);
+                        if left == start {
+                            break 'merge;
+                        }
+                    } else {
+                        ptr::copy_nonoverlapping(b.add(hole.end - 1), gap_end, 1);
+                        hole.end -= 1;
+                        (left_wins, right_wins) = (0, right_wins + 1);
+                        if hole.len() == 0 {
+                            break 'merge;
+                        }
+                    }
+                }
+
+                loop {
+                    let key = &*b.add(hole.end - 1);
+                    let rest = slice::from_raw_parts(v.add(start), left - start);
+                    let keep = gallop(rest, true, |x| !is_less(key, x));
+                    let left_count = left - start - keep;
+                    ptr::copy(
+                        v.add(start + keep),
+                        v.add(start + keep + hole.len()),
+                        left_count,
+                    );
+                    left -= left_count;
+                    hole.dest = v.add(left);
+                    if left == start {
+                        break 'merge;
+                    }
+
+                    ptr::copy_nonoverlapping(b.add(hole.end - 1), v.add(left + hole.len() - 1), 1);
+                    hole.end -= 1;
+                    if hole.len() == 0 {
+                        break 'merge;
+                    }
+
+                    let key = &*v.add(left - 1);
+                    let buffered = slice::from_raw_parts(b.add(hole.start), hole.len());
+                    let keep = gallop(buffered, true, |x| is_less(x, key));
+                    let right_count = hole.len() - keep;
+                    ptr::copy_nonoverlapping(b.add(keep), v.add(left + keep), right_count);
+                    hole.end -= right_count;
+                    if hole.len() == 0 {
+                        break 'merge;
+                    }
+
+                    ptr::copy_nonoverlapping(v.add(left - 1), v.add(left + hole.len() - 1), 1);
+                    left -= 1;
+                    hole.dest = v.add(left);
+                    if left == start {
+                        break 'merge;
+                    }
+
+                    self.min_gallop = self.min_gallop.saturating_sub(1);
+                    if left_count < MIN_GALLOP && right_count < MIN_GALLOP {
+                        self.min_gallop += 2;
+                        break;
+                    }
+                }
+            }
+
+            2 * len + (mid - left)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn test_tim_sort() {
+        let mut arr = [3, 6, 8, 10, 11, 2, 9];
+        tim_sort(&mut arr);
+        assert_eq!(arr, [2, 3, 6, 8, 9, 10, 11]);
+    }
+
+    #[test]
+    fn test_min_run_length() {
+        assert_eq!(min_run_length(10), 10);
+        assert_eq!(min_run_length(64), 32);
+        assert_eq!(min_run_length(65), 33);
+        assert_eq!(min_run_length(2112), 33);
+    }
+
+    #[test]
+    fn test_gallop() {
+        let arr = [1, 2, 2, 3, 5, 8, 13, 21, 34];
+        for key in 0..40 {
+            let expected = arr.iter().filter(|&&x| x <= key).count();
+            assert_eq!(gallop(&arr, false, |&x| x <= key), expected);
+            assert_eq!(gallop(&arr, true, |&x| x <= key), expected);
+        }
+        assert_eq!(gallop(&[] as &[i32], false, |_| true), 0);
+    }
+
+    #[test]
+    fn test_tim_sort_matches_std() {
+        let mut rng = StdRng::seed_from_u64(8);
+        for len in (0..300).chain([1000, 10_000]) {
+            let mut arr: Vec<i32> = (0..len).map(|_| rng.random_range(0..1000)).collect();
+            let mut expected = arr.clone();
+            expected.sort();
+            tim_sort(&mut arr);
+            assert_eq!(arr, expected);
+        }
+    }
+
+    #[test]
+    fn test_tim_sort_is_stable() {
+        let mut rng = StdRng::seed_from_u64(9);
+        for len in (0..200).chain([2000, 20_000]) {
+            for keys in [2, 10, 1000] {
+                // Runs of sorted and reversed keys make the merges gallop
+                let mut records: Vec<(i32, usize)> = (0..len)
+                    .map(|i| match (i / 100) % 3 {
+                        0 => (rng.random_range(0..keys), i),
+                        1 => ((i % keys as usize) as i32, i),
+                        _ => (keys - (i % keys as usize) as i32, i),
+                    })
+                    .collect();
+                let mut expected = records.clone();
+                expected.sort_by_key(|&(key, _)| key);
+                tim_sort_by_key(&mut records, |&(key, _)| key);
+                assert_eq!(records, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_presorted_input_is_linear() {
+        let mut arr: Vec<u32> = (0..10_000).collect();
+        let stats = TimSort.count(&mut arr);
+        assert_eq!(stats.comparisons, arr.len() - 1);
+        assert_eq!(stats.swaps + stats.moves, 0);
+
+        let mut arr: Vec<u32> = (0..10_000).rev().collect();
+        let stats = TimSort.count(&mut arr);
+        assert_eq!(stats.comparisons, arr.len() - 1);
+        assert_eq!(stats.swaps, arr.len() / 2);
+    }
+
+    #[test]
+    fn test_trace_reports_merges() {
+        let mut arr: Vec<u16> = (0..200).map(|i| (i * 37) % 101).collect();
+        let trace = TimSort.trace(&mut arr);
+        assert!(arr.windows(2).all(|w| w[0] <= w[1]));
+        let merges = trace
+            .steps
+            .iter()
+            .filter(|step| matches!(step, crate::sort::Step::Merge { .. }))
+            .count();
+        assert_eq!(merges, 3);
+        // The last merge leaves out the ends its galloping found already in place
+        let Some(crate::sort::Step::Merge { start, end, array }) = trace.steps.last() else {
+            panic!("the last step is not a merge");
+        };
+        assert_eq!((*start, *end), (1, 199));
+        assert_eq!(array, &arr);
+    }
+
+    #[test]
+    fn test_panicking_comparison_keeps_every_element() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let original: Vec<String> = (0..500)
+            .map(|_| rng.random_range(0..100).to_string())
+            .collect();
+        for limit in [10, 500, 2000, 4000] {
+            let mut arr = original.clone();
+            let mut comparisons = 0;
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                tim_sort_by(&mut arr, |a, b| {
+                    comparisons += 1;
+                    assert!(comparisons < limit, "comparison limit");
+                    a.cmp(b)
+                })
+            }));
+            assert!(result.is_err());
+
+            let mut expected = original.clone();
+            expected.sort();
+            arr.sort();
+            assert_eq!(arr, expected);
+        }
+    }
+}
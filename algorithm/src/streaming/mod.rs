@@ -0,0 +1,5 @@
+mod top_k;
+pub use top_k::*;
+
+mod t_digest;
+pub use t_digest::*;
@@ -0,0 +1,389 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// A cluster of nearby values, summarized by their mean and count.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Centroid {
+    pub mean: f64,
+    pub weight: u64,
+}
+
+/// Approximate quantiles of a stream of numbers in bounded memory (Dunning's merging t-digest).
+/// Values are clustered into centroids that stay small near the tails, so extreme quantiles are
+/// more accurate than the median. Digests of parts of a stream merge into one of the whole.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "TDigestRepr")]
+pub struct TDigest {
+    /// Bounds the number of centroids, which is at most about this many; higher is more
+    /// accurate.
+    compression: f64,
+    /// Sorted by mean
+    centroids: Vec<Centroid>,
+    /// Values and centroids not yet merged in
+    buffer: Vec<Centroid>,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+/// The serialized form of `TDigest`, checked before it becomes one, since digests are sent
+/// between nodes to be merged.
+#[derive(Deserialize)]
+struct TDigestRepr {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<Centroid>,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl TryFrom<TDigestRepr> for TDigest {
+    type Error = &'static str;
+
+    fn try_from(repr: TDigestRepr) -> Result<Self, Self::Error> {
+        if repr.compression.is_nan() || repr.compression < 1.0 {
+            return Err("compression must be at least 1");
+        }
+        let all = || repr.centroids.iter().chain(&repr.buffer);
+        if all().any(|c| !c.mean.is_finite() || c.weight == 0) {
+            return Err("centroids must have a finite mean and some weight");
+        }
+        if !repr.centroids.is_sorted_by(|a, b| a.mean <= b.mean) {
+            return Err("centroids must be sorted by mean");
+        }
+        if all().try_fold(0u64, |count, c| count.checked_add(c.weight)) != Some(repr.count) {
+            return Err("count must be the total weight of the centroids");
+        }
+        if repr.count > 0 && !(repr.min.is_finite() && repr.max.is_finite()) {
+            return Err("min and max must be finite");
+        }
+        if repr.count > 0 && all().any(|c| !(repr.min <= c.mean && c.mean <= repr.max)) {
+            return Err("centroids must lie between min and max");
+        }
+        Ok(Self {
+            compression: repr.compression,
+            centroids: repr.centroids,
+            buffer: repr.buffer,
+            count: repr.count,
+            min: repr.min,
+            max: repr.max,
+        })
+    }
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(100.0)
+    }
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        assert!(compression >= 1.0, "compression must be at least 1");
+        Self {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (!self.is_empty()).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (!self.is_empty()).then_some(self.max)
+    }
+
+    /// Adds a value. NaN and infinities are ignored, since no mean could include them.
+    pub fn add(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.push(Centroid {
+            mean: value,
+            weight: 1,
+        });
+    }
+
+    pub fn merge(&mut self, other: &TDigest) {
+        // Centroid means are only averages, so the extremes are carried over separately
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        for &centroid in other.centroids.iter().chain(&other.buffer) {
+            self.push(centroid);
+        }
+    }
+
+    /// The centroids summarizing everything added so far, sorted by mean.
+    ///
+    /// This and the other queries take `&mut self` because they first merge the values added
+    /// since the last query into the centroids, work that later queries then reuse.
+    pub fn centroids(&mut self) -> &[Centroid] {
+        self.compress();
+        &self.centroids
+    }
+
+    /// The approximate value below which a fraction `q` of the values lie, or `None` if
+    /// nothing was added.
+    pub fn quantile(&mut self, q: f64) -> Option<f64> {
+        assert!((0.0..=1.0).contains(&q), "quantile {} is not in [0, 1]", q);
+        self.compress();
+        if self.is_empty() {
+            return None;
+        }
+        let points = self.points();
+        let rank = q * self.count as f64;
+        let i = points.partition_point(|&(r, _)| r < rank);
+        Some(match i {
+            0 => self.min,
+            i if i == points.len() => self.max,
+            i => interpolate(points[i - 1], points[i], rank),
+        })
+    }
+
+    /// The approximate fraction of values at most `value`, or `None` if nothing was added.
+    pub fn cdf(&mut self, value: f64) -> Option<f64> {
+        self.compress();
+        if self.is_empty() {
+            return None;
+        }
+        if value < self.min {
+            return Some(0.0);
+        }
+        if value >= self.max {
+            return Some(1.0);
+        }
+        let points = self.points();
+        let i = points.partition_point(|&(_, v)| v <= value);
+        let ((r0, v0), (r1, v1)) = (points[i - 1], points[i]);
+        let rank = interpolate((v0, r0), (v1, r1), value);
+        Some(rank / self.count as f64)
+    }
+
+    fn push(&mut self, centroid: Centroid) {
+        self.count += centroid.weight;
+        self.min = self.min.min(centroid.mean);
+        self.max = self.max.max(centroid.mean);
+        self.buffer.push(centroid);
+        if self.buffer.len() as f64 >= 5.0 * self.compression {
+            self.compress();
+        }
+    }
+
+    /// Merges the buffer into the centroids, combining neighbours for as long as the k1 scale
+    /// function allows: a centroid may span at most one unit of
+    /// `k(q) = compression / 2π · asin(2q - 1)`, which is steep near q = 0 and q = 1.
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.centroids);
+        all.append(&mut self.buffer);
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total = self.count as f64;
+        let scale = |q: f64| self.compression / (2.0 * PI) * (2.0 * q - 1.0).asin();
+        let limit = |k: f64| ((k * 2.0 * PI / self.compression).sin() + 1.0) / 2.0;
+
+        let mut merged: Vec<Centroid> = Vec::with_capacity(all.len());
+        let mut seen = 0;
+        let mut q_limit = limit(scale(0.0) + 1.0);
+        for centroid in all {
+            match merged.last_mut() {
+                Some(last) if (seen + last.weight + centroid.weight) as f64 / total <= q_limit => {
+                    let weight = last.weight + centroid.weight;
+                    last.mean +=
+                        (centroid.mean - last.mean) * centroid.weight as f64 / weight as f64;
+                    last.weight = weight;
+                }
+                last => {
+                    if let Some(last) = last {
+                        seen += last.weight;
+                        q_limit = limit(scale(seen as f64 / total) + 1.0);
+                    }
+                    merged.push(centroid);
+                }
+            }
+        }
+        self.centroids = merged;
+    }
+
+    /// (rank, value) at the middle of each centroid, between the extremes, for interpolation.
+    fn points(&self) -> Vec<(f64, f64)> {
+        let mut points = Vec::with_capacity(self.centroids.len() + 2);
+        points.push((0.0, self.min));
+        let mut seen = 0.0;
+        for centroid in &self.centroids {
+            let weight = centroid.weight as f64;
+            points.push((seen + weight / 2.0, centroid.mean));
+            seen += weight;
+        }
+        points.push((seen, self.max));
+        points
+    }
+}
+
+impl Extend<f64> for TDigest {
+    fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for value in values {
+            self.add(value);
+        }
+    }
+}
+
+/// The y at `x` on the line through two points, or the first y if they share an x.
+fn interpolate((x0, y0): (f64, f64), (x1, y1): (f64, f64), x: f64) -> f64 {
+    if x1 <= x0 {
+        y0
+    } else {
+        y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    /// How far `value` is from the `q` quantile of `sorted`, as a fraction of all the ranks.
+    fn rank_error(sorted: &[f64], q: f64, value: f64) -> f64 {
+        let rank = sorted.partition_point(|&x| x < value) as f64;
+        (rank / sorted.len() as f64 - q).abs()
+    }
+
+    #[test]
+    fn test_small_digest_is_exact() {
+        let mut digest = TDigest::default();
+        assert_eq!(digest.quantile(0.5), None);
+        assert_eq!(digest.cdf(1.0), None);
+
+        // Too few values for any two to share a centroid
+        digest.extend((1..=20).map(f64::from));
+        digest.extend([f64::NAN, f64::INFINITY, f64::NEG_INFINITY]);
+        assert_eq!(digest.count(), 20);
+        assert_eq!(digest.centroids().len(), 20);
+        assert_eq!(digest.quantile(0.0), Some(1.0));
+        assert_eq!(digest.quantile(1.0), Some(20.0));
+        assert_eq!(digest.quantile(0.5), Some(10.5));
+        assert_eq!(digest.cdf(0.0), Some(0.0));
+        assert_eq!(digest.cdf(10.5), Some(0.5));
+        assert_eq!(digest.cdf(20.0), Some(1.0));
+    }
+
+    #[test]
+    fn test_quantiles_of_a_long_stream() {
+        let mut rng = StdRng::seed_from_u64(20);
+        // Cubes pile up near 0, where a centroid's values are far from evenly spread
+        let mut values: Vec<f64> = (0..200_000).map(|_| rng.random::<f64>().powi(3)).collect();
+        let mut digest = TDigest::default();
+        digest.extend(values.iter().copied());
+        assert!(
+            digest.centroids().len() <= 200,
+            "{}",
+            digest.centroids().len()
+        );
+
+        values.sort_by(f64::total_cmp);
+        for q in [0.001, 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 0.999] {
+            let value = digest.quantile(q).unwrap();
+            assert!(
+                rank_error(&values, q, value) < 0.005,
+                "q {} gave {}",
+                q,
+                value
+            );
+            assert!((digest.cdf(value).unwrap() - q).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_infinities_are_ignored() {
+        let mut digest = TDigest::default();
+        digest.extend((0..200_000).map(|i| f64::from(i % 1000)));
+        digest.add(f64::INFINITY);
+        digest.extend((0..1000).map(f64::from));
+        assert_eq!(digest.count(), 201_000);
+        assert_eq!(digest.quantile(1.0), Some(999.0));
+        assert_eq!(digest.max(), Some(999.0));
+        let median = digest.quantile(0.5).unwrap();
+        assert!((median - 500.0).abs() < 10.0, "{}", median);
+    }
+
+    #[test]
+    fn test_merged_digests_match_one_digest() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let mut values: Vec<f64> = (0..100_000).map(|i| f64::from(i) / 10.0).collect();
+        values.shuffle(&mut rng);
+
+        // Digests of parts, combined as a coordinator would after receiving them serialized
+        let mut merged = TDigest::default();
+        for chunk in values.chunks(7_000) {
+            let mut part = TDigest::default();
+            part.extend(chunk.iter().copied());
+            let json = serde_json::to_string(&part).unwrap();
+            merged.merge(&serde_json::from_str(&json).unwrap());
+        }
+        assert_eq!(merged.count(), 100_000);
+        assert_eq!(merged.min(), Some(0.0));
+        assert_eq!(merged.max(), Some(9999.9));
+
+        values.sort_by(f64::total_cmp);
+        for q in [0.001, 0.05, 0.5, 0.95, 0.999] {
+            let value = merged.quantile(q).unwrap();
+            assert!(
+                rank_error(&values, q, value) < 0.005,
+                "q {} gave {}",
+                q,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize_checks_digest() {
+        let mut digest = TDigest::default();
+        digest.extend([1.0, 2.0, 3.0]);
+        let json = serde_json::to_string(&digest).unwrap();
+        let mut loaded: TDigest = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.quantile(0.5), digest.quantile(0.5));
+
+        let invalid = [
+            json.replace("\"compression\":100.0", "\"compression\":0.5"),
+            json.replace("\"count\":3", "\"count\":4"),
+            json.replace("\"max\":3.0", "\"max\":2.0"),
+        ];
+        for json in invalid {
+            assert_ne!(json, serde_json::to_string(&digest).unwrap());
+            assert!(serde_json::from_str::<TDigest>(&json).is_err(), "{}", json);
+        }
+
+        // JSON has no infinities, but other formats do
+        let infinite = TDigestRepr {
+            compression: 100.0,
+            centroids: vec![Centroid {
+                mean: f64::INFINITY,
+                weight: 1,
+            }],
+            buffer: Vec::new(),
+            count: 1,
+            min: f64::INFINITY,
+            max: f64::INFINITY,
+        };
+        assert!(TDigest::try_from(infinite).is_err());
+    }
+}
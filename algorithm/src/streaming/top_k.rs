@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// The `k` largest items seen in a stream, in O(k) memory and O(log k) per item. Collectors
+/// of parts of a stream merge into the top `k` of the whole.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "TopKRepr<T>")]
+pub struct TopK<T: Ord> {
+    k: usize,
+    /// A min-heap, so the smallest item kept is the one to evict
+    heap: BinaryHeap<Reverse<T>>,
+}
+
+/// The serialized form of `TopK`, checked before it becomes one.
+#[derive(Deserialize)]
+struct TopKRepr<T> {
+    k: usize,
+    heap: Vec<T>,
+}
+
+impl<T: Ord> TryFrom<TopKRepr<T>> for TopK<T> {
+    type Error = &'static str;
+
+    fn try_from(repr: TopKRepr<T>) -> Result<Self, Self::Error> {
+        if repr.heap.len() > repr.k {
+            return Err("more than k items");
+        }
+        Ok(Self {
+            k: repr.k,
+            heap: repr.heap.into_iter().map(Reverse).collect(),
+        })
+    }
+}
+
+impl<T: Ord> TopK<T> {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k),
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// The smallest item kept, which an item must beat to get in once `k` are kept.
+    pub fn threshold(&self) -> Option<&T> {
+        self.heap.peek().map(|Reverse(item)| item)
+    }
+
+    pub fn push(&mut self, item: T) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(item));
+        } else if let Some(mut smallest) = self.heap.peek_mut()
+            && item > smallest.0
+        {
+            smallest.0 = item;
+        }
+    }
+
+    /// Adds everything `other` kept, as if its stream had gone through this collector too.
+    pub fn merge(&mut self, other: TopK<T>) {
+        self.extend(other.heap.into_iter().map(|Reverse(item)| item));
+    }
+
+    /// The items kept, largest first.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        // Ascending in Reverse is descending in T
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(item)| item)
+            .collect()
+    }
+}
+
+impl<T: Ord> Extend<T> for TopK<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        for item in items {
+            self.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_top_k() {
+        let mut top = TopK::new(3);
+        assert_eq!(top.threshold(), None);
+        top.extend([5, 1, 9, 3, 9, 7, 2]);
+        assert_eq!(top.len(), 3);
+        assert_eq!(top.threshold(), Some(&7));
+        assert_eq!(top.into_sorted_vec(), [9, 9, 7]);
+
+        let mut top = TopK::new(5);
+        top.extend(["b", "a"]);
+        assert_eq!(top.into_sorted_vec(), ["b", "a"]);
+        let mut top = TopK::new(0);
+        top.push(1);
+        assert!(top.is_empty());
+    }
+
+    #[test]
+    fn test_merged_top_k_matches_sort() {
+        let mut rng = StdRng::seed_from_u64(19);
+        let items: Vec<u32> = (0..10_000).map(|_| rng.random_range(0..5000)).collect();
+
+        // Partial collectors over chunks, like workers each seeing part of the stream
+        let mut top = TopK::new(25);
+        for chunk in items.chunks(1234) {
+            let mut partial = TopK::new(25);
+            partial.extend(chunk.iter().copied());
+            top.merge(partial);
+        }
+
+        let mut expected = items.clone();
+        expected.sort_by(|a, b| b.cmp(a));
+        expected.truncate(25);
+        assert_eq!(top.into_sorted_vec(), expected);
+    }
+
+    #[test]
+    fn test_deserialize_checks_k() {
+        let mut top = TopK::new(2);
+        top.extend([3, 1, 2]);
+        let json = serde_json::to_string(&top).unwrap();
+        let top: TopK<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(top.into_sorted_vec(), [3, 2]);
+
+        let error = serde_json::from_str::<TopK<i32>>(r#"{"k":1,"heap":[1,2]}"#).unwrap_err();
+        assert!(error.to_string().contains("more than k items"), "{}", error);
+    }
+}
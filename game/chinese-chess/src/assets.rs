@@ -0,0 +1,71 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// Directory whose SVGs replace the embedded ones, e.g. for trying out artwork without a rebuild.
+pub const ASSETS_DIR_ENV: &str = "CHINESE_CHESS_ASSETS";
+
+macro_rules! embed_pieces {
+    ($($name:literal),* $(,)?) => {
+        pub const PIECE_NAMES: [&str; 14] = [$($name),*];
+
+        fn embedded(name: &str) -> Option<&'static [u8]> {
+            match name {
+                $($name => Some(include_bytes!(concat!("../assets/images/", $name, ".svg"))),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+embed_pieces!(
+    "red_general",
+    "red_advisor",
+    "red_elephant",
+    "red_horse",
+    "red_chariot",
+    "red_cannon",
+    "red_soldier",
+    "black_general",
+    "black_advisor",
+    "black_elephant",
+    "black_horse",
+    "black_chariot",
+    "black_cannon",
+    "black_soldier",
+);
+
+/// Finds the SVG for a piece: the selected piece set first, then the override directory, then
+/// the copy embedded in the binary.
+pub fn piece_svg(name: &str, piece_set: Option<&Path>) -> Option<Cow<'static, [u8]>> {
+    let override_dir = std::env::var_os(ASSETS_DIR_ENV).map(PathBuf::from);
+    for dir in [piece_set.map(Path::to_path_buf), override_dir]
+        .into_iter()
+        .flatten()
+    {
+        let path = dir.join(format!("{}.svg", name));
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                log::debug!("Loaded SVG for {} from {:?}", name, path);
+                return Some(Cow::Owned(bytes));
+            }
+            Err(e) => log::debug!("No SVG for {} at {:?}: {}", name, path, e),
+        }
+    }
+
+    embedded(name).map(Cow::Borrowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piece_svg_falls_back_to_embedded() {
+        let missing_set = Path::new("no/such/piece/set");
+        for name in PIECE_NAMES {
+            let svg = piece_svg(name, Some(missing_set)).unwrap();
+            assert!(svg.starts_with(b"<svg"), "{} is not an SVG", name);
+        }
+        assert!(piece_svg("red_queen", None).is_none());
+    }
+}
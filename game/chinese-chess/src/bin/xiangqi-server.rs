@@ -0,0 +1,30 @@
+use anyhow::Result;
+use clap::Parser;
+use log::LevelFilter;
+use simple_logger::SimpleLogger;
+use std::net::TcpListener;
+
+/// Hosts Chinese Chess games for the app's "Play Online" mode
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    #[arg(
+        short,
+        long,
+        default_value = "127.0.0.1:9000",
+        help = "Address to listen on, e.g. 0.0.0.0:9000 to accept other machines",
+        value_name = "ADDR"
+    )]
+    bind: String,
+}
+
+fn main() -> Result<()> {
+    SimpleLogger::new()
+        .with_level(LevelFilter::Info)
+        .with_colors(true)
+        .env()
+        .init()?;
+
+    let cli = Cli::parse();
+    chinese_chess::server::serve(TcpListener::bind(&cli.bind)?)
+}
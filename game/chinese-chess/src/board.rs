@@ -0,0 +1,292 @@
+use anyhow::{bail, Result};
+
+pub const ROWS: usize = 10;
+pub const COLS: usize = 9;
+
+pub const START_FEN: &str = "rnbakabnr/9/1c5c1/p1p1p1p1p/9/9/P1P1P1P1P/1C5C1/9/RNBAKABNR w - - 0 1";
+
+// Row 0 is Black's back rank (top of the screen), row 9 is Red's.
+pub type Board = [[Option<Piece>; COLS]; ROWS];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub enum Player {
+    #[default]
+    Red,
+    Black,
+}
+
+impl Player {
+    pub fn opponent(self) -> Self {
+        match self {
+            Player::Red => Player::Black,
+            Player::Black => Player::Red,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Player::Red => "Red",
+            Player::Black => "Black",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum PieceType {
+    General,
+    Advisor,
+    Elephant,
+    Horse,
+    Chariot,
+    Cannon,
+    Soldier,
+}
+
+impl PieceType {
+    pub fn name(self) -> &'static str {
+        match self {
+            PieceType::General => "General",
+            PieceType::Advisor => "Advisor",
+            PieceType::Elephant => "Elephant",
+            PieceType::Horse => "Horse",
+            PieceType::Chariot => "Chariot",
+            PieceType::Cannon => "Cannon",
+            PieceType::Soldier => "Soldier",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct Piece {
+    pub piece_type: PieceType,
+    pub player: Player,
+}
+
+impl Piece {
+    pub fn new(piece_type: PieceType, player: Player) -> Self {
+        Self { piece_type, player }
+    }
+
+    pub fn from_fen_char(c: char) -> Option<Self> {
+        let piece_type = match c.to_ascii_lowercase() {
+            'k' => PieceType::General,
+            'a' => PieceType::Advisor,
+            'b' | 'e' => PieceType::Elephant,
+            'n' | 'h' => PieceType::Horse,
+            'r' => PieceType::Chariot,
+            'c' => PieceType::Cannon,
+            'p' => PieceType::Soldier,
+            _ => return None,
+        };
+        let player = if c.is_ascii_uppercase() {
+            Player::Red
+        } else {
+            Player::Black
+        };
+        Some(Self::new(piece_type, player))
+    }
+
+    pub fn fen_char(self) -> char {
+        let c = match self.piece_type {
+            PieceType::General => 'k',
+            PieceType::Advisor => 'a',
+            PieceType::Elephant => 'b',
+            PieceType::Horse => 'n',
+            PieceType::Chariot => 'r',
+            PieceType::Cannon => 'c',
+            PieceType::Soldier => 'p',
+        };
+        match self.player {
+            Player::Red => c.to_ascii_uppercase(),
+            Player::Black => c,
+        }
+    }
+
+    /// The traditional character carved on the piece; most differ between Red and Black.
+    pub fn character(self) -> char {
+        match (self.player, self.piece_type) {
+            (Player::Red, PieceType::General) => '帥',
+            (Player::Red, PieceType::Advisor) => '仕',
+            (Player::Red, PieceType::Elephant) => '相',
+            (Player::Red, PieceType::Horse) => '傌',
+            (Player::Red, PieceType::Chariot) => '俥',
+            (Player::Red, PieceType::Cannon) => '炮',
+            (Player::Red, PieceType::Soldier) => '兵',
+            (Player::Black, PieceType::General) => '將',
+            (Player::Black, PieceType::Advisor) => '士',
+            (Player::Black, PieceType::Elephant) => '象',
+            (Player::Black, PieceType::Horse) => '馬',
+            (Player::Black, PieceType::Chariot) => '車',
+            (Player::Black, PieceType::Cannon) => '砲',
+            (Player::Black, PieceType::Soldier) => '卒',
+        }
+    }
+}
+
+/// A board together with the side to move and the move counters, i.e. everything a FEN string
+/// describes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Position {
+    pub board: Board,
+    pub side_to_move: Player,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::from_fen(START_FEN).expect("START_FEN is valid")
+    }
+}
+
+impl Position {
+    pub fn from_fen(fen: &str) -> Result<Self> {
+        let mut fields = fen.split_whitespace();
+        let Some(placement) = fields.next() else {
+            bail!("empty FEN");
+        };
+
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != ROWS {
+            bail!("FEN must have {} ranks, found {}", ROWS, ranks.len());
+        }
+
+        let mut board: Board = [[None; COLS]; ROWS];
+        for (row, rank) in ranks.iter().enumerate() {
+            let mut col = 0;
+            for c in rank.chars() {
+                if let Some(empty) = c.to_digit(10) {
+                    col += empty as usize;
+                } else if let Some(piece) = Piece::from_fen_char(c) {
+                    if col >= COLS {
+                        bail!("rank {} is longer than {} files", row + 1, COLS);
+                    }
+                    board[row][col] = Some(piece);
+                    col += 1;
+                } else {
+                    bail!("unexpected character '{}' in FEN", c);
+                }
+            }
+            if col != COLS {
+                bail!("rank {} has {} files, expected {}", row + 1, col, COLS);
+            }
+        }
+
+        for player in [Player::Red, Player::Black] {
+            let general = Some(Piece::new(PieceType::General, player));
+            let generals: Vec<(usize, usize)> = (0..ROWS)
+                .flat_map(|row| (0..COLS).map(move |col| (row, col)))
+                .filter(|&(row, col)| board[row][col] == general)
+                .collect();
+            let &[(row, col)] = generals.as_slice() else {
+                bail!(
+                    "{} must have exactly one General, found {}",
+                    player.name(),
+                    generals.len()
+                );
+            };
+            // The General never leaves the 3x3 palace on its own side
+            let palace_rows = match player {
+                Player::Red => 7..ROWS,
+                Player::Black => 0..3,
+            };
+            if !palace_rows.contains(&row) || !(3..6).contains(&col) {
+                bail!("{} General is outside its palace", player.name());
+            }
+        }
+
+        let side_to_move = match fields.next() {
+            None | Some("w") | Some("r") => Player::Red,
+            Some("b") => Player::Black,
+            Some(other) => bail!("unknown side to move '{}'", other),
+        };
+
+        // Castling and en passant fields are always "-" in Xiangqi.
+        let _ = fields.next();
+        let _ = fields.next();
+
+        let halfmove_clock = match fields.next() {
+            Some(n) => n.parse()?,
+            None => 0,
+        };
+        let fullmove_number = match fields.next() {
+            Some(n) => n.parse()?,
+            None => 1,
+        };
+
+        Ok(Self {
+            board,
+            side_to_move,
+            halfmove_clock,
+            fullmove_number: fullmove_number.max(1),
+        })
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for (row, rank) in self.board.iter().enumerate() {
+            if row > 0 {
+                fen.push('/');
+            }
+            let mut empty = 0;
+            for square in rank {
+                match square {
+                    Some(piece) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece.fen_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+        }
+
+        let side = match self.side_to_move {
+            Player::Red => "w",
+            Player::Black => "b",
+        };
+        format!(
+            "{} {} - - {} {}",
+            fen, side, self.halfmove_clock, self.fullmove_number
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_fen_round_trip() {
+        let position = Position::default();
+        assert_eq!(position.to_fen(), START_FEN);
+        assert_eq!(
+            position.board[9][4],
+            Some(Piece::new(PieceType::General, Player::Red))
+        );
+        assert_eq!(
+            position.board[2][7],
+            Some(Piece::new(PieceType::Cannon, Player::Black))
+        );
+    }
+
+    #[test]
+    fn test_from_fen_errors() {
+        assert!(Position::from_fen("").is_err());
+        assert!(Position::from_fen("9/9/9/9/9/9/9/9/9/9 w").is_err());
+        assert!(Position::from_fen("4k4/9/9/9/9/9/9/9/9/4K3 w").is_err());
+        assert!(Position::from_fen("4k4/9/9/9/9/9/9/9/9/4K4 x").is_err());
+        assert!(Position::from_fen("4k4/9/9/9/9/9/9/9/9/4K4 b - - 0 12").is_ok());
+        // Missing, duplicate and out-of-palace Generals
+        assert!(Position::from_fen("9/9/9/9/9/9/9/9/9/4K4 w").is_err());
+        assert!(Position::from_fen("3kk4/9/9/9/9/9/9/9/9/4K4 w").is_err());
+        assert!(Position::from_fen("4k4/9/9/9/9/9/9/9/9/K8 w").is_err());
+        assert!(Position::from_fen("9/9/9/4k4/9/9/9/9/9/4K4 w").is_err());
+        assert!(Position::from_fen("9/9/3k5/9/9/9/9/5K3/9/9 w").is_ok());
+    }
+}
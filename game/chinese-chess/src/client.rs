@@ -0,0 +1,100 @@
+//! Connection from the app to a game server, run on a background thread so the UI never blocks
+//! on the network.
+
+use crate::protocol::{self, ClientMessage, ServerMessage};
+use anyhow::{anyhow, Result};
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+#[derive(Debug)]
+pub enum Event {
+    Message(ServerMessage),
+    Disconnected(String),
+}
+
+pub struct Connection {
+    outgoing: Sender<ClientMessage>,
+    incoming: Receiver<Event>,
+}
+
+impl Connection {
+    /// Joins `room` on the server at `address` (host:port); an empty room asks for a new one.
+    /// `notify` is called after every event, e.g. to request a repaint.
+    pub fn open(
+        address: &str,
+        room: &str,
+        name: &str,
+        watch: bool,
+        notify: impl Fn() + Send + 'static,
+    ) -> Self {
+        let room = match room.trim() {
+            "" => "new",
+            room => room,
+        };
+        let mut url = format!(
+            "ws://{}/ws/{}?name={}",
+            address.trim(),
+            encode_component(room),
+            encode_component(name.trim())
+        );
+        if watch {
+            url.push_str("&watch=1");
+        }
+
+        let (outgoing, outgoing_rx) = mpsc::channel();
+        let (incoming_tx, incoming) = mpsc::channel();
+        let address = address.trim().to_string();
+        std::thread::spawn(move || {
+            let send = |event| {
+                let _ = incoming_tx.send(event);
+                notify();
+            };
+            let result = run(&address, &url, &outgoing_rx, |message| {
+                send(Event::Message(message))
+            });
+            let reason = match result {
+                Ok(()) => "connection closed".to_string(),
+                Err(e) => format!("{:#}", e),
+            };
+            log::info!("Disconnected from {}: {}", address, reason);
+            send(Event::Disconnected(reason));
+        });
+
+        Self { outgoing, incoming }
+    }
+
+    pub fn send(&self, message: ClientMessage) {
+        // A send after the thread has ended shows up as a Disconnected event
+        let _ = self.outgoing.send(message);
+    }
+
+    pub fn poll(&self) -> Vec<Event> {
+        self.incoming.try_iter().collect()
+    }
+}
+
+fn run(
+    address: &str,
+    url: &str,
+    outgoing: &Receiver<ClientMessage>,
+    mut on_message: impl FnMut(ServerMessage),
+) -> Result<()> {
+    log::info!("Connecting to {}", url);
+    let stream = TcpStream::connect(address)?;
+    let (mut ws, _) = tungstenite::client(url, stream).map_err(|e| anyhow!("{}", e))?;
+    protocol::pump(&mut ws, outgoing, |text| match serde_json::from_str(text) {
+        Ok(message) => on_message(message),
+        Err(e) => log::warn!("Ignoring unexpected message {}: {}", text, e),
+    })
+}
+
+fn encode_component(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,39 @@
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Cli {
+    /// Starts the game when no command is given
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Generate endgame tables, e.g. `tablebase KRvKAA KHvKE`
+    Tablebase {
+        #[arg(
+            short,
+            long,
+            default_value = "tablebase",
+            help = "Directory the tables are written to",
+            value_name = "DIR"
+        )]
+        dir: String,
+        #[arg(required = true, value_name = "SIGNATURE")]
+        signatures: Vec<String>,
+    },
+    /// Import PGN files into the game database
+    Import {
+        #[arg(
+            short,
+            long,
+            default_value = "games.sqlite",
+            help = "Database file, created if missing",
+            value_name = "FILE"
+        )]
+        database: String,
+        #[arg(required = true, value_name = "PGN")]
+        files: Vec<String>,
+    },
+}
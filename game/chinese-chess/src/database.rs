@@ -0,0 +1,407 @@
+//! Game archive in an SQLite file. Every position reached in a game is indexed by a Zobrist hash
+//! of its board and side to move, so a position can be looked up across all games at once.
+
+use crate::board::{Board, Player, Position, COLS};
+use crate::notation;
+use crate::record::{self, GameRecord, Move};
+use crate::rules;
+use anyhow::{Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS games (
+    id INTEGER PRIMARY KEY,
+    red TEXT NOT NULL,
+    black TEXT NOT NULL,
+    result TEXT NOT NULL,
+    date TEXT NOT NULL,
+    opening TEXT NOT NULL,
+    plies INTEGER NOT NULL,
+    moves TEXT NOT NULL,
+    pgn TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS positions (
+    hash INTEGER NOT NULL,
+    game_id INTEGER NOT NULL REFERENCES games(id),
+    ply INTEGER NOT NULL,
+    next_move TEXT
+);
+CREATE INDEX IF NOT EXISTS positions_by_hash ON positions(hash);
+CREATE INDEX IF NOT EXISTS games_by_red ON games(red);
+CREATE INDEX IF NOT EXISTS games_by_black ON games(black);
+";
+
+/// Filters for `GameDatabase::search`; empty fields match every game.
+#[derive(Clone, Default, Debug)]
+pub struct GameQuery {
+    /// Part of either player's name.
+    pub player: String,
+    /// "1-0", "0-1" or "1/2-1/2".
+    pub result: String,
+    /// Part of the opening name, or the first moves in ICCS, e.g. "h2e2 h9g7".
+    pub opening: String,
+    /// Games that reached this position, whatever the move counters.
+    pub position: Option<Position>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameSummary {
+    pub id: i64,
+    pub red: String,
+    pub black: String,
+    pub result: String,
+    pub date: String,
+    pub opening: String,
+    pub plies: usize,
+    /// When searching by position: the first ply at which the game reached it, and the move
+    /// played from there (`None` if the game ended there).
+    pub reached: Option<(usize, Option<String>)>,
+}
+
+/// A move played from a searched position, with how the games continuing with it ended.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Continuation {
+    pub mv: String,
+    pub games: usize,
+    pub red_wins: usize,
+    pub draws: usize,
+    pub black_wins: usize,
+}
+
+#[derive(Default, Debug)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub failed: Vec<String>,
+}
+
+pub struct GameDatabase {
+    connection: Connection,
+}
+
+impl GameDatabase {
+    /// Opens the archive at `path`, creating it if needed. ":memory:" gives a throwaway one.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let connection =
+            Connection::open(path).with_context(|| format!("cannot open {:?}", path))?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// Imports every game in a PGN file's text. Games that fail to parse are reported and
+    /// skipped; the rest are stored in a single transaction.
+    pub fn import_pgn(&mut self, text: &str) -> Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+        let transaction = self.connection.transaction()?;
+        for (i, game) in record::parse_games(text).into_iter().enumerate() {
+            match game {
+                Ok(game) => {
+                    insert(&transaction, &game)?;
+                    summary.imported += 1;
+                }
+                Err(e) => summary.failed.push(format!("game {}: {:#}", i + 1, e)),
+            }
+        }
+        transaction.commit()?;
+        Ok(summary)
+    }
+
+    pub fn import(&mut self, game: &GameRecord) -> Result<i64> {
+        let transaction = self.connection.transaction()?;
+        let id = insert(&transaction, game)?;
+        transaction.commit()?;
+        Ok(id)
+    }
+
+    pub fn load(&self, id: i64) -> Result<GameRecord> {
+        let pgn: String = self
+            .connection
+            .query_row("SELECT pgn FROM games WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .with_context(|| format!("no game {}", id))?;
+        GameRecord::from_pgn(&pgn)
+    }
+
+    pub fn search(&self, query: &GameQuery, limit: usize) -> Result<Vec<GameSummary>> {
+        let mut conditions = vec!["1".to_string()];
+        let mut values = Vec::new();
+        let player = query.player.trim();
+        if !player.is_empty() {
+            conditions.push("(g.red LIKE ? OR g.black LIKE ?)".to_string());
+            values.push(Value::Text(format!("%{}%", player)));
+            values.push(Value::Text(format!("%{}%", player)));
+        }
+        let result = query.result.trim();
+        if !result.is_empty() {
+            conditions.push("g.result = ?".to_string());
+            values.push(Value::Text(result.to_string()));
+        }
+        let opening = query.opening.trim();
+        if !opening.is_empty() {
+            conditions.push("(g.opening LIKE ? OR g.moves LIKE ?)".to_string());
+            values.push(Value::Text(format!("%{}%", opening)));
+            values.push(Value::Text(format!("{}%", opening.to_ascii_lowercase())));
+        }
+
+        // SQLite fills the bare p.next_move from the row that MIN(p.ply) picked
+        let (reached, join) = match &query.position {
+            Some(position) => {
+                values.insert(
+                    0,
+                    Value::Integer(position_hash(&position.board, position.side_to_move)),
+                );
+                (
+                    "MIN(p.ply), p.next_move",
+                    "JOIN positions p ON p.game_id = g.id AND p.hash = ?",
+                )
+            }
+            None => ("NULL, NULL", ""),
+        };
+        values.push(Value::Integer(limit as i64));
+
+        let sql = format!(
+            "SELECT g.id, g.red, g.black, g.result, g.date, g.opening, g.plies, {}
+             FROM games g {} WHERE {} GROUP BY g.id ORDER BY g.id LIMIT ?",
+            reached,
+            join,
+            conditions.join(" AND ")
+        );
+        let mut statement = self.connection.prepare(&sql)?;
+        let rows = statement.query_map(params_from_iter(values), |row| {
+            let ply: Option<i64> = row.get(7)?;
+            Ok(GameSummary {
+                id: row.get(0)?,
+                red: row.get(1)?,
+                black: row.get(2)?,
+                result: row.get(3)?,
+                date: row.get(4)?,
+                opening: row.get(5)?,
+                plies: row.get::<_, i64>(6)? as usize,
+                reached: match ply {
+                    Some(ply) => Some((ply as usize, row.get(8)?)),
+                    None => None,
+                },
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// The moves played from `position` across the archive, most popular first.
+    pub fn continuations(&self, position: &Position) -> Result<Vec<Continuation>> {
+        let mut statement = self.connection.prepare(
+            "SELECT p.next_move, COUNT(*),
+                    SUM(g.result = '1-0'), SUM(g.result = '1/2-1/2'), SUM(g.result = '0-1')
+             FROM positions p JOIN games g ON g.id = p.game_id
+             WHERE p.hash = ?1 AND p.next_move IS NOT NULL
+             GROUP BY p.next_move ORDER BY COUNT(*) DESC, p.next_move",
+        )?;
+        let hash = position_hash(&position.board, position.side_to_move);
+        let rows = statement.query_map([hash], |row| {
+            Ok(Continuation {
+                mv: row.get(0)?,
+                games: row.get::<_, i64>(1)? as usize,
+                red_wins: row.get::<_, i64>(2)? as usize,
+                draws: row.get::<_, i64>(3)? as usize,
+                black_wins: row.get::<_, i64>(4)? as usize,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+fn insert(connection: &Connection, game: &GameRecord) -> Result<i64> {
+    let tag = |name| game.tag(name).unwrap_or("?").to_string();
+    let moves: Vec<String> = game.moves.iter().map(Move::iccs).collect();
+    connection.execute(
+        "INSERT INTO games (red, black, result, date, opening, plies, moves, pgn)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            tag("Red"),
+            tag("Black"),
+            game.tag("Result").unwrap_or("*"),
+            tag("Date"),
+            opening(game),
+            game.moves.len() as i64,
+            moves.join(" "),
+            game.to_pgn()
+        ],
+    )?;
+    let id = connection.last_insert_rowid();
+
+    let mut statement = connection.prepare_cached(
+        "INSERT INTO positions (hash, game_id, ply, next_move) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    let mut position = game.start.clone();
+    for ply in 0..=game.moves.len() {
+        let hash = position_hash(&position.board, position.side_to_move);
+        let next = game.moves.get(ply);
+        statement.execute(params![hash, id, ply as i64, next.map(Move::iccs)])?;
+        if let Some(&mv) = next {
+            rules::play(&mut position, mv);
+        }
+    }
+    Ok(id)
+}
+
+/// The "Opening" or "ECCO" tag when the record has one, otherwise its first two moves in WXF.
+fn opening(game: &GameRecord) -> String {
+    if let Some(name) = game.tag("Opening").or(game.tag("ECCO")) {
+        return name.to_string();
+    }
+    let mut position = game.start.clone();
+    let mut names = Vec::new();
+    for &mv in game.moves.iter().take(2) {
+        names.push(notation::to_wxf(&position.board, mv).unwrap_or_else(|| mv.iccs()));
+        rules::play(&mut position, mv);
+    }
+    names.join(" ")
+}
+
+/// Zobrist hash of the board and side to move. The keys come from a fixed generator, so hashes
+/// stay valid across runs and machines.
+pub fn position_hash(board: &Board, side: Player) -> i64 {
+    let mut hash = match side {
+        Player::Red => 0,
+        Player::Black => splitmix64(u64::MAX),
+    };
+    for (row, pieces) in board.iter().enumerate() {
+        for (col, piece) in pieces.iter().enumerate() {
+            if let Some(piece) = piece {
+                let kind = piece.player as u64 * 7 + piece.piece_type as u64;
+                hash ^= splitmix64(((row * COLS + col) as u64) * 14 + kind);
+            }
+        }
+    }
+    hash as i64
+}
+
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAMES: &str = "\
+[Red \"Hu Ronghua\"]
+[Black \"Yang Guanlin\"]
+[Result \"1-0\"]
+[Opening \"Central Cannon\"]
+
+1. h2e2 h9g7 2. h0g2 i9h9 1-0
+
+[Red \"Xu Yinchuan\"]
+[Black \"Hu Ronghua\"]
+[Result \"1/2-1/2\"]
+
+1. C2.5 H8+7 2. H2+3 C2.5 1/2-1/2
+
+[Red \"Lu Qin\"]
+[Black \"Xu Yinchuan\"]
+[Result \"0-1\"]
+
+1. c3c4 g6g5 0-1
+
+[Red \"Broken\"]
+
+1. h2h8 *
+";
+
+    fn database() -> GameDatabase {
+        let mut database = GameDatabase::open(":memory:").unwrap();
+        let summary = database.import_pgn(GAMES).unwrap();
+        assert_eq!(summary.imported, 3);
+        assert_eq!(summary.failed.len(), 1);
+        database
+    }
+
+    #[test]
+    fn test_search_by_tags() {
+        let database = database();
+        let query = |query: GameQuery| -> Vec<i64> {
+            let games = database.search(&query, 100).unwrap();
+            games.iter().map(|game| game.id).collect()
+        };
+
+        let hu = query(GameQuery {
+            player: "Ronghua".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(hu, [1, 2]);
+        let draws = query(GameQuery {
+            result: "1/2-1/2".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(draws, [2]);
+        let cannon = query(GameQuery {
+            opening: "Central".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(cannon, [1]);
+        let by_moves = query(GameQuery {
+            opening: "h2e2 h9g7".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(by_moves, [1, 2]);
+        assert_eq!(
+            database.search(&GameQuery::default(), 100).unwrap()[2].opening,
+            "P7+1 P7+1"
+        );
+    }
+
+    #[test]
+    fn test_search_by_position() {
+        let database = database();
+        let mut position = Position::default();
+        rules::play(&mut position, Move::new((7, 7), (7, 4)));
+        rules::play(&mut position, Move::new((0, 7), (2, 6)));
+
+        let query = GameQuery {
+            position: Some(position.clone()),
+            ..Default::default()
+        };
+        let games = database.search(&query, 100).unwrap();
+        let reached: Vec<_> = games.iter().map(|game| game.reached.clone()).collect();
+        assert_eq!(
+            reached,
+            [
+                Some((2, Some("h0g2".to_string()))),
+                Some((2, Some("h0g2".to_string())))
+            ]
+        );
+
+        let continuations = database.continuations(&position).unwrap();
+        assert_eq!(
+            continuations,
+            [Continuation {
+                mv: "h0g2".to_string(),
+                games: 2,
+                red_wins: 1,
+                draws: 1,
+                black_wins: 0,
+            }]
+        );
+
+        // Counters are not part of the position
+        position.fullmove_number = 40;
+        assert_eq!(database.continuations(&position).unwrap().len(), 1);
+        position.side_to_move = Player::Black;
+        assert!(database.continuations(&position).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load() {
+        let database = database();
+        let game = database.load(2).unwrap();
+        assert_eq!(game.tag("Red"), Some("Xu Yinchuan"));
+        assert_eq!(game.moves.len(), 4);
+        assert!(database.load(42).is_err());
+    }
+}
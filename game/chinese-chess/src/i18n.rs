@@ -0,0 +1,571 @@
+//! Translations of the UI into English, Simplified Chinese and Traditional Chinese, and the
+//! lookup of a system font able to draw the Chinese ones.
+
+use crate::board::{PieceType, Player};
+use crate::protocol::Seat;
+use crate::settings::BoardTheme;
+use crate::setup::Handicap;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    SimplifiedChinese,
+    TraditionalChinese,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [
+        Language::English,
+        Language::SimplifiedChinese,
+        Language::TraditionalChinese,
+    ];
+
+    /// The language's name in itself, as shown in the language picker.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::SimplifiedChinese => "简体中文",
+            Language::TraditionalChinese => "繁體中文",
+        }
+    }
+
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Language::English => &ENGLISH,
+            Language::SimplifiedChinese => &SIMPLIFIED_CHINESE,
+            Language::TraditionalChinese => &TRADITIONAL_CHINESE,
+        }
+    }
+}
+
+/// Substitutes `args` for the `{}` placeholders of a translated template, in order.
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut filled = String::new();
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        filled.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            filled.push_str(&arg.to_string());
+        }
+        filled.push_str(part);
+    }
+    filled
+}
+
+/// Every piece of text the app shows. Templates contain `{}` placeholders for `fill`.
+pub struct Strings {
+    pub title: &'static str,
+    pub players: [&'static str; 2],
+    pub spectator: &'static str,
+    pub piece_types: [&'static str; 7],
+    pub themes: [&'static str; 4],
+    pub handicaps: [&'static str; 7],
+
+    pub game_record: &'static str,
+    pub copy_record: &'static str,
+    pub move_status: &'static str,
+    pub settings: &'static str,
+    pub new_game: &'static str,
+    pub play_online: &'static str,
+    pub game_database: &'static str,
+    pub room_watching: &'static str,
+    pub room_playing: &'static str,
+    pub offer_draw: &'static str,
+    pub resign: &'static str,
+    pub leave: &'static str,
+    pub left_online: &'static str,
+    pub endgame_win: &'static str,
+    pub endgame_mated: &'static str,
+    pub endgame_loss: &'static str,
+    pub endgame_draw: &'static str,
+    pub move_label: &'static str,
+    pub move_hint: &'static str,
+
+    pub board_theme: &'static str,
+    pub flip_board: &'static str,
+    pub piece_sets_dir: &'static str,
+    pub piece_set: &'static str,
+    pub bundled: &'static str,
+    pub tablebase_dir: &'static str,
+    pub language: &'static str,
+    pub character_pieces: &'static str,
+    pub font_path: &'static str,
+    pub no_cjk_font: &'static str,
+
+    pub handicap_preset: &'static str,
+    pub handicap: &'static str,
+    pub given_by: &'static str,
+    pub custom_setup: &'static str,
+    pub fen: &'static str,
+    pub load_fen_file: &'static str,
+    pub start: &'static str,
+
+    pub database: &'static str,
+    pub import_pgn: &'static str,
+    pub import: &'static str,
+    pub imported: &'static str,
+    pub player: &'static str,
+    pub result: &'static str,
+    pub any: &'static str,
+    pub opening: &'static str,
+    pub opening_hint: &'static str,
+    pub position_fen: &'static str,
+    pub current: &'static str,
+    pub search: &'static str,
+    pub games_found: &'static str,
+    pub continuations: &'static str,
+    pub games_count: &'static str,
+    pub game_columns: [&'static str; 5],
+    pub reached_at: &'static str,
+    pub game_ended: &'static str,
+    pub load: &'static str,
+    pub loaded: &'static str,
+
+    pub server: &'static str,
+    pub name: &'static str,
+    pub room: &'static str,
+    pub room_hint: &'static str,
+    pub watch_only: &'static str,
+    pub connect: &'static str,
+    pub connecting: &'static str,
+    pub disconnected: &'static str,
+    pub joined: &'static str,
+    pub seated: &'static str,
+    pub left_game: &'static str,
+    pub draw_offered: &'static str,
+    pub game_over: &'static str,
+    pub refused: &'static str,
+    pub bad_server_move: &'static str,
+    /// Checkmate, stalemate, resignation and agreement, as the server names them.
+    pub end_reasons: [&'static str; 4],
+
+    pub selection_cancelled: &'static str,
+    pub selected: &'static str,
+    pub square_empty: &'static str,
+    pub square_piece: &'static str,
+    pub illegal_move: &'static str,
+    pub moved: &'static str,
+    pub captures: &'static str,
+}
+
+impl Strings {
+    pub fn player(&self, player: Player) -> &'static str {
+        self.players[player as usize]
+    }
+
+    pub fn seat(&self, seat: Seat) -> &'static str {
+        seat.player()
+            .map_or(self.spectator, |player| self.player(player))
+    }
+
+    pub fn piece_type(&self, piece_type: PieceType) -> &'static str {
+        self.piece_types[piece_type as usize]
+    }
+
+    pub fn theme(&self, theme: BoardTheme) -> &'static str {
+        self.themes[theme as usize]
+    }
+
+    pub fn handicap(&self, handicap: Handicap) -> &'static str {
+        self.handicaps[handicap as usize]
+    }
+
+    /// Translates a game-over reason sent by the server, passing unknown ones through.
+    pub fn end_reason<'a>(&self, reason: &'a str) -> &'a str {
+        match reason {
+            "checkmate" => self.end_reasons[0],
+            "stalemate" => self.end_reasons[1],
+            "resignation" => self.end_reasons[2],
+            "agreement" => self.end_reasons[3],
+            _ => reason,
+        }
+    }
+}
+
+pub static ENGLISH: Strings = Strings {
+    title: "Chinese Chess Game",
+    players: ["Red", "Black"],
+    spectator: "Spectator",
+    piece_types: [
+        "General", "Advisor", "Elephant", "Horse", "Chariot", "Cannon", "Soldier",
+    ],
+    themes: ["Wood", "Dark", "Jade", "Paper"],
+    handicaps: [
+        "No handicap",
+        "One Horse",
+        "One Chariot",
+        "Both Horses",
+        "Chariot and Horse",
+        "Both Chariots",
+        "Nine pieces",
+    ],
+
+    game_record: "Game Record",
+    copy_record: "Copy Record",
+    move_status: "Move {} - Current player: {}",
+    settings: "Settings",
+    new_game: "New Game",
+    play_online: "Play Online",
+    game_database: "Game Database",
+    room_watching: "Room {} - watching",
+    room_playing: "Room {} - playing {}",
+    offer_draw: "Offer Draw",
+    resign: "Resign",
+    leave: "Leave",
+    left_online: "Left the online game",
+    endgame_win: "Endgame: {} mates in {} plies",
+    endgame_mated: "Endgame: {} is mated",
+    endgame_loss: "Endgame: {} is mated in {} plies",
+    endgame_draw: "Endgame: draw with best play",
+    move_label: "Move:",
+    move_hint: "h2e2 or C2.5",
+
+    board_theme: "Board theme",
+    flip_board: "Flip board (Black at the bottom)",
+    piece_sets_dir: "Piece sets directory:",
+    piece_set: "Piece set",
+    bundled: "Bundled",
+    tablebase_dir: "Endgame tablebase directory:",
+    language: "Language",
+    character_pieces: "Draw pieces as traditional characters",
+    font_path: "Chinese font file (empty to search the system):",
+    no_cjk_font: "No Chinese font found, so Chinese text cannot be shown.",
+
+    handicap_preset: "Handicap preset",
+    handicap: "Handicap",
+    given_by: "Given by:",
+    custom_setup: "Custom setup",
+    fen: "FEN:",
+    load_fen_file: "Or load FEN from file:",
+    start: "Start",
+
+    database: "Database:",
+    import_pgn: "Import PGN:",
+    import: "Import",
+    imported: "Imported {} games, skipped {}",
+    player: "Player:",
+    result: "Result:",
+    any: "Any",
+    opening: "Opening:",
+    opening_hint: "name or first moves, e.g. h2e2",
+    position_fen: "Position (FEN):",
+    current: "Current",
+    search: "Search",
+    games_found: "{} games found",
+    continuations: "Moves played from this position (Red wins / draws / Black wins):",
+    games_count: "{} games",
+    game_columns: ["Red", "Black", "Result", "Opening", "Next"],
+    reached_at: "{} (ply {})",
+    game_ended: "game ended",
+    load: "Load",
+    loaded: "Loaded {} - {}",
+
+    server: "Server:",
+    name: "Name:",
+    room: "Room:",
+    room_hint: "empty for a new room",
+    watch_only: "Watch only",
+    connect: "Connect",
+    connecting: "Connecting to {}",
+    disconnected: "Disconnected: {}",
+    joined: "Joined room {} as {}",
+    seated: "{} plays {}",
+    left_game: "{} left the game",
+    draw_offered: "{} offers a draw",
+    game_over: "Game over: {} by {}",
+    refused: "Server refused: {}",
+    bad_server_move: "the server sent an invalid move {}",
+    end_reasons: ["checkmate", "stalemate", "resignation", "agreement"],
+
+    selection_cancelled: "Selection cancelled",
+    selected: "Selected {}",
+    square_empty: "{}: empty",
+    square_piece: "{}: {} {}",
+    illegal_move: "Illegal move: {}",
+    moved: "{} {} {} to {} ({}){}",
+    captures: ", captures {}",
+};
+
+pub static SIMPLIFIED_CHINESE: Strings = Strings {
+    title: "中国象棋",
+    players: ["红方", "黑方"],
+    spectator: "观众",
+    piece_types: ["将", "士", "象", "马", "车", "炮", "兵"],
+    themes: ["木纹", "深色", "翡翠", "纸张"],
+    handicaps: [
+        "不让子",
+        "让单马",
+        "让单车",
+        "让双马",
+        "让车马",
+        "让双车",
+        "让九子",
+    ],
+
+    game_record: "棋谱",
+    copy_record: "复制棋谱",
+    move_status: "第 {} 回合 - 轮到{}",
+    settings: "设置",
+    new_game: "新对局",
+    play_online: "联机对弈",
+    game_database: "棋谱库",
+    room_watching: "房间 {} - 观战中",
+    room_playing: "房间 {} - 执{}",
+    offer_draw: "提和",
+    resign: "认输",
+    leave: "离开",
+    left_online: "已离开联机对局",
+    endgame_win: "残局：{}可在 {} 步内将死对方",
+    endgame_mated: "残局：{}已被将死",
+    endgame_loss: "残局：{}将在 {} 步内被将死",
+    endgame_draw: "残局：双方正确应对为和棋",
+    move_label: "着法：",
+    move_hint: "h2e2 或 C2.5",
+
+    board_theme: "棋盘主题",
+    flip_board: "翻转棋盘（黑方在下）",
+    piece_sets_dir: "棋子图案目录：",
+    piece_set: "棋子图案",
+    bundled: "内置",
+    tablebase_dir: "残局库目录：",
+    language: "语言",
+    character_pieces: "以传统汉字显示棋子",
+    font_path: "中文字体文件（留空则自动查找）：",
+    no_cjk_font: "未找到中文字体，无法显示中文。",
+
+    handicap_preset: "让子开局",
+    handicap: "让子",
+    given_by: "让子方：",
+    custom_setup: "自定义局面",
+    fen: "FEN：",
+    load_fen_file: "或从文件读取 FEN：",
+    start: "开始",
+
+    database: "数据库：",
+    import_pgn: "导入 PGN：",
+    import: "导入",
+    imported: "已导入 {} 局，跳过 {} 局",
+    player: "棋手：",
+    result: "结果：",
+    any: "全部",
+    opening: "开局：",
+    opening_hint: "名称或开局着法，如 h2e2",
+    position_fen: "局面（FEN）：",
+    current: "当前局面",
+    search: "搜索",
+    games_found: "找到 {} 局",
+    continuations: "此局面下的着法（红胜 / 和 / 黑胜）：",
+    games_count: "{} 局",
+    game_columns: ["红方", "黑方", "结果", "开局", "后续着法"],
+    reached_at: "{}（第 {} 步）",
+    game_ended: "对局结束",
+    load: "载入",
+    loaded: "已载入 {} - {}",
+
+    server: "服务器：",
+    name: "昵称：",
+    room: "房间：",
+    room_hint: "留空以新建房间",
+    watch_only: "仅观战",
+    connect: "连接",
+    connecting: "正在连接 {}",
+    disconnected: "连接已断开：{}",
+    joined: "已加入房间 {}，身份：{}",
+    seated: "{} 执{}",
+    left_game: "{}已离开对局",
+    draw_offered: "{}提议和棋",
+    game_over: "对局结束：{}（{}）",
+    refused: "服务器拒绝：{}",
+    bad_server_move: "服务器发送了无效着法 {}",
+    end_reasons: ["将死", "困毙", "认输", "协议和棋"],
+
+    selection_cancelled: "已取消选择",
+    selected: "已选择 {}",
+    square_empty: "{}：空",
+    square_piece: "{}：{}{}",
+    illegal_move: "着法不合规则：{}",
+    moved: "{}{} 从 {} 到 {}（{}）{}",
+    captures: "，吃{}",
+};
+
+pub static TRADITIONAL_CHINESE: Strings = Strings {
+    title: "中國象棋",
+    players: ["紅方", "黑方"],
+    spectator: "觀眾",
+    piece_types: ["將", "士", "象", "馬", "車", "炮", "兵"],
+    themes: ["木紋", "深色", "翡翠", "紙張"],
+    handicaps: [
+        "不讓子",
+        "讓單馬",
+        "讓單車",
+        "讓雙馬",
+        "讓車馬",
+        "讓雙車",
+        "讓九子",
+    ],
+
+    game_record: "棋譜",
+    copy_record: "複製棋譜",
+    move_status: "第 {} 回合 - 輪到{}",
+    settings: "設定",
+    new_game: "新對局",
+    play_online: "連線對弈",
+    game_database: "棋譜庫",
+    room_watching: "房間 {} - 觀戰中",
+    room_playing: "房間 {} - 執{}",
+    offer_draw: "提和",
+    resign: "認輸",
+    leave: "離開",
+    left_online: "已離開連線對局",
+    endgame_win: "殘局：{}可在 {} 步內將死對方",
+    endgame_mated: "殘局：{}已被將死",
+    endgame_loss: "殘局：{}將在 {} 步內被將死",
+    endgame_draw: "殘局：雙方正確應對為和棋",
+    move_label: "著法：",
+    move_hint: "h2e2 或 C2.5",
+
+    board_theme: "棋盤主題",
+    flip_board: "翻轉棋盤（黑方在下）",
+    piece_sets_dir: "棋子圖案目錄：",
+    piece_set: "棋子圖案",
+    bundled: "內建",
+    tablebase_dir: "殘局庫目錄：",
+    language: "語言",
+    character_pieces: "以傳統漢字顯示棋子",
+    font_path: "中文字型檔（留空則自動尋找）：",
+    no_cjk_font: "未找到中文字型，無法顯示中文。",
+
+    handicap_preset: "讓子開局",
+    handicap: "讓子",
+    given_by: "讓子方：",
+    custom_setup: "自訂局面",
+    fen: "FEN：",
+    load_fen_file: "或從檔案讀取 FEN：",
+    start: "開始",
+
+    database: "資料庫：",
+    import_pgn: "匯入 PGN：",
+    import: "匯入",
+    imported: "已匯入 {} 局，略過 {} 局",
+    player: "棋手：",
+    result: "結果：",
+    any: "全部",
+    opening: "開局：",
+    opening_hint: "名稱或開局著法，如 h2e2",
+    position_fen: "局面（FEN）：",
+    current: "目前局面",
+    search: "搜尋",
+    games_found: "找到 {} 局",
+    continuations: "此局面下的著法（紅勝 / 和 / 黑勝）：",
+    games_count: "{} 局",
+    game_columns: ["紅方", "黑方", "結果", "開局", "後續著法"],
+    reached_at: "{}（第 {} 步）",
+    game_ended: "對局結束",
+    load: "載入",
+    loaded: "已載入 {} - {}",
+
+    server: "伺服器：",
+    name: "暱稱：",
+    room: "房間：",
+    room_hint: "留空以新建房間",
+    watch_only: "僅觀戰",
+    connect: "連線",
+    connecting: "正在連線 {}",
+    disconnected: "連線已中斷：{}",
+    joined: "已加入房間 {}，身分：{}",
+    seated: "{} 執{}",
+    left_game: "{}已離開對局",
+    draw_offered: "{}提議和棋",
+    game_over: "對局結束：{}（{}）",
+    refused: "伺服器拒絕：{}",
+    bad_server_move: "伺服器傳送了無效著法 {}",
+    end_reasons: ["將死", "困斃", "認輸", "協議和棋"],
+
+    selection_cancelled: "已取消選擇",
+    selected: "已選擇 {}",
+    square_empty: "{}：空",
+    square_piece: "{}：{}{}",
+    illegal_move: "著法不合規則：{}",
+    moved: "{}{} 從 {} 到 {}（{}）{}",
+    captures: "，吃{}",
+};
+
+/// Fonts with Chinese glyphs found on common Linux, macOS and Windows installs.
+const CJK_FONT_CANDIDATES: [&str; 10] = [
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/arphic/uming.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/STHeiti Light.ttc",
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+];
+
+/// Reads `custom` if set, otherwise the first Chinese font installed in a usual place.
+pub fn find_cjk_font(custom: &str) -> Option<(PathBuf, Vec<u8>)> {
+    let custom = custom.trim();
+    let candidates: Vec<&Path> = if custom.is_empty() {
+        CJK_FONT_CANDIDATES.iter().map(Path::new).collect()
+    } else {
+        vec![Path::new(custom)]
+    };
+    candidates
+        .into_iter()
+        .find_map(|path| match std::fs::read(path) {
+            Ok(bytes) => Some((path.to_path_buf(), bytes)),
+            Err(e) => {
+                log::debug!("No font at {:?}: {}", path, e);
+                None
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill(ENGLISH.move_status, &[&3, &"Red"]),
+            "Move 3 - Current player: Red"
+        );
+        assert_eq!(
+            fill(
+                TRADITIONAL_CHINESE.move_status,
+                &[&3, &TRADITIONAL_CHINESE.player(Player::Black)]
+            ),
+            "第 3 回合 - 輪到黑方"
+        );
+        assert_eq!(fill("{} and {}", &[&1]), "1 and ");
+    }
+
+    #[test]
+    fn test_names_follow_enum_order() {
+        for language in Language::ALL {
+            let strings = language.strings();
+            assert_eq!(strings.player(Player::Black), strings.players[1]);
+            assert_eq!(
+                strings.piece_type(PieceType::Soldier),
+                strings.piece_types[6]
+            );
+        }
+        assert_eq!(ENGLISH.theme(BoardTheme::Paper), BoardTheme::Paper.name());
+        for handicap in Handicap::ALL {
+            assert_eq!(ENGLISH.handicap(handicap), handicap.name());
+        }
+        for piece_type in [PieceType::General, PieceType::Horse, PieceType::Cannon] {
+            assert_eq!(ENGLISH.piece_type(piece_type), piece_type.name());
+        }
+    }
+}
@@ -0,0 +1,13 @@
+pub mod assets;
+pub mod board;
+pub mod client;
+pub mod database;
+pub mod i18n;
+pub mod notation;
+pub mod protocol;
+pub mod record;
+pub mod rules;
+pub mod server;
+pub mod settings;
+pub mod setup;
+pub mod tablebase;
@@ -1,12 +1,50 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chinese_chess::board::{Board, PieceType, Player, Position, COLS, ROWS, START_FEN};
+use chinese_chess::client::{Connection, Event};
+use chinese_chess::database::{Continuation, GameDatabase, GameQuery, GameSummary};
+use chinese_chess::i18n::{self, fill, Strings};
+use chinese_chess::protocol::{ClientMessage, Seat, ServerMessage};
+use chinese_chess::record::{self, GameRecord, Move};
+use chinese_chess::settings::{BoardTheme, Settings};
+use chinese_chess::setup::{Handicap, Setup};
+use chinese_chess::tablebase::{Signature, Tablebase, Verdict};
+use chinese_chess::{assets, notation, rules};
+use clap::Parser;
+use cmd::{Cli, Commands};
 use eframe::egui;
-use egui_extras::image::load_svg_bytes;
+use egui::SizeHint;
+use egui_extras::image::load_svg_bytes_with_size;
+use log::LevelFilter;
 use resvg::usvg;
+use simple_logger::SimpleLogger;
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::env;
-use std::path::PathBuf;
+use std::path::Path;
+use std::sync::Arc;
+
+mod cmd;
 
 fn main() -> Result<(), eframe::Error> {
+    SimpleLogger::new()
+        .with_level(LevelFilter::Info)
+        .with_colors(true)
+        .env()
+        .init()
+        .expect("logger is only initialized once");
+
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Commands::Tablebase { dir, signatures }) => {
+            generate_tablebase(&dir, &signatures);
+            return Ok(());
+        }
+        Some(Commands::Import { database, files }) => {
+            import_games(&database, &files);
+            return Ok(());
+        }
+        None => {}
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([800.0, 600.0]),
         ..Default::default()
@@ -17,194 +55,254 @@ fn main() -> Result<(), eframe::Error> {
         options,
         Box::new(|cc| {
             let mut app = ChineseChessApp::default();
+            if let Some(settings) = cc
+                .storage
+                .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            {
+                app.settings = settings;
+            }
+            app.tablebase = Tablebase::new(&app.settings.tablebase_dir);
+            app.update_endgame_verdict();
+            app.install_fonts(&cc.egui_ctx);
+            let title = app.text().title.to_string();
+            cc.egui_ctx
+                .send_viewport_cmd(egui::ViewportCommand::Title(title));
             app.load_textures(&cc.egui_ctx);
             Ok(Box::new(app))
         }),
     )
 }
 
+fn generate_tablebase(dir: &str, signatures: &[String]) {
+    let mut tablebase = Tablebase::new(dir);
+    for name in signatures {
+        let result = Signature::parse(name).and_then(|signature| tablebase.generate(&signature));
+        if let Err(e) = result {
+            log::error!("Failed to generate {}: {:#}", name, e);
+        }
+    }
+}
+
+fn import_games(path: &str, files: &[String]) {
+    let mut database = match GameDatabase::open(path) {
+        Ok(database) => database,
+        Err(e) => {
+            log::error!("{:#}", e);
+            return;
+        }
+    };
+    for file in files {
+        let result = std::fs::read_to_string(file)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| database.import_pgn(&text));
+        match result {
+            Ok(summary) => {
+                for failure in &summary.failed {
+                    log::warn!("{}: {}", file, failure);
+                }
+                log::info!("Imported {} games from {}", summary.imported, file);
+            }
+            Err(e) => log::error!("Failed to import {}: {:#}", file, e),
+        }
+    }
+}
+
 struct ChineseChessApp {
-    board: [[Option<Piece>; 9]; 10],
+    board: Board,
     selected_piece: Option<(usize, usize)>,
     current_player: Player,
     textures: HashMap<String, egui::TextureHandle>,
-    dark_mode: bool,
+    piece_svgs: HashMap<String, Cow<'static, [u8]>>,
+    texture_size: u32,
+    settings: Settings,
+    settings_open: bool,
+    record: GameRecord,
+    new_game_dialog: Option<NewGameDialog>,
+    cursor: (usize, usize),
+    move_input: String,
+    move_input_error: Option<String>,
+    announcement: String,
+    tablebase: Tablebase,
+    endgame_verdict: Option<Verdict>,
+    online: Option<OnlineGame>,
+    connect_dialog: Option<ConnectDialog>,
+    database_panel: Option<DatabasePanel>,
+    /// Whether a font with Chinese glyphs was found and installed.
+    cjk_font: bool,
 }
 
-#[derive(Clone, Copy, PartialEq, Default)]
-enum Player {
-    #[default]
-    Red,
-    Black,
+/// A game played on a server, which echoes every accepted move back to all players.
+struct OnlineGame {
+    connection: Connection,
+    room: String,
+    seat: Seat,
 }
 
-#[derive(Clone, Copy, PartialEq)]
-enum PieceType {
-    General,
-    Advisor,
-    Elephant,
-    Horse,
-    Chariot,
-    Cannon,
-    Soldier,
+#[derive(Default)]
+struct ConnectDialog {
+    room: String,
+    watch: bool,
 }
 
-#[derive(Clone, Copy)]
-struct Piece {
-    piece_type: PieceType,
-    player: Player,
+const SEARCH_LIMIT: usize = 200;
+
+#[derive(Default)]
+struct DatabasePanel {
+    /// The open archive and the path it was opened from.
+    database: Option<(String, GameDatabase)>,
+    import_path: String,
+    query: GameQuery,
+    fen: String,
+    games: Vec<GameSummary>,
+    continuations: Vec<Continuation>,
+    status: Option<String>,
 }
 
-impl Default for ChineseChessApp {
+impl DatabasePanel {
+    fn database(&mut self, path: &str) -> Result<&mut GameDatabase> {
+        let path = path.trim();
+        if self.database.as_ref().is_none_or(|(open, _)| open != path) {
+            self.database = Some((path.to_string(), GameDatabase::open(path)?));
+        }
+        Ok(&mut self.database.as_mut().unwrap().1)
+    }
+
+    fn import(&mut self, path: &str, t: &Strings) -> Result<String> {
+        let file = self.import_path.trim().to_string();
+        let text =
+            std::fs::read_to_string(&file).with_context(|| format!("cannot read {}", file))?;
+        let summary = self.database(path)?.import_pgn(&text)?;
+        for failure in &summary.failed {
+            log::warn!("{}: {}", file, failure);
+        }
+        Ok(fill(
+            t.imported,
+            &[&summary.imported, &summary.failed.len()],
+        ))
+    }
+
+    fn search(&mut self, path: &str, t: &Strings) -> Result<String> {
+        self.query.position = match self.fen.trim() {
+            "" => None,
+            fen => Some(Position::from_fen(fen)?),
+        };
+        let query = self.query.clone();
+        let database = self.database(path)?;
+        let games = database.search(&query, SEARCH_LIMIT)?;
+        let continuations = match &query.position {
+            Some(position) => database.continuations(position)?,
+            None => Vec::new(),
+        };
+        self.games = games;
+        self.continuations = continuations;
+        Ok(fill(t.games_found, &[&self.games.len()]))
+    }
+}
+
+struct NewGameDialog {
+    handicap: Handicap,
+    giver: Player,
+    custom: bool,
+    fen: String,
+    path: String,
+    error: Option<String>,
+}
+
+impl Default for NewGameDialog {
     fn default() -> Self {
-        let mut board = [[None; 9]; 10];
+        Self {
+            handicap: Handicap::None,
+            giver: Player::Red,
+            custom: false,
+            fen: START_FEN.to_string(),
+            path: String::new(),
+            error: None,
+        }
+    }
+}
 
-        // Initialize red pieces (bottom)
-        board[9][0] = Some(Piece {
-            piece_type: PieceType::Chariot,
-            player: Player::Red,
-        });
-        board[9][1] = Some(Piece {
-            piece_type: PieceType::Horse,
-            player: Player::Red,
-        });
-        board[9][2] = Some(Piece {
-            piece_type: PieceType::Elephant,
-            player: Player::Red,
-        });
-        board[9][3] = Some(Piece {
-            piece_type: PieceType::Advisor,
-            player: Player::Red,
-        });
-        board[9][4] = Some(Piece {
-            piece_type: PieceType::General,
-            player: Player::Red,
-        });
-        board[9][5] = Some(Piece {
-            piece_type: PieceType::Advisor,
-            player: Player::Red,
-        });
-        board[9][6] = Some(Piece {
-            piece_type: PieceType::Elephant,
-            player: Player::Red,
-        });
-        board[9][7] = Some(Piece {
-            piece_type: PieceType::Horse,
-            player: Player::Red,
-        });
-        board[9][8] = Some(Piece {
-            piece_type: PieceType::Chariot,
-            player: Player::Red,
-        });
-        board[7][1] = Some(Piece {
-            piece_type: PieceType::Cannon,
-            player: Player::Red,
-        });
-        board[7][7] = Some(Piece {
-            piece_type: PieceType::Cannon,
-            player: Player::Red,
-        });
-        board[6][0] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Red,
-        });
-        board[6][2] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Red,
-        });
-        board[6][4] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Red,
-        });
-        board[6][6] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Red,
-        });
-        board[6][8] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Red,
-        });
+impl NewGameDialog {
+    fn setup(&self) -> Result<Setup> {
+        if !self.custom {
+            return Ok(Setup::Handicap {
+                handicap: self.handicap,
+                giver: self.giver,
+            });
+        }
 
-        // Initialize black pieces (top)
-        board[0][0] = Some(Piece {
-            piece_type: PieceType::Chariot,
-            player: Player::Black,
-        });
-        board[0][1] = Some(Piece {
-            piece_type: PieceType::Horse,
-            player: Player::Black,
-        });
-        board[0][2] = Some(Piece {
-            piece_type: PieceType::Elephant,
-            player: Player::Black,
-        });
-        board[0][3] = Some(Piece {
-            piece_type: PieceType::Advisor,
-            player: Player::Black,
-        });
-        board[0][4] = Some(Piece {
-            piece_type: PieceType::General,
-            player: Player::Black,
-        });
-        board[0][5] = Some(Piece {
-            piece_type: PieceType::Advisor,
-            player: Player::Black,
-        });
-        board[0][6] = Some(Piece {
-            piece_type: PieceType::Elephant,
-            player: Player::Black,
-        });
-        board[0][7] = Some(Piece {
-            piece_type: PieceType::Horse,
-            player: Player::Black,
-        });
-        board[0][8] = Some(Piece {
-            piece_type: PieceType::Chariot,
-            player: Player::Black,
-        });
-        board[2][1] = Some(Piece {
-            piece_type: PieceType::Cannon,
-            player: Player::Black,
-        });
-        board[2][7] = Some(Piece {
-            piece_type: PieceType::Cannon,
-            player: Player::Black,
-        });
-        board[3][0] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Black,
-        });
-        board[3][2] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Black,
-        });
-        board[3][4] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Black,
-        });
-        board[3][6] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Black,
-        });
-        board[3][8] = Some(Piece {
-            piece_type: PieceType::Soldier,
-            player: Player::Black,
-        });
+        if !self.path.trim().is_empty() {
+            return Setup::load_custom(Path::new(self.path.trim()));
+        }
+
+        let setup = Setup::Custom {
+            fen: self.fen.trim().to_string(),
+        };
+        setup.position()?;
+        Ok(setup)
+    }
+}
+
+impl Default for ChineseChessApp {
+    fn default() -> Self {
+        let setup = Setup::Standard;
+        let position = Position::default();
 
         Self {
-            board,
+            board: position.board,
             selected_piece: None,
-            current_player: Player::default(),
+            current_player: position.side_to_move,
             textures: HashMap::new(),
-            dark_mode: false,
+            piece_svgs: HashMap::new(),
+            texture_size: 100,
+            settings: Settings::default(),
+            settings_open: false,
+            record: GameRecord::new(&setup, &position),
+            new_game_dialog: None,
+            cursor: (ROWS - 1, 4),
+            move_input: String::new(),
+            move_input_error: None,
+            announcement: String::new(),
+            tablebase: Tablebase::new(""),
+            endgame_verdict: None,
+            online: None,
+            connect_dialog: None,
+            database_panel: None,
+            cjk_font: false,
         }
     }
 }
 
 impl eframe::App for ChineseChessApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_online();
+        let t = self.text();
+
+        egui::SidePanel::right("game_record").show(ctx, |ui| {
+            ui.heading(t.game_record);
+            egui::Grid::new("record_tags").show(ui, |ui| {
+                for (key, value) in &self.record.tags {
+                    ui.label(key);
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let pgn = self.record.to_pgn();
+                let moves = pgn.lines().last().unwrap_or_default();
+                ui.label(moves);
+            });
+
+            if ui.button(t.copy_record).clicked() {
+                ctx.copy_text(self.record.to_pgn());
+            }
+        });
+
+        self.handle_keys(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Chinese Chess Game");
+            ui.heading(t.title);
 
             // Draw the chess board
             let board_size = 400.0;
@@ -214,13 +312,15 @@ impl eframe::App for ChineseChessApp {
                 egui::Sense::click(),
             );
 
-            // Draw board background with toggleable color
-            let bg_color = if self.dark_mode {
-                egui::Color32::from_rgb(50, 50, 50) // Dark background
-            } else {
-                egui::Color32::from_rgb(210, 180, 140) // Light background
-            };
-            painter.rect_filled(response.rect, 0.0, bg_color);
+            // Re-rasterize the pieces when their on-screen pixel size changes
+            let piece_pixels = (cell_size * 0.8 * ctx.pixels_per_point()).round() as u32;
+            if piece_pixels != self.texture_size {
+                self.rasterize_textures(ctx, piece_pixels);
+            }
+
+            // Draw board background in the selected theme
+            let theme = self.settings.theme;
+            painter.rect_filled(response.rect, 0.0, theme.background());
 
             // Draw grid lines
             for i in 0..10 {
@@ -235,7 +335,7 @@ impl eframe::App for ChineseChessApp {
                             response.rect.top() + i as f32 * cell_size,
                         ),
                     ],
-                    egui::Stroke::new(2.0, egui::Color32::BLACK),
+                    egui::Stroke::new(2.0, theme.lines()),
                 );
             }
             for j in 0..9 {
@@ -250,7 +350,7 @@ impl eframe::App for ChineseChessApp {
                             response.rect.bottom(),
                         ),
                     ],
-                    egui::Stroke::new(2.0, egui::Color32::BLACK),
+                    egui::Stroke::new(2.0, theme.lines()),
                 );
             }
 
@@ -258,14 +358,36 @@ impl eframe::App for ChineseChessApp {
             for row in 0..10 {
                 for col in 0..9 {
                     if let Some(piece) = self.board[row][col] {
-                        let x = response.rect.left() + col as f32 * cell_size + cell_size / 2.0;
-                        let y = response.rect.top() + row as f32 * cell_size + cell_size / 2.0;
+                        let (view_row, view_col) = self.settings.view_square((row, col));
+                        let x =
+                            response.rect.left() + view_col as f32 * cell_size + cell_size / 2.0;
+                        let y = response.rect.top() + view_row as f32 * cell_size + cell_size / 2.0;
 
                         let color = match piece.player {
                             Player::Red => egui::Color32::RED,
                             Player::Black => egui::Color32::BLACK,
                         };
 
+                        // Traditional characters, drawn from the Chinese font
+                        if self.settings.character_pieces {
+                            let center = egui::pos2(x, y);
+                            let face = egui::Color32::from_rgb(245, 222, 179);
+                            painter.circle_filled(center, cell_size * 0.45, face);
+                            painter.circle_stroke(
+                                center,
+                                cell_size * 0.38,
+                                egui::Stroke::new(2.0, color),
+                            );
+                            painter.text(
+                                center,
+                                egui::Align2::CENTER_CENTER,
+                                piece.character(),
+                                egui::FontId::proportional(cell_size * 0.5),
+                                color,
+                            );
+                            continue;
+                        }
+
                         // Draw colored circle background first
                         painter.circle_filled(egui::pos2(x, y), cell_size * 0.45, color);
 
@@ -288,24 +410,8 @@ impl eframe::App for ChineseChessApp {
                         };
 
                         if let Some(texture) = self.textures.get(texture_name) {
-                            println!(
-                                "Drawing texture: {} at position ({}, {}), texture size: {:?}",
-                                texture_name,
-                                x,
-                                y,
-                                texture.size()
-                            );
                             let size = egui::vec2(cell_size * 0.8, cell_size * 0.8);
                             let rect = egui::Rect::from_center_size(egui::pos2(x, y), size);
-                            println!("Drawing rect: {:?}", rect);
-
-                            // Draw a debug rectangle around the image area
-                            painter.rect_stroke(
-                                rect,
-                                0.0,
-                                egui::Stroke::new(1.0, egui::Color32::GREEN),
-                                egui::StrokeKind::Inside,
-                            );
 
                             painter.image(
                                 texture.id(),
@@ -317,12 +423,38 @@ impl eframe::App for ChineseChessApp {
                                 egui::Color32::WHITE,
                             );
                         } else {
-                            println!("Texture not found: {}", texture_name);
+                            log::error!("Texture not found: {}", texture_name);
                         }
                     }
                 }
             }
 
+            // Highlight the selected piece and the keyboard cursor
+            let cell_rect = |square: (usize, usize)| {
+                let (row, col) = self.settings.view_square(square);
+                egui::Rect::from_min_size(
+                    response.rect.min + egui::vec2(col as f32, row as f32) * cell_size,
+                    egui::vec2(cell_size, cell_size),
+                )
+            };
+            if let Some(selected) = self.selected_piece {
+                painter.rect_stroke(
+                    cell_rect(selected),
+                    0.0,
+                    egui::Stroke::new(3.0, egui::Color32::GOLD),
+                    egui::StrokeKind::Inside,
+                );
+            }
+            painter.rect_stroke(
+                cell_rect(self.cursor),
+                0.0,
+                egui::Stroke::new(2.0, egui::Color32::LIGHT_BLUE),
+                egui::StrokeKind::Inside,
+            );
+            response.widget_info(|| {
+                egui::WidgetInfo::labeled(egui::WidgetType::Other, true, self.describe_cursor())
+            });
+
             // Handle click events
             if response.clicked() {
                 if let Some(pos) = response.interact_pointer_pos() {
@@ -330,6 +462,8 @@ impl eframe::App for ChineseChessApp {
                     let row = ((pos.y - response.rect.top()) / cell_size) as usize;
 
                     if row < 10 && col < 9 {
+                        let (row, col) = self.settings.view_square((row, col));
+                        self.cursor = (row, col);
                         self.handle_click(row, col);
                     }
                 }
@@ -337,77 +471,142 @@ impl eframe::App for ChineseChessApp {
 
             // Display current player and background toggle
             ui.horizontal(|ui| {
-                ui.label(format!(
-                    "Current player: {}",
-                    match self.current_player {
-                        Player::Red => "Red",
-                        Player::Black => "Black",
-                    }
+                ui.label(fill(
+                    t.move_status,
+                    &[&self.record.move_number(), &t.player(self.current_player)],
                 ));
 
-                // Background color toggle button
+                if ui.button(format!("⚙ {}", t.settings)).clicked() {
+                    self.settings_open = !self.settings_open;
+                }
+
+                let offline = self.online.is_none();
                 if ui
-                    .button(if self.dark_mode {
-                        "☀️ Light Mode"
-                    } else {
-                        "🌙 Dark Mode"
-                    })
+                    .add_enabled(offline, egui::Button::new(t.new_game))
                     .clicked()
                 {
-                    self.dark_mode = !self.dark_mode;
+                    self.new_game_dialog = Some(NewGameDialog::default());
+                }
+
+                if offline && ui.button(t.play_online).clicked() {
+                    self.connect_dialog = Some(ConnectDialog::default());
+                }
+
+                if ui.button(t.game_database).clicked() {
+                    self.database_panel = Some(DatabasePanel::default());
+                }
+            });
+
+            if let Some(online) = &self.online {
+                let mut leave = false;
+                ui.horizontal(|ui| {
+                    ui.label(match online.seat {
+                        Seat::Spectator => fill(t.room_watching, &[&online.room]),
+                        seat => fill(t.room_playing, &[&online.room, &t.seat(seat)]),
+                    });
+                    if online.seat != Seat::Spectator {
+                        if ui.button(t.offer_draw).clicked() {
+                            online.connection.send(ClientMessage::OfferDraw);
+                        }
+                        if ui.button(t.resign).clicked() {
+                            online.connection.send(ClientMessage::Resign);
+                        }
+                    }
+                    leave = ui.button(t.leave).clicked();
+                });
+                if leave {
+                    self.online = None;
+                    self.announcement = t.left_online.to_string();
                 }
+            }
+
+            if let Some(verdict) = self.endgame_verdict {
+                let side = t.player(self.current_player);
+                ui.label(match verdict {
+                    Verdict::Win(plies) => fill(t.endgame_win, &[&side, &plies]),
+                    Verdict::Loss(0) => fill(t.endgame_mated, &[&side]),
+                    Verdict::Loss(plies) => fill(t.endgame_loss, &[&side, &plies]),
+                    Verdict::Draw => t.endgame_draw.to_string(),
+                });
+            }
+
+            // Typed move entry in ICCS or WXF notation
+            ui.horizontal(|ui| {
+                ui.label(t.move_label);
+                let input =
+                    ui.add(egui::TextEdit::singleline(&mut self.move_input).hint_text(t.move_hint));
+                if input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    self.submit_move_input();
+                    input.request_focus();
+                }
+                if let Some(error) = &self.move_input_error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+            });
+
+            // Screen readers pick up changes to this live region
+            let announcement = ui.label(&self.announcement);
+            ctx.accesskit_node_builder(announcement.id, |node| {
+                node.set_live(egui::accesskit::Live::Polite);
             });
         });
+
+        self.show_new_game_dialog(ctx);
+        self.show_connect_dialog(ctx);
+        self.show_database(ctx);
+        self.show_settings(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, &self.settings);
     }
 }
 
 impl ChineseChessApp {
     fn load_textures(&mut self, ctx: &egui::Context) {
-        let piece_names = [
-            "red_general",
-            "red_advisor",
-            "red_elephant",
-            "red_horse",
-            "red_chariot",
-            "red_cannon",
-            "red_soldier",
-            "black_general",
-            "black_advisor",
-            "black_elephant",
-            "black_horse",
-            "black_chariot",
-            "black_cannon",
-            "black_soldier",
-        ];
-
-        for name in piece_names {
-            // Get the path to the assets directory relative to the executable
-            let mut svg_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-            svg_path.push("assets");
-            svg_path.push("images");
-            svg_path.push(format!("{}.svg", name));
-
-            // Create a simple test texture instead of loading SVG
-            // Try to load SVG file
-            if let Ok(svg_bytes) = std::fs::read(&svg_path) {
-                println!("Loading SVG file: {:?}", svg_path);
-                let options = usvg::Options::default();
-                match load_svg_bytes(&svg_bytes, &options) {
-                    Ok(image) => {
-                        let texture = ctx.load_texture(name, image, egui::TextureOptions::LINEAR);
-                        self.textures.insert(name.to_string(), texture);
-                        println!("Successfully loaded SVG texture for: {}", name);
-                    }
-                    Err(e) => {
-                        println!("Failed to load SVG for {}: {:?}", name, e);
-                        // Fallback to test texture
-                        self.create_test_texture(ctx, name);
-                    }
+        let piece_set = self.settings.piece_set_path();
+        self.piece_svgs.clear();
+        for name in assets::PIECE_NAMES {
+            match assets::piece_svg(name, piece_set.as_deref()) {
+                Some(svg) => {
+                    self.piece_svgs.insert(name.to_string(), svg);
                 }
-            } else {
-                println!("SVG file not found: {:?}, using test texture", svg_path);
+                None => log::warn!("No SVG found for {}, using test texture", name),
+            }
+        }
+
+        self.rasterize_textures(ctx, self.texture_size);
+    }
+
+    /// Renders the cached SVGs into textures `size` pixels wide, so pieces stay crisp at any
+    /// board size and DPI.
+    fn rasterize_textures(&mut self, ctx: &egui::Context, size: u32) {
+        log::debug!("Rasterizing piece textures at {}px", size);
+        self.texture_size = size;
+        let options = usvg::Options::default();
+        let size_hint = SizeHint::Size {
+            width: size,
+            height: size,
+            maintain_aspect_ratio: true,
+        };
+
+        for name in assets::PIECE_NAMES {
+            let Some(svg) = self.piece_svgs.get(name) else {
                 // Fallback to test texture
                 self.create_test_texture(ctx, name);
+                continue;
+            };
+
+            match load_svg_bytes_with_size(svg, size_hint, &options) {
+                Ok(image) => {
+                    let texture = ctx.load_texture(name, image, egui::TextureOptions::LINEAR);
+                    self.textures.insert(name.to_string(), texture);
+                }
+                Err(e) => {
+                    log::warn!("Failed to load SVG for {}: {}", name, e);
+                    // Fallback to test texture
+                    self.create_test_texture(ctx, name);
+                }
             }
         }
     }
@@ -441,26 +640,666 @@ impl ChineseChessApp {
 
         let texture = ctx.load_texture(name, image, egui::TextureOptions::LINEAR);
         self.textures.insert(name.to_string(), texture);
-        println!("Created test texture for: {}", name);
+        log::info!("Created test texture for: {}", name);
+    }
+
+    fn text(&self) -> &'static Strings {
+        self.settings.language.strings()
+    }
+
+    /// Adds the Chinese font as a fallback to egui's own fonts, which have no CJK glyphs.
+    fn install_fonts(&mut self, ctx: &egui::Context) {
+        let mut fonts = egui::FontDefinitions::default();
+        match i18n::find_cjk_font(&self.settings.font_path) {
+            Some((path, bytes)) => {
+                log::info!("Using {:?} for Chinese text", path);
+                fonts.font_data.insert(
+                    "cjk".to_string(),
+                    Arc::new(egui::FontData::from_owned(bytes)),
+                );
+                for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+                    fonts
+                        .families
+                        .entry(family)
+                        .or_default()
+                        .push("cjk".to_string());
+                }
+                self.cjk_font = true;
+            }
+            None => {
+                log::warn!("No Chinese font found, Chinese text will not render");
+                self.cjk_font = false;
+            }
+        }
+        ctx.set_fonts(fonts);
+    }
+
+    fn show_settings(&mut self, ctx: &egui::Context) {
+        let mut open = self.settings_open;
+        let before = self.settings.clone();
+        let t = self.text();
+        let cjk_font = self.cjk_font;
+        egui::Window::new(t.settings)
+            .id(egui::Id::new("settings"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::ComboBox::from_label(t.language)
+                    .selected_text(self.settings.language.name())
+                    .show_ui(ui, |ui| {
+                        for language in i18n::Language::ALL {
+                            ui.selectable_value(
+                                &mut self.settings.language,
+                                language,
+                                language.name(),
+                            );
+                        }
+                    });
+                ui.label(t.font_path);
+                ui.text_edit_singleline(&mut self.settings.font_path);
+                if !cjk_font {
+                    ui.colored_label(egui::Color32::RED, t.no_cjk_font);
+                }
+                ui.separator();
+
+                egui::ComboBox::from_label(t.board_theme)
+                    .selected_text(t.theme(self.settings.theme))
+                    .show_ui(ui, |ui| {
+                        for theme in BoardTheme::ALL {
+                            ui.selectable_value(&mut self.settings.theme, theme, t.theme(theme));
+                        }
+                    });
+
+                ui.checkbox(&mut self.settings.flipped, t.flip_board);
+                ui.checkbox(&mut self.settings.character_pieces, t.character_pieces);
+                ui.separator();
+
+                ui.label(t.piece_sets_dir);
+                ui.text_edit_singleline(&mut self.settings.piece_sets_dir);
+                let sets = self.settings.available_piece_sets();
+                egui::ComboBox::from_label(t.piece_set)
+                    .selected_text(self.settings.piece_set.as_deref().unwrap_or(t.bundled))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.settings.piece_set, None, t.bundled);
+                        for set in sets {
+                            let label = set.clone();
+                            ui.selectable_value(&mut self.settings.piece_set, Some(set), label);
+                        }
+                    });
+                ui.separator();
+
+                ui.label(t.tablebase_dir);
+                ui.text_edit_singleline(&mut self.settings.tablebase_dir);
+            });
+        self.settings_open = open;
+
+        if self.settings.language != before.language {
+            let title = self.text().title.to_string();
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+        }
+        if self.settings.font_path != before.font_path {
+            self.install_fonts(ctx);
+        }
+        if self.settings.piece_set != before.piece_set {
+            self.textures.clear();
+            self.load_textures(ctx);
+        }
+        if self.settings.tablebase_dir != before.tablebase_dir {
+            self.tablebase = Tablebase::new(&self.settings.tablebase_dir);
+            self.update_endgame_verdict();
+        }
+    }
+
+    fn start_game(&mut self, setup: &Setup) -> Result<()> {
+        let position = setup.position()?;
+        self.board = position.board;
+        self.current_player = position.side_to_move;
+        self.selected_piece = None;
+        self.record = GameRecord::new(setup, &position);
+        self.update_endgame_verdict();
+        Ok(())
+    }
+
+    fn update_endgame_verdict(&mut self) {
+        self.endgame_verdict = self.tablebase.probe(&self.board, self.current_player);
+    }
+
+    fn show_new_game_dialog(&mut self, ctx: &egui::Context) {
+        let t = self.text();
+        let Some(dialog) = &mut self.new_game_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut start = None;
+        egui::Window::new(t.new_game)
+            .id(egui::Id::new("new_game"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.radio_value(&mut dialog.custom, false, t.handicap_preset);
+                ui.add_enabled_ui(!dialog.custom, |ui| {
+                    egui::ComboBox::from_label(t.handicap)
+                        .selected_text(t.handicap(dialog.handicap))
+                        .show_ui(ui, |ui| {
+                            for handicap in Handicap::ALL {
+                                ui.selectable_value(
+                                    &mut dialog.handicap,
+                                    handicap,
+                                    t.handicap(handicap),
+                                );
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        ui.label(t.given_by);
+                        for player in [Player::Red, Player::Black] {
+                            ui.radio_value(&mut dialog.giver, player, t.player(player));
+                        }
+                    });
+                });
+
+                ui.radio_value(&mut dialog.custom, true, t.custom_setup);
+                ui.add_enabled_ui(dialog.custom, |ui| {
+                    ui.label(t.fen);
+                    ui.text_edit_singleline(&mut dialog.fen);
+                    ui.label(t.load_fen_file);
+                    ui.text_edit_singleline(&mut dialog.path);
+                });
+
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+
+                if ui.button(t.start).clicked() {
+                    match dialog.setup() {
+                        Ok(setup) => start = Some(setup),
+                        Err(e) => dialog.error = Some(format!("{:#}", e)),
+                    }
+                }
+            });
+
+        if let Some(setup) = start {
+            match self.start_game(&setup) {
+                Ok(()) => self.new_game_dialog = None,
+                Err(e) => {
+                    if let Some(dialog) = &mut self.new_game_dialog {
+                        dialog.error = Some(format!("{:#}", e));
+                    }
+                }
+            }
+        } else if !open {
+            self.new_game_dialog = None;
+        }
+    }
+
+    fn show_database(&mut self, ctx: &egui::Context) {
+        let t = self.text();
+        let Some(panel) = &mut self.database_panel else {
+            return;
+        };
+
+        let current = Position {
+            board: self.board,
+            side_to_move: self.current_player,
+            halfmove_clock: 0,
+            fullmove_number: self.record.move_number(),
+        };
+        let offline = self.online.is_none();
+        let mut open = true;
+        let mut load = None;
+        egui::Window::new(t.game_database)
+            .id(egui::Id::new("game_database"))
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("database_files").show(ui, |ui| {
+                    ui.label(t.database);
+                    ui.text_edit_singleline(&mut self.settings.database_path);
+                    ui.end_row();
+                    ui.label(t.import_pgn);
+                    ui.text_edit_singleline(&mut panel.import_path);
+                    if ui.button(t.import).clicked() {
+                        let status = panel.import(&self.settings.database_path, t);
+                        panel.status = Some(status.unwrap_or_else(|e| format!("{:#}", e)));
+                    }
+                    ui.end_row();
+                });
+                ui.separator();
+
+                egui::Grid::new("database_query").show(ui, |ui| {
+                    ui.label(t.player);
+                    ui.text_edit_singleline(&mut panel.query.player);
+                    ui.end_row();
+                    ui.label(t.result);
+                    egui::ComboBox::from_id_salt("database_result")
+                        .selected_text(match panel.query.result.as_str() {
+                            "" => t.any,
+                            result => result,
+                        })
+                        .show_ui(ui, |ui| {
+                            for (result, label) in [
+                                ("", t.any),
+                                ("1-0", "1-0"),
+                                ("1/2-1/2", "1/2-1/2"),
+                                ("0-1", "0-1"),
+                            ] {
+                                ui.selectable_value(
+                                    &mut panel.query.result,
+                                    result.to_string(),
+                                    label,
+                                );
+                            }
+                        });
+                    ui.end_row();
+                    ui.label(t.opening);
+                    ui.add(
+                        egui::TextEdit::singleline(&mut panel.query.opening)
+                            .hint_text(t.opening_hint),
+                    );
+                    ui.end_row();
+                    ui.label(t.position_fen);
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut panel.fen);
+                        if ui.button(t.current).clicked() {
+                            panel.fen = current.to_fen();
+                        }
+                    });
+                    ui.end_row();
+                });
+                if ui.button(t.search).clicked() {
+                    let status = panel.search(&self.settings.database_path, t);
+                    panel.status = Some(status.unwrap_or_else(|e| format!("{:#}", e)));
+                }
+                if let Some(status) = &panel.status {
+                    ui.label(status);
+                }
+
+                if let Some(position) = &panel.query.position {
+                    if !panel.continuations.is_empty() {
+                        ui.separator();
+                        ui.label(t.continuations);
+                        egui::Grid::new("database_continuations").show(ui, |ui| {
+                            for continuation in &panel.continuations {
+                                let wxf = notation::parse_iccs(&continuation.mv)
+                                    .and_then(|mv| notation::to_wxf(&position.board, mv));
+                                ui.label(wxf.unwrap_or_else(|| continuation.mv.clone()));
+                                ui.label(fill(t.games_count, &[&continuation.games]));
+                                ui.label(format!(
+                                    "{} / {} / {}",
+                                    continuation.red_wins,
+                                    continuation.draws,
+                                    continuation.black_wins
+                                ));
+                                ui.end_row();
+                            }
+                        });
+                    }
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("database_games")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for heading in t.game_columns {
+                                    ui.strong(heading);
+                                }
+                                ui.label("");
+                                ui.end_row();
+                                for game in &panel.games {
+                                    ui.label(&game.red);
+                                    ui.label(&game.black);
+                                    ui.label(&game.result);
+                                    ui.label(&game.opening);
+                                    ui.label(match &game.reached {
+                                        Some((ply, Some(next))) => {
+                                            fill(t.reached_at, &[next, &(ply + 1)])
+                                        }
+                                        Some((_, None)) => t.game_ended.to_string(),
+                                        None => String::new(),
+                                    });
+                                    if ui.add_enabled(offline, egui::Button::new(t.load)).clicked()
+                                    {
+                                        load = Some(game.id);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if let Some(id) = load {
+            let loaded = panel
+                .database(&self.settings.database_path)
+                .and_then(|database| database.load(id))
+                .and_then(|record| self.load_game(record));
+            if let (Err(e), Some(panel)) = (loaded, &mut self.database_panel) {
+                panel.status = Some(format!("{:#}", e));
+            }
+        } else if !open {
+            self.database_panel = None;
+        }
+    }
+
+    fn load_game(&mut self, record: GameRecord) -> Result<()> {
+        self.start_game(&record.setup())?;
+        for &mv in &record.moves {
+            self.apply_move(mv);
+        }
+        self.announcement = fill(
+            self.text().loaded,
+            &[
+                &record.tag("Red").unwrap_or("?"),
+                &record.tag("Black").unwrap_or("?"),
+            ],
+        );
+        self.record = record;
+        Ok(())
+    }
+
+    fn show_connect_dialog(&mut self, ctx: &egui::Context) {
+        let t = self.text();
+        let Some(dialog) = &mut self.connect_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut connect = false;
+        egui::Window::new(t.play_online)
+            .id(egui::Id::new("play_online"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("connect_fields").show(ui, |ui| {
+                    ui.label(t.server);
+                    ui.text_edit_singleline(&mut self.settings.server_address);
+                    ui.end_row();
+                    ui.label(t.name);
+                    ui.text_edit_singleline(&mut self.settings.player_name);
+                    ui.end_row();
+                    ui.label(t.room);
+                    ui.add(egui::TextEdit::singleline(&mut dialog.room).hint_text(t.room_hint));
+                    ui.end_row();
+                });
+                ui.checkbox(&mut dialog.watch, t.watch_only);
+                connect = ui.button(t.connect).clicked();
+            });
+
+        if connect {
+            let ctx = ctx.clone();
+            let connection = Connection::open(
+                &self.settings.server_address,
+                &dialog.room,
+                &self.settings.player_name,
+                dialog.watch,
+                move || ctx.request_repaint(),
+            );
+            self.online = Some(OnlineGame {
+                connection,
+                room: dialog.room.trim().to_string(),
+                seat: Seat::Spectator,
+            });
+            self.connect_dialog = None;
+            self.announcement = fill(t.connecting, &[&self.settings.server_address]);
+        } else if !open {
+            self.connect_dialog = None;
+        }
+    }
+
+    fn poll_online(&mut self) {
+        let Some(online) = &self.online else {
+            return;
+        };
+        for event in online.connection.poll() {
+            match event {
+                Event::Message(message) => {
+                    self.handle_server_message(message);
+                    if self.online.is_none() {
+                        return;
+                    }
+                }
+                Event::Disconnected(reason) => {
+                    self.disconnect(&reason);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn disconnect(&mut self, reason: &str) {
+        self.online = None;
+        self.announcement = fill(self.text().disconnected, &[&reason]);
+    }
+
+    fn handle_server_message(&mut self, message: ServerMessage) {
+        let t = self.text();
+        match message {
+            ServerMessage::Welcome {
+                room,
+                seat,
+                fen,
+                moves,
+                red,
+                black,
+                result,
+            } => {
+                let setup = if fen == START_FEN {
+                    Setup::Standard
+                } else {
+                    Setup::Custom { fen }
+                };
+                if let Err(e) = self.start_game(&setup) {
+                    log::error!("Server sent an unusable game: {:#}", e);
+                    return;
+                }
+                for mv in &moves {
+                    if !self.apply_server_move(mv) {
+                        return;
+                    }
+                }
+                self.record.set_tag("Red", red.as_deref().unwrap_or("?"));
+                self.record
+                    .set_tag("Black", black.as_deref().unwrap_or("?"));
+                if let Some(result) = result {
+                    self.record.set_tag("Result", &result);
+                }
+                self.announcement = fill(t.joined, &[&room, &t.seat(seat)]);
+                if let Some(online) = &mut self.online {
+                    online.room = room;
+                    online.seat = seat;
+                }
+            }
+            ServerMessage::Seated { seat, name } => {
+                let Some(player) = seat.player() else {
+                    return;
+                };
+                self.record
+                    .set_tag(player.name(), name.as_deref().unwrap_or("?"));
+                self.announcement = match name {
+                    Some(name) => fill(t.seated, &[&name, &t.player(player)]),
+                    None => fill(t.left_game, &[&t.player(player)]),
+                };
+            }
+            ServerMessage::Moved { mv } => {
+                self.apply_server_move(&mv);
+            }
+            ServerMessage::DrawOffered { by } => {
+                self.announcement = fill(t.draw_offered, &[&t.seat(by)]);
+            }
+            ServerMessage::GameOver { result, reason } => {
+                self.record.set_tag("Result", &result);
+                self.announcement = fill(t.game_over, &[&result, &t.end_reason(&reason)]);
+            }
+            ServerMessage::Rejected { reason } => {
+                self.announcement = fill(t.refused, &[&reason]);
+                self.move_input_error = Some(reason);
+            }
+        }
+    }
+
+    fn handle_keys(&mut self, ctx: &egui::Context) {
+        // Leave the keys to the move entry field while it has focus
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        // Arrow keys move the cursor as drawn, which is mirrored when the board is flipped
+        let (mut row, mut col) = self.settings.view_square(self.cursor);
+        let pressed = |key| ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, key));
+        if pressed(egui::Key::ArrowUp) {
+            row = row.saturating_sub(1);
+        }
+        if pressed(egui::Key::ArrowDown) {
+            row = (row + 1).min(ROWS - 1);
+        }
+        if pressed(egui::Key::ArrowLeft) {
+            col = col.saturating_sub(1);
+        }
+        if pressed(egui::Key::ArrowRight) {
+            col = (col + 1).min(COLS - 1);
+        }
+        let (row, col) = self.settings.view_square((row, col));
+        if (row, col) != self.cursor {
+            self.cursor = (row, col);
+            self.announcement = self.describe_cursor();
+        }
+
+        if pressed(egui::Key::Enter) {
+            self.handle_click(row, col);
+        }
+        if pressed(egui::Key::Escape) && self.selected_piece.take().is_some() {
+            self.announcement = self.text().selection_cancelled.to_string();
+        }
+    }
+
+    fn describe_cursor(&self) -> String {
+        let t = self.text();
+        let square = record::square_name(self.cursor);
+        match self.board[self.cursor.0][self.cursor.1] {
+            Some(piece) => fill(
+                t.square_piece,
+                &[
+                    &square,
+                    &t.player(piece.player),
+                    &t.piece_type(piece.piece_type),
+                ],
+            ),
+            None => fill(t.square_empty, &[&square]),
+        }
+    }
+
+    fn submit_move_input(&mut self) {
+        let mv = match notation::parse_move(&self.board, self.current_player, &self.move_input) {
+            Ok(mv) => mv,
+            Err(e) => {
+                self.move_input_error = Some(format!("{:#}", e));
+                return;
+            }
+        };
+
+        let selected = self.board[mv.from.0][mv.from.1]
+            .is_some_and(|piece| piece.player == self.current_player);
+        if selected && self.try_move(mv) {
+            self.move_input.clear();
+            self.move_input_error = None;
+        } else {
+            self.move_input_error =
+                Some(fill(self.text().illegal_move, &[&self.move_input.trim()]));
+        }
+    }
+
+    fn try_move(&mut self, mv: Move) -> bool {
+        let ((from_row, from_col), (to_row, to_col)) = (mv.from, mv.to);
+        if !self.can_move() || !self.is_valid_move(from_row, from_col, to_row, to_col) {
+            return false;
+        }
+
+        // Online, the move is only played once the server echoes it back
+        if let Some(online) = &self.online {
+            online
+                .connection
+                .send(ClientMessage::Move { mv: mv.iccs() });
+            self.selected_piece = None;
+            return true;
+        }
+        self.apply_move(mv);
+        true
+    }
+
+    /// Plays a move from the server, which only sends legal ones. A server that sends anything
+    /// else cannot be trusted to keep the game in step, so the connection is dropped and false
+    /// is returned.
+    fn apply_server_move(&mut self, text: &str) -> bool {
+        let reason = match notation::parse_iccs(text) {
+            Some(mv) if rules::is_legal(&self.board, self.current_player, mv) => {
+                self.apply_move(mv);
+                return true;
+            }
+            Some(_) => "illegal",
+            None => "unreadable",
+        };
+        log::error!("Server sent an {} move: {}", reason, text);
+        self.disconnect(&fill(self.text().bad_server_move, &[&text]));
+        false
+    }
+
+    /// Whether the local player may move the side whose turn it is.
+    fn can_move(&self) -> bool {
+        self.online
+            .as_ref()
+            .is_none_or(|online| online.seat.player() == Some(self.current_player))
+    }
+
+    fn apply_move(&mut self, mv: Move) {
+        let ((from_row, from_col), (to_row, to_col)) = (mv.from, mv.to);
+        let wxf = notation::to_wxf(&self.board, mv).unwrap_or_default();
+        let piece = self.board[from_row][from_col].take().unwrap();
+        let captured = self.board[to_row][to_col].replace(piece);
+        self.record.push(mv);
+        self.selected_piece = None;
+        self.current_player = match self.current_player {
+            Player::Red => Player::Black,
+            Player::Black => Player::Red,
+        };
+        self.update_endgame_verdict();
+
+        let t = self.text();
+        let captures = captured
+            .map(|c| fill(t.captures, &[&t.piece_type(c.piece_type)]))
+            .unwrap_or_default();
+        self.announcement = fill(
+            t.moved,
+            &[
+                &t.player(piece.player),
+                &t.piece_type(piece.piece_type),
+                &record::square_name(mv.from),
+                &record::square_name(mv.to),
+                &wxf,
+                &captures,
+            ],
+        );
     }
 
     fn handle_click(&mut self, row: usize, col: usize) {
         if let Some((selected_row, selected_col)) = self.selected_piece {
             // Try to move piece
-            if self.is_valid_move(selected_row, selected_col, row, col) {
-                self.board[row][col] = self.board[selected_row][selected_col].take();
-                self.current_player = match self.current_player {
-                    Player::Red => Player::Black,
-                    Player::Black => Player::Red,
-                };
+            if !self.try_move(Move::new((selected_row, selected_col), (row, col))) {
+                self.announcement = self.text().selection_cancelled.to_string();
             }
             self.selected_piece = None;
         } else if let Some(piece) = self.board[row][col] {
             // Select piece if it belongs to current player
-            if (piece.player == Player::Red && self.current_player == Player::Red)
-                || (piece.player == Player::Black && self.current_player == Player::Black)
+            if self.can_move()
+                && ((piece.player == Player::Red && self.current_player == Player::Red)
+                    || (piece.player == Player::Black && self.current_player == Player::Black))
             {
                 self.selected_piece = Some((row, col));
+                self.announcement = fill(self.text().selected, &[&self.describe_cursor()]);
             }
         }
     }
@@ -472,24 +1311,10 @@ impl ChineseChessApp {
         to_row: usize,
         to_col: usize,
     ) -> bool {
-        // Basic validation - implement proper Chinese chess rules here
-        if from_row == to_row && from_col == to_col {
-            return false;
-        }
-
-        if let Some(piece) = self.board[from_row][from_col] {
-            // Check if destination is empty or contains opponent's piece
-            if let Some(target_piece) = self.board[to_row][to_col] {
-                if target_piece.player == piece.player {
-                    return false;
-                }
-            }
-
-            // TODO: Implement proper movement rules for each piece type
-            // For now, allow any move for demonstration
-            return true;
-        }
-
-        false
+        rules::is_legal(
+            &self.board,
+            self.current_player,
+            Move::new((from_row, from_col), (to_row, to_col)),
+        )
     }
 }
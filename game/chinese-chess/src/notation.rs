@@ -0,0 +1,320 @@
+//! Typed move entry in ICCS ("h2e2") and WXF ("C2.5", "H8+7", "+R-1") notation.
+
+use crate::board::{Board, Piece, PieceType, Player, COLS, ROWS};
+use crate::record::Move;
+use anyhow::{anyhow, bail, Result};
+
+pub fn parse_move(board: &Board, player: Player, text: &str) -> Result<Move> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if text.is_empty() {
+        bail!("empty move");
+    }
+
+    match parse_iccs(&text) {
+        Some(mv) => Ok(mv),
+        None => parse_wxf(board, player, &text),
+    }
+}
+
+/// Parses "h2e2" or "h2-e2".
+pub fn parse_iccs(text: &str) -> Option<Move> {
+    let chars: Vec<char> = text
+        .chars()
+        .filter(|&c| c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if chars.len() != 4 {
+        return None;
+    }
+
+    let square = |file: char, rank: char| {
+        let col = (file as usize).checked_sub('a' as usize)?;
+        let rank = rank.to_digit(10)? as usize;
+        (col < COLS && rank < ROWS).then_some((ROWS - 1 - rank, col))
+    };
+    Some(Move::new(
+        square(chars[0], chars[1])?,
+        square(chars[2], chars[3])?,
+    ))
+}
+
+fn parse_wxf(board: &Board, player: Player, text: &str) -> Result<Move> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() != 4 {
+        bail!("cannot read move '{}'", text);
+    }
+
+    // Tandem pieces replace the file number with + (front) or - (rear), written either before
+    // or after the piece letter.
+    let (letter, locator) = if chars[0] == '+' || chars[0] == '-' {
+        (chars[1], chars[0])
+    } else {
+        (chars[0], chars[1])
+    };
+    let piece_type = piece_type_from_letter(letter)
+        .ok_or_else(|| anyhow!("unknown piece letter '{}'", letter))?;
+    let operator = chars[2];
+    let target = chars[3]
+        .to_digit(10)
+        .filter(|&d| (1..=9).contains(&d))
+        .ok_or_else(|| anyhow!("expected a digit 1-9 at the end of '{}'", text))?
+        as usize;
+
+    let piece = Piece::new(piece_type, player);
+    let candidates: Vec<(usize, usize)> = match locator {
+        '+' | '-' => {
+            let files: Vec<Vec<(usize, usize)>> = (0..COLS)
+                .map(|col| squares_on_file(board, piece, col))
+                .filter(|squares| squares.len() > 1)
+                .collect();
+            files
+                .into_iter()
+                .map(|squares| {
+                    // squares_on_file lists squares from the most advanced one backwards.
+                    if locator == '+' {
+                        squares[0]
+                    } else {
+                        squares[squares.len() - 1]
+                    }
+                })
+                .collect()
+        }
+        _ => {
+            let file = locator
+                .to_digit(10)
+                .filter(|&d| (1..=9).contains(&d))
+                .ok_or_else(|| anyhow!("expected a file number in '{}'", text))?;
+            squares_on_file(board, piece, file_to_col(file as usize, player))
+        }
+    };
+
+    let moves: Vec<Move> = candidates
+        .into_iter()
+        .filter_map(|from| destination(piece, from, operator, target).map(|to| Move::new(from, to)))
+        .filter(|mv| board[mv.to.0][mv.to.1].is_none_or(|p| p.player != player))
+        .collect();
+    match moves.as_slice() {
+        [mv] => Ok(*mv),
+        [] => bail!(
+            "no {} {} can play '{}'",
+            player.name(),
+            piece_type.name(),
+            text
+        ),
+        _ => bail!("'{}' is ambiguous", text),
+    }
+}
+
+/// Formats a move in WXF notation. `board` is the position before the move is played.
+pub fn to_wxf(board: &Board, mv: Move) -> Option<String> {
+    let piece = board[mv.from.0][mv.from.1]?;
+    let (from_row, from_col) = mv.from;
+    let (to_row, to_col) = mv.to;
+
+    let same_file = squares_on_file(board, piece, from_col);
+    let locator = if same_file.len() == 2 {
+        if same_file[0] == mv.from {
+            '+'
+        } else {
+            '-'
+        }
+    } else {
+        char::from_digit(col_to_file(from_col, piece.player) as u32, 10)?
+    };
+
+    let forward = match piece.player {
+        Player::Red => from_row > to_row,
+        Player::Black => from_row < to_row,
+    };
+    let (operator, target) = if from_row == to_row {
+        ('.', col_to_file(to_col, piece.player))
+    } else if is_straight_mover(piece.piece_type) && from_col == to_col {
+        (if forward { '+' } else { '-' }, from_row.abs_diff(to_row))
+    } else {
+        (
+            if forward { '+' } else { '-' },
+            col_to_file(to_col, piece.player),
+        )
+    };
+
+    Some(format!(
+        "{}{}{}{}",
+        piece_letter(piece.piece_type),
+        locator,
+        operator,
+        target
+    ))
+}
+
+fn destination(
+    piece: Piece,
+    from: (usize, usize),
+    operator: char,
+    target: usize,
+) -> Option<(usize, usize)> {
+    let (row, col) = (from.0 as i32, from.1 as i32);
+    let forward = match piece.player {
+        Player::Red => -1,
+        Player::Black => 1,
+    };
+    let sign = match operator {
+        '+' => forward,
+        '-' => -forward,
+        '.' | '=' => 0,
+        _ => return None,
+    };
+
+    let (to_row, to_col) = if sign == 0 {
+        if !is_straight_mover(piece.piece_type) {
+            return None;
+        }
+        (row, file_to_col(target, piece.player) as i32)
+    } else if is_straight_mover(piece.piece_type) {
+        (row + sign * target as i32, col)
+    } else {
+        let to_col = file_to_col(target, piece.player) as i32;
+        let rows = match (piece.piece_type, (to_col - col).abs()) {
+            (PieceType::Advisor, 1) => 1,
+            (PieceType::Elephant, 2) => 2,
+            (PieceType::Horse, 1) => 2,
+            (PieceType::Horse, 2) => 1,
+            _ => return None,
+        };
+        (row + sign * rows, to_col)
+    };
+
+    (to_row >= 0 && to_row < ROWS as i32 && to_col >= 0 && to_col < COLS as i32)
+        .then_some((to_row as usize, to_col as usize))
+}
+
+/// Squares on `col` holding `piece`, from the most advanced one backwards.
+fn squares_on_file(board: &Board, piece: Piece, col: usize) -> Vec<(usize, usize)> {
+    let mut squares: Vec<(usize, usize)> = (0..ROWS)
+        .filter(|&row| board[row][col] == Some(piece))
+        .map(|row| (row, col))
+        .collect();
+    if piece.player == Player::Black {
+        squares.reverse();
+    }
+    squares
+}
+
+// Files are numbered 1-9 from each player's own right-hand side.
+fn file_to_col(file: usize, player: Player) -> usize {
+    match player {
+        Player::Red => COLS - file,
+        Player::Black => file - 1,
+    }
+}
+
+fn col_to_file(col: usize, player: Player) -> usize {
+    match player {
+        Player::Red => COLS - col,
+        Player::Black => col + 1,
+    }
+}
+
+fn is_straight_mover(piece_type: PieceType) -> bool {
+    matches!(
+        piece_type,
+        PieceType::General | PieceType::Chariot | PieceType::Cannon | PieceType::Soldier
+    )
+}
+
+fn piece_letter(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::General => 'K',
+        PieceType::Advisor => 'A',
+        PieceType::Elephant => 'E',
+        PieceType::Horse => 'H',
+        PieceType::Chariot => 'R',
+        PieceType::Cannon => 'C',
+        PieceType::Soldier => 'P',
+    }
+}
+
+fn piece_type_from_letter(letter: char) -> Option<PieceType> {
+    match letter.to_ascii_uppercase() {
+        'K' | 'G' => Some(PieceType::General),
+        'A' => Some(PieceType::Advisor),
+        'E' | 'B' => Some(PieceType::Elephant),
+        'H' | 'N' => Some(PieceType::Horse),
+        'R' => Some(PieceType::Chariot),
+        'C' => Some(PieceType::Cannon),
+        'P' => Some(PieceType::Soldier),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Position;
+
+    #[test]
+    fn test_parse_iccs() {
+        let board = Position::default().board;
+        let mv = parse_move(&board, Player::Red, "h2e2").unwrap();
+        assert_eq!(mv, Move::new((7, 7), (7, 4)));
+        assert_eq!(parse_move(&board, Player::Red, "H2-E2").unwrap(), mv);
+        assert!(parse_iccs("j2e2").is_none());
+    }
+
+    #[test]
+    fn test_parse_wxf() {
+        let board = Position::default().board;
+        assert_eq!(
+            parse_move(&board, Player::Red, "C2.5").unwrap(),
+            Move::new((7, 7), (7, 4))
+        );
+        assert_eq!(
+            parse_move(&board, Player::Red, "H8+7").unwrap(),
+            Move::new((9, 1), (7, 2))
+        );
+        assert_eq!(
+            parse_move(&board, Player::Black, "H2+3").unwrap(),
+            Move::new((0, 1), (2, 2))
+        );
+        assert_eq!(
+            parse_move(&board, Player::Red, "R1+2").unwrap(),
+            Move::new((9, 8), (7, 8))
+        );
+        assert!(parse_move(&board, Player::Red, "E3+4").is_err());
+    }
+
+    #[test]
+    fn test_tandem_pieces() {
+        let board = Position::from_fen("4k4/9/9/9/9/4R4/9/4R4/9/3K5 w")
+            .unwrap()
+            .board;
+        assert_eq!(
+            parse_move(&board, Player::Red, "+R.1").unwrap(),
+            Move::new((5, 4), (5, 8))
+        );
+        assert_eq!(
+            parse_move(&board, Player::Red, "R--1").unwrap(),
+            Move::new((7, 4), (8, 4))
+        );
+        assert_eq!(
+            to_wxf(&board, Move::new((5, 4), (5, 8))).as_deref(),
+            Some("R+.1")
+        );
+    }
+
+    #[test]
+    fn test_to_wxf() {
+        let board = Position::default().board;
+        assert_eq!(
+            to_wxf(&board, Move::new((7, 7), (7, 4))).as_deref(),
+            Some("C2.5")
+        );
+        assert_eq!(
+            to_wxf(&board, Move::new((0, 1), (2, 2))).as_deref(),
+            Some("H2+3")
+        );
+        assert_eq!(
+            to_wxf(&board, Move::new((9, 8), (7, 8))).as_deref(),
+            Some("R1+2")
+        );
+    }
+}
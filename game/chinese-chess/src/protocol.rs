@@ -0,0 +1,142 @@
+//! Messages exchanged between the game server and its clients, one JSON object per WebSocket
+//! text frame.
+
+use crate::board::Player;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+/// How long a connection waits for an incoming frame before flushing queued outgoing ones.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Seat {
+    Red,
+    Black,
+    Spectator,
+}
+
+impl Seat {
+    pub fn of(player: Player) -> Self {
+        match player {
+            Player::Red => Seat::Red,
+            Player::Black => Seat::Black,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        self.player().map_or("Spectator", Player::name)
+    }
+
+    pub fn player(self) -> Option<Player> {
+        match self {
+            Seat::Red => Some(Player::Red),
+            Seat::Black => Some(Player::Black),
+            Seat::Spectator => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// A move in ICCS coordinates, e.g. "h2e2".
+    Move {
+        mv: String,
+    },
+    Resign,
+    /// Offers a draw, or accepts the opponent's pending offer.
+    OfferDraw,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Sent to a client once it has joined: its seat and the game so far.
+    Welcome {
+        room: String,
+        seat: Seat,
+        fen: String,
+        moves: Vec<String>,
+        red: Option<String>,
+        black: Option<String>,
+        result: Option<String>,
+    },
+    /// A player took a seat, or left it when `name` is `None`.
+    Seated {
+        seat: Seat,
+        name: Option<String>,
+    },
+    Moved {
+        mv: String,
+    },
+    DrawOffered {
+        by: Seat,
+    },
+    GameOver {
+        result: String,
+        reason: String,
+    },
+    /// Sent only to the client whose request was refused.
+    Rejected {
+        reason: String,
+    },
+}
+
+/// Runs a connection until the peer closes it or `outgoing` is dropped, sending everything
+/// queued on `outgoing` and handing each incoming text frame to `on_text`.
+pub fn pump<T: Serialize>(
+    ws: &mut WebSocket<TcpStream>,
+    outgoing: &Receiver<T>,
+    mut on_text: impl FnMut(&str),
+) -> Result<()> {
+    ws.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    loop {
+        loop {
+            match outgoing.try_recv() {
+                Ok(message) => ws.send(Message::text(serde_json::to_string(&message)?))?,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    ws.close(None)?;
+                    ws.flush()?;
+                    return Ok(());
+                }
+            }
+        }
+
+        match ws.read() {
+            Ok(Message::Text(text)) => on_text(text.as_str()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_json() {
+        let message = ClientMessage::Move {
+            mv: "h2e2".to_string(),
+        };
+        let json = serde_json::to_string(&message).unwrap();
+        assert_eq!(json, r#"{"type":"move","mv":"h2e2"}"#);
+        assert_eq!(
+            serde_json::from_str::<ClientMessage>(&json).unwrap(),
+            message
+        );
+
+        let message: ServerMessage =
+            serde_json::from_str(r#"{"type":"draw_offered","by":"Black"}"#).unwrap();
+        assert_eq!(message, ServerMessage::DrawOffered { by: Seat::Black });
+    }
+}
@@ -0,0 +1,330 @@
+use crate::board::{Player, Position, ROWS, START_FEN};
+use crate::setup::{Handicap, Setup};
+use crate::{notation, rules};
+use anyhow::{bail, Context, Result};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Move {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+}
+
+impl Move {
+    pub fn new(from: (usize, usize), to: (usize, usize)) -> Self {
+        Self { from, to }
+    }
+
+    /// ICCS coordinates, e.g. "h2e2".
+    pub fn iccs(&self) -> String {
+        format!("{}{}", square_name(self.from), square_name(self.to))
+    }
+}
+
+/// Names a square in ICCS style: files a-i from Red's left, ranks 0-9 from Red's side.
+pub fn square_name((row, col): (usize, usize)) -> String {
+    format!("{}{}", (b'a' + col as u8) as char, ROWS - 1 - row)
+}
+
+/// The moves of a game together with PGN-style tags describing how it started.
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub tags: Vec<(String, String)>,
+    pub start: Position,
+    pub moves: Vec<Move>,
+}
+
+impl GameRecord {
+    pub fn new(setup: &Setup, start: &Position) -> Self {
+        let mut record = Self {
+            tags: Vec::new(),
+            start: start.clone(),
+            moves: Vec::new(),
+        };
+        record.set_tag("Game", "Chinese Chess");
+        record.set_tag("Red", "?");
+        record.set_tag("Black", "?");
+        record.set_tag("Result", "*");
+        record.set_tag("Setup", &setup.description());
+        if let Setup::Handicap { handicap, giver } = setup {
+            if *handicap != Handicap::None {
+                record.set_tag("Handicap", handicap.name());
+                record.set_tag("HandicapGiver", giver.name());
+            }
+        }
+
+        let fen = start.to_fen();
+        if fen != START_FEN {
+            record.set_tag("SetUp", "1");
+            record.set_tag("FEN", &fen);
+        }
+        record
+    }
+
+    /// The setup the game started from, as `new` recorded it in the tags. A handicap is only
+    /// trusted while it still gives the recorded FEN; otherwise the FEN stands on its own.
+    pub fn setup(&self) -> Setup {
+        let Some(fen) = self.tag("FEN") else {
+            return Setup::Standard;
+        };
+        let handicap = Handicap::ALL
+            .into_iter()
+            .find(|handicap| Some(handicap.name()) == self.tag("Handicap"));
+        let giver = [Player::Red, Player::Black]
+            .into_iter()
+            .find(|giver| Some(giver.name()) == self.tag("HandicapGiver"));
+        if let (Some(handicap), Some(giver)) = (handicap, giver) {
+            let setup = Setup::Handicap { handicap, giver };
+            if setup
+                .position()
+                .is_ok_and(|position| position.to_fen() == fen)
+            {
+                return setup;
+            }
+        }
+        Setup::Custom {
+            fen: fen.to_string(),
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(key, _)| key == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn push(&mut self, mv: Move) {
+        self.moves.push(mv);
+    }
+
+    /// The number shown for the move about to be played, continuing from the start position's
+    /// full-move counter.
+    pub fn move_number(&self) -> u32 {
+        let black_first = self.start.side_to_move == Player::Black;
+        let plies = self.moves.len() as u32 + black_first as u32;
+        self.start.fullmove_number + plies / 2
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for (key, value) in &self.tags {
+            pgn.push_str(&format!("[{} \"{}\"]\n", key, value.replace('"', "\\\"")));
+        }
+        pgn.push('\n');
+
+        let mut number = self.start.fullmove_number;
+        let mut player = self.start.side_to_move;
+        let mut body = Vec::new();
+        for (i, mv) in self.moves.iter().enumerate() {
+            match player {
+                Player::Red => body.push(format!("{}.", number)),
+                Player::Black if i == 0 => body.push(format!("{}...", number)),
+                Player::Black => {}
+            }
+            body.push(mv.iccs());
+            if player == Player::Black {
+                number += 1;
+            }
+            player = player.opponent();
+        }
+        body.push(self.tag("Result").unwrap_or("*").to_string());
+        pgn.push_str(&body.join(" "));
+        pgn.push('\n');
+        pgn
+    }
+
+    /// Reads one game as written by `to_pgn`. Moves may also be in WXF notation, as many other
+    /// programs write them; every move is checked against the rules.
+    pub fn from_pgn(text: &str) -> Result<Self> {
+        let mut tags = Vec::new();
+        let mut movetext = String::new();
+        for line in text.lines().map(str::trim) {
+            if let Some(tag) = line.strip_prefix('[').and_then(|tag| tag.strip_suffix(']')) {
+                let (key, value) = tag
+                    .split_once(char::is_whitespace)
+                    .with_context(|| format!("malformed tag [{}]", tag))?;
+                let value = value.trim();
+                let value = value.strip_prefix('"').unwrap_or(value);
+                let value = value.strip_suffix('"').unwrap_or(value);
+                let value = value.replace("\\\"", "\"");
+                tags.push((key.to_string(), value));
+            } else {
+                movetext.push_str(line);
+                movetext.push('\n');
+            }
+        }
+
+        let start = match tags.iter().find(|(key, _)| key == "FEN") {
+            Some((_, fen)) => Position::from_fen(fen)?,
+            None => Position::default(),
+        };
+        let mut record = Self {
+            tags,
+            start: start.clone(),
+            moves: Vec::new(),
+        };
+
+        let mut position = start;
+        for token in strip_comments(&movetext).split_whitespace() {
+            if matches!(token, "*" | "1-0" | "0-1" | "1/2-1/2") {
+                continue;
+            }
+            // Move numbers: "1.", "30..." or glued to the move as in "1.h2e2"
+            let token = token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+            if token.is_empty() {
+                continue;
+            }
+
+            let ply = record.moves.len() + 1;
+            let mv = notation::parse_move(&position.board, position.side_to_move, token)
+                .with_context(|| format!("ply {}: {}", ply, token))?;
+            if !rules::is_legal(&position.board, position.side_to_move, mv) {
+                bail!("ply {}: illegal move {}", ply, token);
+            }
+            rules::play(&mut position, mv);
+            record.push(mv);
+        }
+        Ok(record)
+    }
+}
+
+/// Splits a file holding several games, each starting with its tags, and reads them one by one.
+pub fn parse_games(text: &str) -> Vec<Result<GameRecord>> {
+    let mut games = Vec::new();
+    let mut current = String::new();
+    let mut in_movetext = false;
+    for line in text.lines() {
+        let is_tag = line.trim_start().starts_with('[');
+        if is_tag && in_movetext {
+            games.push(std::mem::take(&mut current));
+            in_movetext = false;
+        }
+        in_movetext |= !is_tag && !line.trim().is_empty();
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.trim().is_empty() {
+        games.push(current);
+    }
+    games
+        .iter()
+        .map(|game| GameRecord::from_pgn(game))
+        .collect()
+}
+
+/// Drops `{...}` comments and `;` comments running to the end of the line.
+fn strip_comments(movetext: &str) -> String {
+    let mut stripped = String::new();
+    let mut depth = 0;
+    let mut line_comment = false;
+    for c in movetext.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            ';' if depth == 0 => line_comment = true,
+            '\n' => {
+                line_comment = false;
+                stripped.push(' ');
+            }
+            _ if depth == 0 && !line_comment => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handicap_record() {
+        let setup = Setup::Handicap {
+            handicap: Handicap::OneHorse,
+            giver: Player::Red,
+        };
+        let start = setup.position().unwrap();
+        let mut record = GameRecord::new(&setup, &start);
+        assert_eq!(record.tag("Handicap"), Some("One Horse"));
+        assert_eq!(record.tag("SetUp"), Some("1"));
+        assert_eq!(record.move_number(), 1);
+
+        record.push(Move::new((7, 7), (7, 4)));
+        assert_eq!(record.move_number(), 1);
+        record.push(Move::new((0, 7), (2, 6)));
+        assert_eq!(record.move_number(), 2);
+        assert!(record.to_pgn().ends_with("1. h2e2 h9g7 *\n"));
+    }
+
+    #[test]
+    fn test_setup_from_tags() {
+        let setup = Setup::Handicap {
+            handicap: Handicap::ChariotAndHorse,
+            giver: Player::Black,
+        };
+        let record = GameRecord::new(&setup, &setup.position().unwrap());
+        assert_eq!(record.setup(), setup);
+
+        let record = GameRecord::new(&Setup::Standard, &Position::default());
+        assert_eq!(record.setup(), Setup::Standard);
+
+        // A handicap tag that no longer matches the FEN is not trusted
+        let fen = "4k4/9/9/9/9/9/9/9/4R4/4K4 b - - 0 30".to_string();
+        let custom = Setup::Custom { fen: fen.clone() };
+        let mut record = GameRecord::new(&custom, &custom.position().unwrap());
+        record.set_tag("Handicap", Handicap::OneHorse.name());
+        record.set_tag("HandicapGiver", "Red");
+        assert_eq!(record.setup(), custom);
+    }
+
+    #[test]
+    fn test_black_to_move_numbering() {
+        let setup = Setup::Custom {
+            fen: "4k4/9/9/9/9/9/9/9/4R4/4K4 b - - 0 30".to_string(),
+        };
+        let start = setup.position().unwrap();
+        let mut record = GameRecord::new(&setup, &start);
+        assert_eq!(record.move_number(), 30);
+        record.push(Move::new((0, 4), (0, 3)));
+        assert_eq!(record.move_number(), 31);
+        record.push(Move::new((8, 4), (8, 3)));
+        assert!(record.to_pgn().ends_with("30... e9d9 31. e1d1 *\n"));
+    }
+
+    #[test]
+    fn test_pgn_round_trip() {
+        let setup = Setup::Custom {
+            fen: "4k4/9/9/9/9/9/9/9/4R4/4K4 b - - 0 30".to_string(),
+        };
+        let start = setup.position().unwrap();
+        let mut record = GameRecord::new(&setup, &start);
+        record.set_tag("Red", "Hu \"Ronghua\"");
+        record.push(Move::new((0, 4), (0, 3)));
+        record.push(Move::new((8, 4), (8, 3)));
+
+        let parsed = GameRecord::from_pgn(&record.to_pgn()).unwrap();
+        assert_eq!(parsed.tags, record.tags);
+        assert_eq!(parsed.start, record.start);
+        assert_eq!(parsed.moves, record.moves);
+    }
+
+    #[test]
+    fn test_parse_games() {
+        let text = "[Red \"A\"]\n[Result \"1-0\"]\n\n1. C2.5 {central cannon} H8+7 2. H2+3 1-0\n\n\
+                    [Red \"B\"]\n\n1. h2e2 h9g7 ; comment h0g2\n2. a0a5 *\n";
+        let games = parse_games(text);
+        assert_eq!(games.len(), 2);
+        let first = games[0].as_ref().unwrap();
+        assert_eq!(first.tag("Red"), Some("A"));
+        let moves: Vec<String> = first.moves.iter().map(Move::iccs).collect();
+        assert_eq!(moves, ["h2e2", "h9g7", "h0g2"]);
+        assert!(games[1].as_ref().unwrap_err().to_string().contains("ply 3"));
+    }
+}
@@ -0,0 +1,315 @@
+//! Xiangqi movement rules: pseudo-legal moves per piece, check detection (including the
+//! "flying general" rule) and legal move generation.
+
+use crate::board::{Board, Piece, PieceType, Player, Position, COLS, ROWS};
+use crate::record::Move;
+
+const ORTHOGONAL: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const DIAGONAL: [(i32, i32); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+// Horse jumps paired with the leg square that must be empty.
+const HORSE: [((i32, i32), (i32, i32)); 8] = [
+    ((-2, -1), (-1, 0)),
+    ((-2, 1), (-1, 0)),
+    ((2, -1), (1, 0)),
+    ((2, 1), (1, 0)),
+    ((-1, -2), (0, -1)),
+    ((1, -2), (0, -1)),
+    ((-1, 2), (0, 1)),
+    ((1, 2), (0, 1)),
+];
+
+pub fn offset((row, col): (usize, usize), (dr, dc): (i32, i32)) -> Option<(usize, usize)> {
+    let (row, col) = (row as i32 + dr, col as i32 + dc);
+    (row >= 0 && row < ROWS as i32 && col >= 0 && col < COLS as i32)
+        .then_some((row as usize, col as usize))
+}
+
+pub fn in_palace(player: Player, (row, col): (usize, usize)) -> bool {
+    let rows = match player {
+        Player::Red => 7..=9,
+        Player::Black => 0..=2,
+    };
+    rows.contains(&row) && (3..=5).contains(&col)
+}
+
+pub fn on_own_side(player: Player, row: usize) -> bool {
+    match player {
+        Player::Red => row >= ROWS / 2,
+        Player::Black => row < ROWS / 2,
+    }
+}
+
+pub fn forward(player: Player) -> i32 {
+    match player {
+        Player::Red => -1,
+        Player::Black => 1,
+    }
+}
+
+/// Destinations of the piece on `from`, ignoring whether its own General is left in check.
+pub fn pseudo_moves(board: &Board, from: (usize, usize)) -> Vec<(usize, usize)> {
+    let Some(piece) = board[from.0][from.1] else {
+        return Vec::new();
+    };
+    let player = piece.player;
+    let free =
+        |square: (usize, usize)| board[square.0][square.1].is_none_or(|p| p.player != player);
+    let empty = |square: (usize, usize)| board[square.0][square.1].is_none();
+
+    let mut moves = Vec::new();
+    match piece.piece_type {
+        PieceType::General => {
+            for step in ORTHOGONAL {
+                if let Some(to) = offset(from, step).filter(|&to| in_palace(player, to)) {
+                    if free(to) {
+                        moves.push(to);
+                    }
+                }
+            }
+        }
+        PieceType::Advisor => {
+            for step in DIAGONAL {
+                if let Some(to) = offset(from, step).filter(|&to| in_palace(player, to)) {
+                    if free(to) {
+                        moves.push(to);
+                    }
+                }
+            }
+        }
+        PieceType::Elephant => {
+            for (dr, dc) in DIAGONAL {
+                let Some(eye) = offset(from, (dr, dc)) else {
+                    continue;
+                };
+                if let Some(to) = offset(from, (dr * 2, dc * 2)) {
+                    if on_own_side(player, to.0) && empty(eye) && free(to) {
+                        moves.push(to);
+                    }
+                }
+            }
+        }
+        PieceType::Horse => {
+            for (jump, leg) in HORSE {
+                let Some(leg) = offset(from, leg) else {
+                    continue;
+                };
+                if let Some(to) = offset(from, jump) {
+                    if empty(leg) && free(to) {
+                        moves.push(to);
+                    }
+                }
+            }
+        }
+        PieceType::Chariot | PieceType::Cannon => {
+            let cannon = piece.piece_type == PieceType::Cannon;
+            for step in ORTHOGONAL {
+                let mut square = from;
+                let mut screened = false;
+                while let Some(to) = offset(square, step) {
+                    square = to;
+                    match board[to.0][to.1] {
+                        None if !screened => moves.push(to),
+                        None => {}
+                        Some(_) if cannon && !screened => screened = true,
+                        Some(target) => {
+                            if target.player != player {
+                                moves.push(to);
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        PieceType::Soldier => {
+            let mut steps = vec![(forward(player), 0)];
+            if !on_own_side(player, from.0) {
+                steps.extend([(0, -1), (0, 1)]);
+            }
+            for step in steps {
+                if let Some(to) = offset(from, step).filter(|&to| free(to)) {
+                    moves.push(to);
+                }
+            }
+        }
+    }
+    moves
+}
+
+pub fn find_general(board: &Board, player: Player) -> Option<(usize, usize)> {
+    let general = Some(Piece::new(PieceType::General, player));
+    let rows = match player {
+        Player::Red => 7..ROWS,
+        Player::Black => 0..3,
+    };
+    rows.flat_map(|row| (3..6).map(move |col| (row, col)))
+        .find(|&(row, col)| board[row][col] == general)
+}
+
+/// Whether `player`'s General is attacked, including by the opposing General along an open file.
+pub fn is_in_check(board: &Board, player: Player) -> bool {
+    let Some(general) = find_general(board, player) else {
+        return false;
+    };
+    let enemy = |square: (usize, usize), types: &[PieceType]| {
+        board[square.0][square.1]
+            .is_some_and(|p| p.player != player && types.contains(&p.piece_type))
+    };
+
+    // Chariots, cannons and the facing General along ranks and files
+    for step in ORTHOGONAL {
+        let mut square = general;
+        let mut screens = 0;
+        while let Some(next) = offset(square, step) {
+            square = next;
+            if board[square.0][square.1].is_none() {
+                continue;
+            }
+            let attackers: &[PieceType] = match screens {
+                0 if step.1 == 0 => &[PieceType::Chariot, PieceType::General],
+                0 => &[PieceType::Chariot],
+                _ => &[PieceType::Cannon],
+            };
+            if enemy(square, attackers) {
+                return true;
+            }
+            screens += 1;
+            if screens > 1 {
+                break;
+            }
+        }
+    }
+
+    // Horses: the leg is the square diagonally adjacent to the General, next to the horse.
+    for (dr, dc) in DIAGONAL {
+        let Some(leg) = offset(general, (dr, dc)) else {
+            continue;
+        };
+        if board[leg.0][leg.1].is_some() {
+            continue;
+        }
+        for jump in [(dr * 2, dc), (dr, dc * 2)] {
+            if offset(general, jump).is_some_and(|square| enemy(square, &[PieceType::Horse])) {
+                return true;
+            }
+        }
+    }
+
+    // Soldiers attack forwards and, once across the river, sideways.
+    let soldier_steps = [(-forward(player.opponent()), 0), (0, -1), (0, 1)];
+    for step in soldier_steps {
+        if offset(general, step).is_some_and(|square| enemy(square, &[PieceType::Soldier])) {
+            return true;
+        }
+    }
+    false
+}
+
+pub fn apply(board: &mut Board, mv: Move) -> Option<Piece> {
+    let piece = board[mv.from.0][mv.from.1].take();
+    std::mem::replace(&mut board[mv.to.0][mv.to.1], piece)
+}
+
+/// Plays `mv` on `position`, passing the turn and updating the move counters.
+pub fn play(position: &mut Position, mv: Move) -> Option<Piece> {
+    let captured = apply(&mut position.board, mv);
+    if captured.is_some() {
+        position.halfmove_clock = 0;
+    } else {
+        position.halfmove_clock += 1;
+    }
+    if position.side_to_move == Player::Black {
+        position.fullmove_number += 1;
+    }
+    position.side_to_move = position.side_to_move.opponent();
+    captured
+}
+
+pub fn is_legal(board: &Board, player: Player, mv: Move) -> bool {
+    if board[mv.from.0][mv.from.1].is_none_or(|p| p.player != player) {
+        return false;
+    }
+    if !pseudo_moves(board, mv.from).contains(&mv.to) {
+        return false;
+    }
+    let mut after = *board;
+    apply(&mut after, mv);
+    !is_in_check(&after, player)
+}
+
+pub fn legal_moves(board: &Board, player: Player) -> Vec<Move> {
+    let mut moves = Vec::new();
+    for row in 0..ROWS {
+        for col in 0..COLS {
+            if board[row][col].is_none_or(|p| p.player != player) {
+                continue;
+            }
+            for to in pseudo_moves(board, (row, col)) {
+                let mv = Move::new((row, col), to);
+                let mut after = *board;
+                apply(&mut after, mv);
+                if !is_in_check(&after, player) {
+                    moves.push(mv);
+                }
+            }
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_position_moves() {
+        let board = Position::default().board;
+        assert_eq!(legal_moves(&board, Player::Red).len(), 44);
+        assert_eq!(legal_moves(&board, Player::Black).len(), 44);
+        assert!(is_legal(&board, Player::Red, Move::new((7, 7), (7, 4))));
+        assert!(is_legal(&board, Player::Red, Move::new((7, 7), (0, 7))));
+        assert!(!is_legal(&board, Player::Red, Move::new((7, 7), (2, 7))));
+        assert!(is_legal(&board, Player::Red, Move::new((9, 2), (7, 4))));
+        assert!(!is_legal(&board, Player::Red, Move::new((9, 1), (8, 3))));
+    }
+
+    #[test]
+    fn test_flying_general() {
+        let board = Position::from_fen("4k4/9/9/9/9/9/9/9/9/3K5 w")
+            .unwrap()
+            .board;
+        assert!(!is_in_check(&board, Player::Red));
+        assert!(!is_legal(&board, Player::Red, Move::new((9, 3), (9, 4))));
+        assert!(is_legal(&board, Player::Red, Move::new((9, 3), (8, 3))));
+    }
+
+    #[test]
+    fn test_checks() {
+        let horse = Position::from_fen("4k4/9/3N5/9/9/9/9/9/9/3K5 b")
+            .unwrap()
+            .board;
+        assert!(is_in_check(&horse, Player::Black));
+        let cannon = Position::from_fen("4k4/9/4p4/9/4C4/9/9/9/9/3K5 b")
+            .unwrap()
+            .board;
+        assert!(is_in_check(&cannon, Player::Black));
+        let soldier = Position::from_fen("4k4/3P5/9/9/9/9/9/9/9/3K5 b")
+            .unwrap()
+            .board;
+        assert!(!is_in_check(&soldier, Player::Black));
+        let soldier = Position::from_fen("3Pk4/9/9/9/9/9/9/9/9/3K5 b")
+            .unwrap()
+            .board;
+        assert!(is_in_check(&soldier, Player::Black));
+    }
+
+    #[test]
+    fn test_checkmate() {
+        // Double chariot mate on the back rank
+        let board = Position::from_fen("R2k5/R8/9/9/9/9/9/9/9/4K4 b")
+            .unwrap()
+            .board;
+        assert!(legal_moves(&board, Player::Black).is_empty());
+        assert!(is_in_check(&board, Player::Black));
+    }
+}
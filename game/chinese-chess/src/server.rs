@@ -0,0 +1,555 @@
+//! Hosts games over HTTP and WebSocket.
+//!
+//! - `GET /rooms` lists the rooms as JSON.
+//! - `POST /rooms` creates a room, starting from the FEN in the body if there is one.
+//! - `GET /ws/<room>?name=<name>[&watch=1]` opens a WebSocket to a room, creating it if needed
+//!   (`new` picks a fresh room id). Players get the first free seat, everyone else watches.
+
+use crate::board::{Player, Position};
+use crate::notation;
+use crate::protocol::{self, ClientMessage, Seat, ServerMessage};
+use crate::record::Move;
+use crate::rules;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use tungstenite::protocol::Role;
+use tungstenite::WebSocket;
+
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+
+/// One game and the seats around it. The server applies every request to it, so clients never
+/// have to trust each other's boards.
+pub struct Room {
+    start: Position,
+    position: Position,
+    moves: Vec<Move>,
+    players: [Option<String>; 2],
+    draw_offer: Option<Player>,
+    result: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RoomSummary {
+    pub room: String,
+    pub red: Option<String>,
+    pub black: Option<String>,
+    pub moves: usize,
+    pub result: Option<String>,
+}
+
+impl Room {
+    pub fn new(start: Position) -> Self {
+        Self {
+            position: start.clone(),
+            start,
+            moves: Vec::new(),
+            players: [None, None],
+            draw_offer: None,
+            result: None,
+        }
+    }
+
+    fn player_mut(&mut self, player: Player) -> &mut Option<String> {
+        match player {
+            Player::Red => &mut self.players[0],
+            Player::Black => &mut self.players[1],
+        }
+    }
+
+    /// Seats `name` at the first free side, or as a spectator when watching or when both sides
+    /// are taken.
+    pub fn join(&mut self, name: &str, watch: bool) -> Seat {
+        if !watch {
+            for player in [Player::Red, Player::Black] {
+                let seat = self.player_mut(player);
+                if seat.is_none() {
+                    *seat = Some(name.to_string());
+                    return Seat::of(player);
+                }
+            }
+        }
+        Seat::Spectator
+    }
+
+    pub fn leave(&mut self, seat: Seat) {
+        if let Some(player) = seat.player() {
+            *self.player_mut(player) = None;
+        }
+    }
+
+    pub fn welcome(&self, room: &str, seat: Seat) -> ServerMessage {
+        ServerMessage::Welcome {
+            room: room.to_string(),
+            seat,
+            fen: self.start.to_fen(),
+            moves: self.moves.iter().map(Move::iccs).collect(),
+            red: self.players[0].clone(),
+            black: self.players[1].clone(),
+            result: self.result.clone(),
+        }
+    }
+
+    pub fn summary(&self, room: &str) -> RoomSummary {
+        RoomSummary {
+            room: room.to_string(),
+            red: self.players[0].clone(),
+            black: self.players[1].clone(),
+            moves: self.moves.len(),
+            result: self.result.clone(),
+        }
+    }
+
+    /// Applies a request from `seat`, returning the messages for everyone in the room.
+    pub fn handle(&mut self, seat: Seat, message: ClientMessage) -> Result<Vec<ServerMessage>> {
+        let Some(player) = seat.player() else {
+            bail!("spectators cannot play");
+        };
+        if self.result.is_some() {
+            bail!("the game is over");
+        }
+
+        match message {
+            ClientMessage::Move { mv } => {
+                if player != self.position.side_to_move {
+                    bail!("it is {}'s turn", self.position.side_to_move.name());
+                }
+                let parsed = notation::parse_iccs(&mv).ok_or_else(|| anyhow!("bad move {}", mv))?;
+                if !rules::is_legal(&self.position.board, player, parsed) {
+                    bail!("illegal move {}", mv);
+                }
+
+                rules::play(&mut self.position, parsed);
+                self.moves.push(parsed);
+                self.draw_offer = None;
+                let mut messages = vec![ServerMessage::Moved { mv: parsed.iccs() }];
+
+                // Having no legal move loses in Xiangqi, whether or not the General is in check
+                let next = self.position.side_to_move;
+                if rules::legal_moves(&self.position.board, next).is_empty() {
+                    let reason = if rules::is_in_check(&self.position.board, next) {
+                        "checkmate"
+                    } else {
+                        "stalemate"
+                    };
+                    messages.push(self.finish(Some(player), reason));
+                }
+                Ok(messages)
+            }
+            ClientMessage::Resign => Ok(vec![self.finish(Some(player.opponent()), "resignation")]),
+            ClientMessage::OfferDraw => match self.draw_offer {
+                Some(offered) if offered != player => Ok(vec![self.finish(None, "agreement")]),
+                _ => {
+                    self.draw_offer = Some(player);
+                    Ok(vec![ServerMessage::DrawOffered { by: seat }])
+                }
+            },
+        }
+    }
+
+    fn finish(&mut self, winner: Option<Player>, reason: &str) -> ServerMessage {
+        let result = match winner {
+            Some(Player::Red) => "1-0",
+            Some(Player::Black) => "0-1",
+            None => "1/2-1/2",
+        };
+        self.result = Some(result.to_string());
+        ServerMessage::GameOver {
+            result: result.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+struct HostedRoom {
+    room: Room,
+    clients: Vec<(u64, Sender<ServerMessage>)>,
+}
+
+impl HostedRoom {
+    fn broadcast(&self, message: &ServerMessage) {
+        for (_, client) in &self.clients {
+            // A client that has gone away is removed when its session ends
+            let _ = client.send(message.clone());
+        }
+    }
+}
+
+#[derive(Default)]
+struct Lobby {
+    rooms: BTreeMap<String, HostedRoom>,
+    next_room: u64,
+    next_client: u64,
+}
+
+impl Lobby {
+    fn create_room(&mut self, id: Option<&str>, start: Position) -> String {
+        let id = match id {
+            Some(id) => id.to_string(),
+            None => loop {
+                self.next_room += 1;
+                let id = self.next_room.to_string();
+                if !self.rooms.contains_key(&id) {
+                    break id;
+                }
+            },
+        };
+        self.rooms.entry(id.clone()).or_insert_with(|| HostedRoom {
+            room: Room::new(start),
+            clients: Vec::new(),
+        });
+        id
+    }
+}
+
+/// Accepts connections until the listener fails, serving each one on its own thread.
+pub fn serve(listener: TcpListener) -> Result<()> {
+    log::info!("Serving games on {}", listener.local_addr()?);
+    let lobby = Arc::new(Mutex::new(Lobby::default()));
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let lobby = lobby.clone();
+        std::thread::spawn(move || {
+            let peer = stream.peer_addr().ok();
+            if let Err(e) = handle_connection(&lobby, stream) {
+                log::warn!("Connection from {:?} failed: {:#}", peer, e);
+            }
+        });
+    }
+    Ok(())
+}
+
+struct Request {
+    method: String,
+    path: String,
+    websocket_key: Option<String>,
+    body: Vec<u8>,
+}
+
+fn read_request(stream: &mut TcpStream) -> Result<Request> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0; 1024];
+    loop {
+        let read = stream.read(&mut chunk)?;
+        if read == 0 {
+            bail!("connection closed before the request was complete");
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+
+        let mut headers = [httparse::EMPTY_HEADER; 32];
+        let mut request = httparse::Request::new(&mut headers);
+        let httparse::Status::Complete(head_len) = request.parse(&buffer)? else {
+            if buffer.len() > MAX_REQUEST_BYTES {
+                bail!("request too large");
+            }
+            continue;
+        };
+
+        let header = |name: &str| {
+            request
+                .headers
+                .iter()
+                .find(|header| header.name.eq_ignore_ascii_case(name))
+                .and_then(|header| std::str::from_utf8(header.value).ok())
+        };
+        let content_length: usize = header("Content-Length")
+            .map(|len| len.trim().parse())
+            .transpose()?
+            .unwrap_or(0);
+        if content_length > MAX_REQUEST_BYTES {
+            bail!("request too large");
+        }
+        let request = Request {
+            method: request.method.unwrap_or_default().to_string(),
+            path: request.path.unwrap_or_default().to_string(),
+            websocket_key: header("Sec-WebSocket-Key").map(str::to_string),
+            body: Vec::new(),
+        };
+
+        let mut body = buffer.split_off(head_len);
+        if body.len() < content_length {
+            let start = body.len();
+            body.resize(content_length, 0);
+            stream.read_exact(&mut body[start..])?;
+        }
+        body.truncate(content_length);
+        return Ok(Request { body, ..request });
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+fn handle_connection(lobby: &Mutex<Lobby>, mut stream: TcpStream) -> Result<()> {
+    let request = read_request(&mut stream)?;
+    log::debug!("{} {}", request.method, request.path);
+    let (path, query) = request
+        .path
+        .split_once('?')
+        .unwrap_or((request.path.as_str(), ""));
+
+    match (request.method.as_str(), path) {
+        ("GET", "/rooms") => {
+            let lobby = lobby.lock().unwrap();
+            let rooms: Vec<RoomSummary> = lobby
+                .rooms
+                .iter()
+                .map(|(id, hosted)| hosted.room.summary(id))
+                .collect();
+            respond(&mut stream, "200 OK", &serde_json::to_string(&rooms)?)
+        }
+        ("POST", "/rooms") => {
+            let fen = String::from_utf8(request.body)?;
+            let start = match fen.trim() {
+                "" => Position::default(),
+                fen => match Position::from_fen(fen) {
+                    Ok(position) => position,
+                    Err(e) => {
+                        let error = serde_json::json!({ "error": format!("{:#}", e) });
+                        return respond(&mut stream, "400 Bad Request", &error.to_string());
+                    }
+                },
+            };
+            let id = lobby.lock().unwrap().create_room(None, start);
+            log::info!("Created room {}", id);
+            let body = serde_json::json!({ "room": id });
+            respond(&mut stream, "201 Created", &body.to_string())
+        }
+        ("GET", path) if path.starts_with("/ws/") => {
+            let key = request
+                .websocket_key
+                .context("missing Sec-WebSocket-Key header")?;
+            write!(
+                stream,
+                "HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\nUpgrade: websocket\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                tungstenite::handshake::derive_accept_key(key.as_bytes())
+            )?;
+            let ws = WebSocket::from_raw_socket(stream, Role::Server, None);
+
+            let room = decode_component(&path["/ws/".len()..]);
+            let mut name = "?".to_string();
+            let mut watch = false;
+            for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+                match key {
+                    "name" if !value.is_empty() => name = decode_component(value),
+                    "watch" => watch = value == "1" || value == "true",
+                    _ => {}
+                }
+            }
+            run_session(lobby, ws, &room, &name, watch)
+        }
+        _ => respond(&mut stream, "404 Not Found", r#"{"error":"not found"}"#),
+    }
+}
+
+fn run_session(
+    lobby: &Mutex<Lobby>,
+    mut ws: WebSocket<TcpStream>,
+    room: &str,
+    name: &str,
+    watch: bool,
+) -> Result<()> {
+    let (sender, outgoing) = mpsc::channel();
+    let (room, client, seat) = {
+        let mut lobby = lobby.lock().unwrap();
+        lobby.next_client += 1;
+        let client = lobby.next_client;
+        let requested = (room != "new").then_some(room);
+        let room = lobby.create_room(requested, Position::default());
+        let hosted = lobby.rooms.get_mut(&room).unwrap();
+
+        let seat = hosted.room.join(name, watch);
+        sender.send(hosted.room.welcome(&room, seat))?;
+        if seat != Seat::Spectator {
+            hosted.broadcast(&ServerMessage::Seated {
+                seat,
+                name: Some(name.to_string()),
+            });
+        }
+        hosted.clients.push((client, sender.clone()));
+        (room, client, seat)
+    };
+    log::info!("{} joined room {} as {:?}", name, room, seat);
+
+    let result = protocol::pump(&mut ws, &outgoing, |text| {
+        let mut lobby = lobby.lock().unwrap();
+        let Some(hosted) = lobby.rooms.get_mut(&room) else {
+            return;
+        };
+        let handled = serde_json::from_str(text)
+            .map_err(anyhow::Error::from)
+            .and_then(|message| hosted.room.handle(seat, message));
+        match handled {
+            Ok(messages) => messages
+                .iter()
+                .for_each(|message| hosted.broadcast(message)),
+            Err(e) => {
+                let _ = sender.send(ServerMessage::Rejected {
+                    reason: format!("{:#}", e),
+                });
+            }
+        }
+    });
+
+    let mut lobby = lobby.lock().unwrap();
+    if let Some(hosted) = lobby.rooms.get_mut(&room) {
+        hosted.clients.retain(|(id, _)| *id != client);
+        if seat != Seat::Spectator {
+            hosted.room.leave(seat);
+            hosted.broadcast(&ServerMessage::Seated { seat, name: None });
+        }
+    }
+    log::info!("{} left room {}", name, room);
+    result
+}
+
+/// Undoes the percent-encoding of a path segment or query value.
+fn decode_component(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 3 <= bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{Connection, Event};
+    use std::time::{Duration, Instant};
+
+    fn play(room: &mut Room, seat: Seat, mv: &str) -> Result<Vec<ServerMessage>> {
+        room.handle(seat, ClientMessage::Move { mv: mv.to_string() })
+    }
+
+    #[test]
+    fn test_room_rules() {
+        let mut room = Room::new(Position::default());
+        assert_eq!(room.join("alice", false), Seat::Red);
+        assert_eq!(room.join("bob", false), Seat::Black);
+        assert_eq!(room.join("carol", false), Seat::Spectator);
+
+        assert!(play(&mut room, Seat::Black, "h7e7").is_err());
+        assert!(play(&mut room, Seat::Spectator, "h2e2").is_err());
+        assert!(play(&mut room, Seat::Red, "a0a5").is_err());
+        let moved = play(&mut room, Seat::Red, "h2e2").unwrap();
+        assert_eq!(moved, vec![ServerMessage::Moved { mv: "h2e2".into() }]);
+
+        room.handle(Seat::Red, ClientMessage::OfferDraw).unwrap();
+        let over = room.handle(Seat::Black, ClientMessage::OfferDraw).unwrap();
+        assert!(matches!(&over[0], ServerMessage::GameOver { result, .. } if result == "1/2-1/2"));
+        assert!(play(&mut room, Seat::Black, "h7e7").is_err());
+
+        room.leave(Seat::Red);
+        assert_eq!(room.join("dave", false), Seat::Red);
+    }
+
+    #[test]
+    fn test_checkmate_ends_game() {
+        let start = Position::from_fen("3k5/R8/9/9/9/9/9/9/8R/4K4 w").unwrap();
+        let mut room = Room::new(start);
+        let messages = play(&mut room, Seat::Red, "i1i9").unwrap();
+        assert_eq!(
+            messages[1],
+            ServerMessage::GameOver {
+                result: "1-0".into(),
+                reason: "checkmate".into()
+            }
+        );
+    }
+
+    fn wait_for(connection: &Connection, mut matches: impl FnMut(&ServerMessage) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline {
+            for event in connection.poll() {
+                match event {
+                    Event::Message(message) if matches(&message) => return,
+                    Event::Message(_) => {}
+                    Event::Disconnected(reason) => panic!("disconnected: {}", reason),
+                }
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("timed out waiting for a message");
+    }
+
+    #[test]
+    fn test_localhost_game() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || serve(listener));
+
+        let red = Connection::open(&address, "office", "alice", false, || {});
+        wait_for(&red, |m| {
+            matches!(
+                m,
+                ServerMessage::Welcome {
+                    seat: Seat::Red,
+                    ..
+                }
+            )
+        });
+        let black = Connection::open(&address, "office", "bob smith", false, || {});
+        wait_for(&black, |m| {
+            matches!(
+                m,
+                ServerMessage::Welcome {
+                    seat: Seat::Black,
+                    ..
+                }
+            )
+        });
+        wait_for(
+            &red,
+            |m| matches!(m, ServerMessage::Seated { seat: Seat::Black, name: Some(name) } if name == "bob smith"),
+        );
+
+        red.send(ClientMessage::Move { mv: "h2e2".into() });
+        wait_for(
+            &black,
+            |m| matches!(m, ServerMessage::Moved { mv } if mv == "h2e2"),
+        );
+
+        // A spectator joining late gets the game so far, then follows it live
+        let watcher = Connection::open(&address, "office", "carol", true, || {});
+        wait_for(
+            &watcher,
+            |m| matches!(m, ServerMessage::Welcome { seat: Seat::Spectator, moves, .. } if moves == &["h2e2"]),
+        );
+        black.send(ClientMessage::Move { mv: "h2e2".into() });
+        wait_for(&black, |m| matches!(m, ServerMessage::Rejected { .. }));
+        black.send(ClientMessage::Resign);
+        wait_for(
+            &watcher,
+            |m| matches!(m, ServerMessage::GameOver { result, .. } if result == "1-0"),
+        );
+    }
+}
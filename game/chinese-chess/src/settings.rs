@@ -0,0 +1,120 @@
+use crate::board::{COLS, ROWS};
+use crate::i18n::Language;
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum BoardTheme {
+    #[default]
+    Wood,
+    Dark,
+    Jade,
+    Paper,
+}
+
+impl BoardTheme {
+    pub const ALL: [BoardTheme; 4] = [
+        BoardTheme::Wood,
+        BoardTheme::Dark,
+        BoardTheme::Jade,
+        BoardTheme::Paper,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BoardTheme::Wood => "Wood",
+            BoardTheme::Dark => "Dark",
+            BoardTheme::Jade => "Jade",
+            BoardTheme::Paper => "Paper",
+        }
+    }
+
+    pub fn background(self) -> Color32 {
+        match self {
+            BoardTheme::Wood => Color32::from_rgb(210, 180, 140),
+            BoardTheme::Dark => Color32::from_rgb(50, 50, 50),
+            BoardTheme::Jade => Color32::from_rgb(150, 200, 170),
+            BoardTheme::Paper => Color32::from_rgb(245, 240, 225),
+        }
+    }
+
+    pub fn lines(self) -> Color32 {
+        match self {
+            BoardTheme::Dark => Color32::from_gray(160),
+            _ => Color32::BLACK,
+        }
+    }
+}
+
+/// Appearance options, persisted between runs through eframe's storage.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub language: Language,
+    pub theme: BoardTheme,
+    /// Directory holding one sub-directory of SVGs per piece set.
+    pub piece_sets_dir: String,
+    /// Selected sub-directory of `piece_sets_dir`, or `None` for the bundled pieces.
+    pub piece_set: Option<String>,
+    /// Show Black at the bottom of the board.
+    pub flipped: bool,
+    /// Draw pieces as their traditional characters instead of the SVG artwork.
+    pub character_pieces: bool,
+    /// Font with Chinese glyphs; empty to look in the usual system locations.
+    pub font_path: String,
+    /// Directory of generated endgame tables.
+    pub tablebase_dir: String,
+    /// SQLite file of archived games.
+    pub database_path: String,
+    /// Game server to play online on, as host:port.
+    pub server_address: String,
+    pub player_name: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: Language::default(),
+            theme: BoardTheme::default(),
+            piece_sets_dir: String::new(),
+            piece_set: None,
+            flipped: false,
+            character_pieces: false,
+            font_path: String::new(),
+            tablebase_dir: "tablebase".to_string(),
+            database_path: "games.sqlite".to_string(),
+            server_address: "127.0.0.1:9000".to_string(),
+            player_name: String::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn available_piece_sets(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(self.piece_sets_dir.trim()) else {
+            return Vec::new();
+        };
+        let mut sets: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        sets.sort();
+        sets
+    }
+
+    pub fn piece_set_path(&self) -> Option<PathBuf> {
+        let set = self.piece_set.as_ref()?;
+        Some(PathBuf::from(self.piece_sets_dir.trim()).join(set))
+    }
+
+    /// Maps a board square to the square it is drawn on, and back.
+    pub fn view_square(&self, (row, col): (usize, usize)) -> (usize, usize) {
+        if self.flipped {
+            (ROWS - 1 - row, COLS - 1 - col)
+        } else {
+            (row, col)
+        }
+    }
+}
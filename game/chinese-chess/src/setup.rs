@@ -0,0 +1,162 @@
+use crate::board::{PieceType, Player, Position, COLS, ROWS};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Material given up by the stronger side before the game starts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Handicap {
+    #[default]
+    None,
+    OneHorse,
+    OneChariot,
+    TwoHorses,
+    ChariotAndHorse,
+    TwoChariots,
+    AllPieces,
+}
+
+impl Handicap {
+    pub const ALL: [Handicap; 7] = [
+        Handicap::None,
+        Handicap::OneHorse,
+        Handicap::OneChariot,
+        Handicap::TwoHorses,
+        Handicap::ChariotAndHorse,
+        Handicap::TwoChariots,
+        Handicap::AllPieces,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Handicap::None => "No handicap",
+            Handicap::OneHorse => "One Horse",
+            Handicap::OneChariot => "One Chariot",
+            Handicap::TwoHorses => "Both Horses",
+            Handicap::ChariotAndHorse => "Chariot and Horse",
+            Handicap::TwoChariots => "Both Chariots",
+            Handicap::AllPieces => "Nine pieces",
+        }
+    }
+
+    /// Back-rank files emptied by this handicap, seen from the giving side's left.
+    fn removed_files(self) -> &'static [usize] {
+        match self {
+            Handicap::None => &[],
+            Handicap::OneHorse => &[1],
+            Handicap::OneChariot => &[0],
+            Handicap::TwoHorses => &[1, 7],
+            Handicap::ChariotAndHorse => &[0, 1],
+            Handicap::TwoChariots => &[0, 8],
+            // Everything on the back rank except the General.
+            Handicap::AllPieces => &[0, 1, 2, 3, 5, 6, 7, 8],
+        }
+    }
+
+    pub fn apply(self, position: &mut Position, giver: Player) {
+        let row = match giver {
+            Player::Red => ROWS - 1,
+            Player::Black => 0,
+        };
+        for &file in self.removed_files() {
+            // Black's left is Red's right, so mirror the file for Black.
+            let col = match giver {
+                Player::Red => file,
+                Player::Black => COLS - 1 - file,
+            };
+            if let Some(piece) = position.board[row][col] {
+                if piece.player == giver && piece.piece_type != PieceType::General {
+                    position.board[row][col] = None;
+                }
+            }
+        }
+    }
+}
+
+/// How the pieces are arranged when a new game starts.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub enum Setup {
+    #[default]
+    Standard,
+    Handicap {
+        handicap: Handicap,
+        giver: Player,
+    },
+    Custom {
+        fen: String,
+    },
+}
+
+impl Setup {
+    pub fn load_custom(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read setup file {:?}", path))?;
+        let fen = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .with_context(|| format!("no FEN found in {:?}", path))?;
+        Position::from_fen(fen)?;
+        Ok(Setup::Custom {
+            fen: fen.to_string(),
+        })
+    }
+
+    pub fn position(&self) -> Result<Position> {
+        match self {
+            Setup::Standard => Ok(Position::default()),
+            Setup::Handicap { handicap, giver } => {
+                let mut position = Position::default();
+                handicap.apply(&mut position, *giver);
+                Ok(position)
+            }
+            Setup::Custom { fen } => Position::from_fen(fen),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Setup::Standard
+            | Setup::Handicap {
+                handicap: Handicap::None,
+                ..
+            } => "Standard".to_string(),
+            Setup::Handicap { handicap, giver } => {
+                format!("{} gives {}", giver.name(), handicap.name())
+            }
+            Setup::Custom { .. } => "Custom".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handicap_position() {
+        let setup = Setup::Handicap {
+            handicap: Handicap::TwoHorses,
+            giver: Player::Red,
+        };
+        let position = setup.position().unwrap();
+        assert!(position.board[9][1].is_none());
+        assert!(position.board[9][7].is_none());
+        assert!(position.board[0][1].is_some());
+        assert_eq!(
+            position.to_fen(),
+            "rnbakabnr/9/1c5c1/p1p1p1p1p/9/9/P1P1P1P1P/1C5C1/9/R1BAKAB1R w - - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_handicap_mirrors_for_black() {
+        let setup = Setup::Handicap {
+            handicap: Handicap::ChariotAndHorse,
+            giver: Player::Black,
+        };
+        let position = setup.position().unwrap();
+        assert!(position.board[0][8].is_none());
+        assert!(position.board[0][7].is_none());
+        assert!(position.board[0][0].is_some());
+    }
+}
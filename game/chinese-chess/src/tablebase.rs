@@ -0,0 +1,641 @@
+//! Endgame tablebases for small material signatures such as "KRvKAA".
+//!
+//! Tables are built by retrograde analysis: positions without legal moves are lost (checkmate
+//! and stalemate both lose in Xiangqi), and results are propagated backwards one ply at a time
+//! through un-moves. Captures lead into smaller tables, which are generated first. Positions
+//! never resolved are draws; repetition rules (perpetual check/chase) are not modelled.
+
+use crate::board::{Board, Piece, PieceType, Player, COLS, ROWS};
+use crate::record::Move;
+use crate::rules;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+pub const MAX_PIECES: usize = 5;
+const MAX_ENTRIES: usize = 64 * 1024 * 1024;
+const MAGIC: &[u8; 4] = b"XTB1";
+const EXTENSION: &str = "xtb";
+
+// Stored values: DRAW, INVALID, or FIRST_RESULT + plies to mate. An even number of plies means
+// the side to move is mated, an odd number means it mates.
+const DRAW: u16 = 0;
+const INVALID: u16 = 1;
+const FIRST_RESULT: u16 = 2;
+
+/// Result for the side to move, counted in plies until mate.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    Win(u16),
+    Loss(u16),
+    Draw,
+}
+
+impl Verdict {
+    fn from_value(value: u16) -> Option<Self> {
+        match value {
+            DRAW => Some(Verdict::Draw),
+            INVALID => None,
+            _ => {
+                let plies = value - FIRST_RESULT;
+                Some(if plies % 2 == 1 {
+                    Verdict::Win(plies)
+                } else {
+                    Verdict::Loss(plies)
+                })
+            }
+        }
+    }
+}
+
+/// The material on the board: Red's pieces, then Black's, strongest first.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Signature {
+    pieces: Vec<Piece>,
+}
+
+impl Signature {
+    /// Parses names like "KRvKAA"; the letters follow WXF notation.
+    pub fn parse(name: &str) -> Result<Self> {
+        let Some((red, black)) = name.split_once(['v', 'V']) else {
+            bail!("signature '{}' must look like KRvKAA", name);
+        };
+        let mut pieces = Vec::new();
+        for (letters, player) in [(red, Player::Red), (black, Player::Black)] {
+            for letter in letters.chars() {
+                let Some(piece_type) = piece_type_from_letter(letter) else {
+                    bail!("unknown piece letter '{}' in '{}'", letter, name);
+                };
+                pieces.push(Piece::new(piece_type, player));
+            }
+        }
+        Self::new(pieces)
+    }
+
+    pub fn of(board: &Board) -> Result<Self> {
+        Self::new(board.iter().flatten().flatten().copied().collect())
+    }
+
+    fn new(mut pieces: Vec<Piece>) -> Result<Self> {
+        pieces.sort_by_key(|p| (p.player as u8, strength_order(p.piece_type)));
+        for player in [Player::Red, Player::Black] {
+            let generals = pieces
+                .iter()
+                .filter(|p| p.player == player && p.piece_type == PieceType::General)
+                .count();
+            if generals != 1 {
+                bail!("{} needs exactly one General", player.name());
+            }
+        }
+        if pieces.len() > MAX_PIECES {
+            bail!("tablebases support at most {} pieces", MAX_PIECES);
+        }
+        Ok(Self { pieces })
+    }
+
+    pub fn name(&self) -> String {
+        let letters = |player: Player| -> String {
+            self.pieces
+                .iter()
+                .filter(|p| p.player == player)
+                .map(|p| piece_letter(p.piece_type))
+                .collect()
+        };
+        format!("{}v{}", letters(Player::Red), letters(Player::Black))
+    }
+
+    /// Signatures reachable by capturing one piece.
+    fn captures(&self) -> Vec<Signature> {
+        let mut result: Vec<Signature> = Vec::new();
+        for (i, piece) in self.pieces.iter().enumerate() {
+            if piece.piece_type == PieceType::General {
+                continue;
+            }
+            let mut pieces = self.pieces.clone();
+            pieces.remove(i);
+            let signature = Signature { pieces };
+            if !result.contains(&signature) {
+                result.push(signature);
+            }
+        }
+        result
+    }
+}
+
+/// Win/draw/loss with distance for every placement of one signature's pieces, both sides to
+/// move.
+pub struct Table {
+    signature: Signature,
+    // Squares each piece may legally stand on, and the reverse lookup square -> domain index.
+    domains: Vec<Vec<(usize, usize)>>,
+    lookup: Vec<[Option<u8>; ROWS * COLS]>,
+    values: Vec<u16>,
+}
+
+impl Table {
+    fn empty(signature: Signature) -> Result<Self> {
+        let domains: Vec<Vec<(usize, usize)>> =
+            signature.pieces.iter().map(|&p| domain(p)).collect();
+        let lookup = domains
+            .iter()
+            .map(|squares| {
+                let mut lookup = [None; ROWS * COLS];
+                for (i, &(row, col)) in squares.iter().enumerate() {
+                    lookup[row * COLS + col] = Some(i as u8);
+                }
+                lookup
+            })
+            .collect();
+        let len = domains
+            .iter()
+            .try_fold(2usize, |len, squares| len.checked_mul(squares.len()))
+            .filter(|&len| len <= MAX_ENTRIES);
+        let Some(len) = len else {
+            bail!("{} is too large to generate", signature.name());
+        };
+        Ok(Self {
+            signature,
+            domains,
+            lookup,
+            values: vec![DRAW; len],
+        })
+    }
+
+    pub fn probe(&self, board: &Board, side: Player) -> Option<Verdict> {
+        let index = self.index(board, side)?;
+        Verdict::from_value(self.values[index])
+    }
+
+    fn index(&self, board: &Board, side: Player) -> Option<usize> {
+        let mut squares = [None; MAX_PIECES];
+        for (row, rank) in board.iter().enumerate() {
+            for (col, square) in rank.iter().enumerate() {
+                let Some(piece) = *square else {
+                    continue;
+                };
+                // Identical pieces may fill their slots in any order.
+                let slot = (0..self.signature.pieces.len()).find(|&slot| {
+                    squares[slot].is_none() && self.signature.pieces[slot] == piece
+                })?;
+                squares[slot] = Some(self.lookup[slot][row * COLS + col]?);
+            }
+        }
+
+        let mut index = 0;
+        for (slot, squares_in_domain) in self.domains.iter().enumerate() {
+            index = index * squares_in_domain.len() + squares[slot]? as usize;
+        }
+        Some(index * 2 + side as usize)
+    }
+
+    /// Slot squares and side to move of an index.
+    fn decode(&self, index: usize) -> ([u8; MAX_PIECES], Player) {
+        let side = if index.is_multiple_of(2) {
+            Player::Red
+        } else {
+            Player::Black
+        };
+        let mut rest = index / 2;
+        let mut squares = [0; MAX_PIECES];
+        for slot in (0..self.domains.len()).rev() {
+            let len = self.domains[slot].len();
+            squares[slot] = (rest % len) as u8;
+            rest /= len;
+        }
+        (squares, side)
+    }
+
+    fn encode(&self, squares: &[u8; MAX_PIECES], side: Player) -> usize {
+        let mut index = 0;
+        for (slot, squares_in_domain) in self.domains.iter().enumerate() {
+            index = index * squares_in_domain.len() + squares[slot] as usize;
+        }
+        index * 2 + side as usize
+    }
+
+    fn board(&self, squares: &[u8; MAX_PIECES]) -> Option<Board> {
+        let mut board: Board = [[None; COLS]; ROWS];
+        for (slot, &piece) in self.signature.pieces.iter().enumerate() {
+            let (row, col) = self.domains[slot][squares[slot] as usize];
+            if board[row][col].is_some() {
+                return None;
+            }
+            board[row][col] = Some(piece);
+        }
+        Some(board)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let name = self.signature.name();
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[name.len() as u8])?;
+        writer.write_all(name.as_bytes())?;
+        writer.write_all(&(self.values.len() as u64).to_le_bytes())?;
+        for value in &self.values {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            bail!("{:?} is not a tablebase file", path);
+        }
+
+        let mut len = [0; 1];
+        reader.read_exact(&mut len)?;
+        let mut name = vec![0; len[0] as usize];
+        reader.read_exact(&mut name)?;
+        let mut table = Self::empty(Signature::parse(&String::from_utf8(name)?)?)?;
+
+        let mut count = [0; 8];
+        reader.read_exact(&mut count)?;
+        if u64::from_le_bytes(count) != table.values.len() as u64 {
+            bail!("{:?} has the wrong number of entries", path);
+        }
+        let mut bytes = vec![0; table.values.len() * 2];
+        reader.read_exact(&mut bytes)?;
+        for (value, chunk) in table.values.iter_mut().zip(bytes.chunks_exact(2)) {
+            *value = u16::from_le_bytes([chunk[0], chunk[1]]);
+        }
+        Ok(table)
+    }
+}
+
+/// A directory of table files, loaded on demand.
+pub struct Tablebase {
+    dir: PathBuf,
+    tables: HashMap<Signature, Option<Table>>,
+}
+
+impl Tablebase {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            tables: HashMap::new(),
+        }
+    }
+
+    fn path(&self, signature: &Signature) -> PathBuf {
+        self.dir.join(format!("{}.{}", signature.name(), EXTENSION))
+    }
+
+    /// Exact verdict for `side` to move, if the position's table has been generated.
+    pub fn probe(&mut self, board: &Board, side: Player) -> Option<Verdict> {
+        let signature = Signature::of(board).ok()?;
+        let path = self.path(&signature);
+        let table = self.tables.entry(signature).or_insert_with(|| {
+            if !path.exists() {
+                return None;
+            }
+            Table::load(&path)
+                .inspect_err(|e| log::warn!("Failed to load tablebase {:?}: {:#}", path, e))
+                .ok()
+        });
+        table.as_ref()?.probe(board, side)
+    }
+
+    /// Generates the table for `signature` and every table it captures into, writing each to the
+    /// tablebase directory. Tables already on disk are reused.
+    pub fn generate(&mut self, signature: &Signature) -> Result<()> {
+        if matches!(self.tables.get(signature), Some(Some(_))) {
+            return Ok(());
+        }
+        let path = self.path(signature);
+        if path.exists() {
+            let table = Table::load(&path)?;
+            self.tables.insert(signature.clone(), Some(table));
+            return Ok(());
+        }
+
+        let captures = signature.captures();
+        for sub in &captures {
+            self.generate(sub)?;
+        }
+
+        log::info!("Generating {}", signature.name());
+        let table = self.retrograde(signature)?;
+        std::fs::create_dir_all(&self.dir)?;
+        table
+            .save(&path)
+            .with_context(|| format!("failed to write {:?}", path))?;
+        log::info!("Wrote {:?}", path);
+        self.tables.insert(signature.clone(), Some(table));
+        Ok(())
+    }
+
+    fn retrograde(&self, signature: &Signature) -> Result<Table> {
+        let mut table = Table::empty(signature.clone())?;
+        let len = table.values.len();
+        // Moves not yet known to lose, and the longest resistance among the losing ones.
+        let mut remaining = vec![0u8; len];
+        let mut longest = vec![0u16; len];
+        let mut buckets: Vec<Vec<u32>> = Vec::new();
+        let push = |buckets: &mut Vec<Vec<u32>>, plies: usize, index: usize| {
+            if buckets.len() <= plies {
+                buckets.resize(plies + 1, Vec::new());
+            }
+            buckets[plies].push(index as u32);
+        };
+
+        for index in 0..len {
+            let (squares, side) = table.decode(index);
+            let Some(board) = table.board(&squares) else {
+                table.values[index] = INVALID;
+                continue;
+            };
+            if rules::is_in_check(&board, side.opponent()) {
+                table.values[index] = INVALID;
+                continue;
+            }
+
+            let moves = rules::legal_moves(&board, side);
+            let mut count = 0u8;
+            for mv in &moves {
+                if board[mv.to.0][mv.to.1].is_none() {
+                    count += 1;
+                    continue;
+                }
+                let mut child = board;
+                rules::apply(&mut child, *mv);
+                match self.probe_loaded(&child, side.opponent()) {
+                    Verdict::Loss(plies) => {
+                        count += 1;
+                        push(&mut buckets, plies as usize + 1, index);
+                    }
+                    Verdict::Win(plies) => {
+                        longest[index] = longest[index].max(plies + 1);
+                    }
+                    Verdict::Draw => count += 1,
+                }
+            }
+            remaining[index] = count;
+            if count == 0 {
+                push(&mut buckets, longest[index] as usize, index);
+            }
+        }
+
+        let mut plies = 0;
+        while plies < buckets.len() {
+            let bucket = std::mem::take(&mut buckets[plies]);
+            for index in bucket {
+                let index = index as usize;
+                if table.values[index] != DRAW {
+                    continue;
+                }
+                table.values[index] = FIRST_RESULT + plies as u16;
+
+                let lost = plies % 2 == 0;
+                for previous in table.predecessors(index) {
+                    if table.values[previous] != DRAW {
+                        continue;
+                    }
+                    if lost {
+                        push(&mut buckets, plies + 1, previous);
+                    } else {
+                        remaining[previous] -= 1;
+                        longest[previous] = longest[previous].max(plies as u16 + 1);
+                        if remaining[previous] == 0 {
+                            push(&mut buckets, longest[previous] as usize, previous);
+                        }
+                    }
+                }
+            }
+            plies += 1;
+        }
+        Ok(table)
+    }
+
+    fn probe_loaded(&self, board: &Board, side: Player) -> Verdict {
+        Signature::of(board)
+            .ok()
+            .and_then(|signature| self.tables.get(&signature)?.as_ref()?.probe(board, side))
+            .unwrap_or(Verdict::Draw)
+    }
+}
+
+impl Table {
+    /// Indices of the positions that reach `index` with one non-capturing move.
+    fn predecessors(&self, index: usize) -> Vec<usize> {
+        let (squares, side) = self.decode(index);
+        let Some(board) = self.board(&squares) else {
+            return Vec::new();
+        };
+        let mover = side.opponent();
+
+        let mut result = Vec::new();
+        for (slot, &piece) in self.signature.pieces.iter().enumerate() {
+            if piece.player != mover {
+                continue;
+            }
+            let to = self.domains[slot][squares[slot] as usize];
+            for from in unmoves(&board, to, piece) {
+                let Some(domain_index) = self.lookup[slot][from.0 * COLS + from.1] else {
+                    continue;
+                };
+                let mut previous = squares;
+                previous[slot] = domain_index;
+                let previous = self.encode(&previous, mover);
+                // Only keep un-moves whose forward move is legal in the earlier position.
+                let mut before = board;
+                rules::apply(&mut before, Move::new(to, from));
+                if rules::is_legal(&before, mover, Move::new(from, to)) {
+                    result.push(previous);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Squares the piece on `to` could have come from with a non-capturing move.
+fn unmoves(board: &Board, to: (usize, usize), piece: Piece) -> Vec<(usize, usize)> {
+    let empty = |square: (usize, usize)| board[square.0][square.1].is_none();
+    let mut result = Vec::new();
+    match piece.piece_type {
+        PieceType::Horse => {
+            // The leg sits next to the starting square, so pseudo_moves from `to` would test
+            // the wrong one.
+            for (dr, dc) in [
+                (-2, -1),
+                (-2, 1),
+                (2, -1),
+                (2, 1),
+                (-1, -2),
+                (1, -2),
+                (-1, 2),
+                (1, 2),
+            ] {
+                let Some(from) = rules::offset(to, (-dr, -dc)) else {
+                    continue;
+                };
+                let leg = if i32::abs(dr) == 2 {
+                    (dr / 2, 0)
+                } else {
+                    (0, dc / 2)
+                };
+                if empty(from) && rules::offset(from, leg).is_some_and(empty) {
+                    result.push(from);
+                }
+            }
+        }
+        PieceType::Soldier => {
+            let back = (-rules::forward(piece.player), 0);
+            result.extend(rules::offset(to, back).filter(|&from| empty(from)));
+            if !rules::on_own_side(piece.player, to.0) {
+                for side_step in [(0, -1), (0, 1)] {
+                    result.extend(rules::offset(to, side_step).filter(|&from| empty(from)));
+                }
+            }
+        }
+        // The remaining pieces move symmetrically, so their quiet moves are their un-moves.
+        _ => {
+            result.extend(
+                rules::pseudo_moves(board, to)
+                    .into_iter()
+                    .filter(|&from| empty(from)),
+            );
+        }
+    }
+    result
+}
+
+fn domain(piece: Piece) -> Vec<(usize, usize)> {
+    let all = (0..ROWS).flat_map(|row| (0..COLS).map(move |col| (row, col)));
+    let mirror = |squares: Vec<(usize, usize)>| -> Vec<(usize, usize)> {
+        match piece.player {
+            Player::Red => squares,
+            Player::Black => squares
+                .into_iter()
+                .map(|(row, col)| (ROWS - 1 - row, col))
+                .collect(),
+        }
+    };
+    match piece.piece_type {
+        PieceType::General => all.filter(|&s| rules::in_palace(piece.player, s)).collect(),
+        PieceType::Advisor => mirror(vec![(7, 3), (7, 5), (8, 4), (9, 3), (9, 5)]),
+        PieceType::Elephant => mirror(vec![(5, 2), (5, 6), (7, 0), (7, 4), (7, 8), (9, 2), (9, 6)]),
+        PieceType::Soldier => all
+            .filter(|&(row, col)| {
+                if rules::on_own_side(piece.player, row) {
+                    // Before crossing the river a soldier stays on its starting files and the
+                    // two ranks in front of its side's back area.
+                    let starting = match piece.player {
+                        Player::Red => 5..=6,
+                        Player::Black => 3..=4,
+                    };
+                    starting.contains(&row) && col % 2 == 0
+                } else {
+                    true
+                }
+            })
+            .collect(),
+        PieceType::Horse | PieceType::Chariot | PieceType::Cannon => all.collect(),
+    }
+}
+
+fn strength_order(piece_type: PieceType) -> u8 {
+    match piece_type {
+        PieceType::General => 0,
+        PieceType::Chariot => 1,
+        PieceType::Horse => 2,
+        PieceType::Cannon => 3,
+        PieceType::Advisor => 4,
+        PieceType::Elephant => 5,
+        PieceType::Soldier => 6,
+    }
+}
+
+fn piece_letter(piece_type: PieceType) -> char {
+    match piece_type {
+        PieceType::General => 'K',
+        PieceType::Advisor => 'A',
+        PieceType::Elephant => 'E',
+        PieceType::Horse => 'H',
+        PieceType::Chariot => 'R',
+        PieceType::Cannon => 'C',
+        PieceType::Soldier => 'P',
+    }
+}
+
+fn piece_type_from_letter(letter: char) -> Option<PieceType> {
+    match letter.to_ascii_uppercase() {
+        'K' | 'G' => Some(PieceType::General),
+        'A' => Some(PieceType::Advisor),
+        'E' | 'B' => Some(PieceType::Elephant),
+        'H' | 'N' => Some(PieceType::Horse),
+        'R' => Some(PieceType::Chariot),
+        'C' => Some(PieceType::Cannon),
+        'P' => Some(PieceType::Soldier),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Position;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("chinese-chess-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_signature() {
+        let signature = Signature::parse("KAAvKR").unwrap();
+        assert_eq!(signature.name(), "KAAvKR");
+        assert_eq!(Signature::parse("kravk").unwrap().name(), "KRAvK");
+        assert!(Signature::parse("KRvR").is_err());
+        assert!(Signature::parse("KRRRvKR").is_err());
+
+        let board = Position::from_fen("4k4/9/9/9/9/9/9/9/4A4/3K1R3 w")
+            .unwrap()
+            .board;
+        assert_eq!(Signature::of(&board).unwrap().name(), "KRAvK");
+    }
+
+    #[test]
+    fn test_chariot_wins_against_bare_general() {
+        let dir = temp_dir("krvk");
+        let mut tablebase = Tablebase::new(&dir);
+        tablebase
+            .generate(&Signature::parse("KRvK").unwrap())
+            .unwrap();
+        assert!(dir.join("KRvK.xtb").exists());
+        assert!(dir.join("KvK.xtb").exists());
+
+        // Mate in one: the chariot checks along the file while the generals guard the rest
+        let board = Position::from_fen("9/9/3k5/9/9/8R/9/9/9/4K4 w")
+            .unwrap()
+            .board;
+        assert_eq!(tablebase.probe(&board, Player::Red), Some(Verdict::Win(1)));
+
+        // Black to move can capture the chariot with the general
+        let board = Position::from_fen("3k5/3R5/9/9/9/9/9/9/9/4K4 b")
+            .unwrap()
+            .board;
+        assert_eq!(tablebase.probe(&board, Player::Black), Some(Verdict::Draw));
+
+        // Every legal KRvK position without an immediate capture is won for Red
+        let mut reloaded = Tablebase::new(&dir);
+        let board = Position::from_fen("4k4/9/9/9/9/9/9/9/9/R2K5 b")
+            .unwrap()
+            .board;
+        assert!(matches!(
+            reloaded.probe(&board, Player::Black),
+            Some(Verdict::Loss(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}